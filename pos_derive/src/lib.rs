@@ -0,0 +1,134 @@
+//! Derives the database plumbing for a model, so adding one is a single `#[derive(DbObject)]`.
+//!
+//! ```ignore
+//! #[derive(DbObject)]
+//! #[db(table = "gift_shop_sales")]
+//! pub struct GiftShopSale {
+//!     #[db(rename = "item_description")]
+//!     description: String,
+//!     #[db(with = "crate::database::codec::text")]
+//!     kind: Kind,
+//!     #[db(skip)]
+//!     cached_total: f32,
+//! }
+//! ```
+//!
+//! Every stored field needs `HasSchema + ToSql + FromColumn`, or a `with` codec: a module with
+//! `fn to_sql(&T) -> impl Mappable` and `fn from_sql(Value) -> anyhow::Result<T>`. Skipped fields are never written
+//! and come back as `Default::default()`.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Error, Fields, LitStr, Path};
+
+#[proc_macro_derive(DbObject, attributes(db))]
+pub fn derive_db_object(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input).unwrap_or_else(Error::into_compile_error).into()
+}
+
+#[derive(Default)]
+struct FieldOptions {
+    rename: Option<String>,
+    skip: bool,
+    with: Option<Path>,
+}
+
+fn table_name(input: &DeriveInput) -> syn::Result<String> {
+    let mut table = None;
+    for attr in input.attrs.iter().filter(|x| x.path().is_ident("db")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                table = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("expected `table = \"...\"`"))
+            }
+        })?;
+    }
+
+    table.ok_or_else(|| Error::new_spanned(&input.ident, "DbObject needs #[db(table = \"...\")]"))
+}
+
+fn field_options(field: &syn::Field) -> syn::Result<FieldOptions> {
+    let mut options = FieldOptions::default();
+    for attr in field.attrs.iter().filter(|x| x.path().is_ident("db")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                options.rename = Some(meta.value()?.parse::<LitStr>()?.value());
+            } else if meta.path.is_ident("skip") {
+                options.skip = true;
+            } else if meta.path.is_ident("with") {
+                options.with = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+            } else {
+                return Err(meta.error("expected `rename = \"...\"`, `skip` or `with = \"path::to::codec\"`"));
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(options)
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let table = table_name(&input)?;
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(name, "DbObject can only be derived for structs"));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(Error::new_spanned(name, "DbObject needs named fields"));
+    };
+
+    let mut add_fields = Vec::new();
+    let mut read_fields = Vec::new();
+    for field in &fields.named {
+        let options = field_options(field)?;
+        let ident = field.ident.as_ref().expect("named fields have names");
+        let ty = &field.ty;
+        if options.skip {
+            read_fields.push(quote! { #ident: ::core::default::Default::default() });
+            continue;
+        }
+
+        let column = options.rename.unwrap_or_else(|| ident.to_string());
+        let (to_sql, from_sql) = match &options.with {
+            Some(codec) => (
+                quote! { #codec::to_sql(&self.#ident) },
+                quote! { #codec::from_sql(value) },
+            ),
+            None => (
+                quote! { ::core::clone::Clone::clone(&self.#ident) },
+                quote! { <#ty as crate::database::from_sql::FromColumn>::from_column(value) },
+            ),
+        };
+
+        add_fields.push(quote! { .add_field(#column, #to_sql) });
+        read_fields.push(quote! {
+            #ident: {
+                let value = crate::database::from_sql::take_column(&mut row, #column)?;
+                anyhow::Context::with_context(#from_sql, || format!("reading {}.{}", #table, #column))?
+            }
+        });
+    }
+
+    Ok(quote! {
+        impl crate::database::database_object::CanBuildObjectMapper for #name {
+            const TABLE_NAME: &'static str = #table;
+
+            fn build_object_mapper(&self) -> crate::database::object_mapper::ObjectMapper {
+                crate::database::object_mapper::ObjectMapper::new(Self::TABLE_NAME)
+                    #(#add_fields)*
+            }
+        }
+
+        impl crate::database::from_sql::FromSql for #name {
+            fn from_sql(mut row: sqlite::Row) -> anyhow::Result<Self> {
+                Ok(Self {
+                    #(#read_fields,)*
+                })
+            }
+        }
+    })
+}
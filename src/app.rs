@@ -1,73 +1,541 @@
 use std::fmt::Display;
-use chrono::{Datelike, Local};
+use std::path::PathBuf;
+use std::time::Duration;
+use chrono::Local;
 use iced::advanced::Widget;
 use iced::alignment::Horizontal;
-use iced::{Element, Length};
-use iced::widget::{button, container, horizontal_rule, scrollable, text};
+use iced::{Element, Length, Subscription};
+use iced::keyboard::{Key, Modifiers};
+use iced::widget::{button, container, horizontal_rule, pick_list, row, scrollable, text, text_input, Row};
 use iced_aw::selection_list;
 use log::{error, info};
+use strum::VariantArray;
 use crate::{HEADER_SIZE, RULE_HEIGHT, TEXT_SIZE};
-use crate::app::Message::{CartSelection, CommitToDb, DeleteSelected, RenderDailyReport};
-use crate::database::Database;
-use crate::database::database_object::CanBuildObjectMapper;
-use crate::model::as_transaction_record::AsTransactionRecord;
+use crate::app::Message::{CartSelection, CashierChanged, CommitToDb, DeleteSelected, ExportCsv, ExportXlsx, RemovalReasonChanged, RenderReport, ShowScreen};
+use crate::database::{Database, AUDIT_LOG_PAGE};
+use crate::decimal_input::DecimalInput;
+use crate::model::audit_entry::{AuditAction, AuditEntry};
 use crate::model::cart_item::CartItem;
-use crate::model::date_time_wrapper::WrapInDateTime;
+use crate::model::date_time_wrapper::DateTimeWrapper;
+use crate::model::gift_shop_sale::DEFAULT_SALES_TAX;
 use crate::model::has_total_cost::HasTotalCost;
+use crate::model::payment_method::PaymentMethod;
+use crate::model::quick_sale::QuickSale;
+use crate::model::unfinished_cart::ParkedCart;
+use crate::report::{csv_export, xlsx_export};
+use crate::report::comparison::Comparison;
+use crate::report::day_close::DayReport;
+use crate::report::period::Period;
+use crate::report::{receipt, summary_export};
 use crate::sale_screen::SaleScreen;
+use crate::settings::Settings;
 use crate::to_model::ToModel;
+use crate::view::about;
 use crate::view::adapters::ff;
+use crate::view::audit_log::AuditLog;
+use crate::view::backups::Backups;
+use crate::view::comparison::Comparisons;
+use crate::view::period_picker::PeriodPicker;
+use crate::view::history::History;
+use crate::view::quick_sales::QuickSales;
+use crate::view::reports::{Format, Reports};
 use crate::view::summary_dicts::SummaryDicts;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Screen {
+    #[default]
+    Sales,
+    Reports,
+    History,
+    AuditLog,
+    Compare,
+    Backups,
+    QuickSales,
+    About,
+}
+
 pub struct App {
+    screen: Screen,
     sale_screen: SaleScreen,
     database: Database,
+    settings: Settings,
     error: Option<anyhow::Error>,
+    /// Whoever typed their name in at the till, for the audit log. Anyone can type any name, so it is not a login and
+    /// grants nothing.
+    cashier: String,
     cart: Vec<CartItem>,
     stringified_cart: Vec<String>,
+    /// Says so when the cart was left unfinished last time and restored, until it is saved or emptied.
+    cart_status: String,
+    park_label: String,
+    parked_carts: Vec<ParkedCart>,
+    quick_sales: Vec<QuickSale>,
+    /// How quick sale buttons without a payment method of their own are paid for.
+    quick_sale_payment: Option<PaymentMethod>,
+    quick_sale_editor: QuickSales,
     selected_index: Option<usize>,
+    removal_reason: String,
+    audit_log: AuditLog,
+    audit_entries: Vec<DateTimeWrapper<AuditEntry>>,
+    /// Whether the audit log goes back further than `audit_entries`.
+    older_audit_entries: bool,
+    report_period: PeriodPicker,
+    backups: Backups,
+    comparisons: Comparisons,
+    reports: Reports,
+    history: History,
+    counted_cash: DecimalInput,
+    confirming_close: bool,
+    day_status: String,
 }
 
 type SaleMessage = crate::sale_screen::Message;
+type AuditLogMessage = crate::view::audit_log::Message;
+type PeriodPickerMessage = crate::view::period_picker::Message;
+type BackupsMessage = crate::view::backups::Message;
+type ComparisonsMessage = crate::view::comparison::Message;
+type ReportsMessage = crate::view::reports::Message;
+type HistoryMessage = crate::view::history::Message;
+type QuickSalesMessage = crate::view::quick_sales::Message;
+type DecimalInputMessage = crate::decimal_input::Message;
 #[derive(Debug, Clone)]
 pub enum Message {
+    ShowScreen(Screen),
     SaleMessage(SaleMessage),
-    RenderDailyReport,
+    AuditLogMessage(AuditLogMessage),
+    ReportPeriod(PeriodPickerMessage),
+    Backups(BackupsMessage),
+    Comparisons(ComparisonsMessage),
+    Reports(ReportsMessage),
+    History(HistoryMessage),
+    QuickSales(QuickSalesMessage),
+    /// The backup timer went off.
+    AutomaticBackup,
+    /// Another minute passed, so the business day may have ended.
+    ClockTick,
+    RenderReport(Period),
+    ExportCsv(Period),
+    ExportXlsx(Period),
+    CountedCash(DecimalInputMessage),
+    PrintXReport,
+    CloseDay,
+    ConfirmCloseDay,
+    CancelCloseDay,
+    CashierChanged(String),
     CartSelection(usize),
+    RemovalReasonChanged(String),
     DeleteSelected,
+    ParkLabelChanged(String),
+    ParkCart,
+    RecallCart(String),
+    QuickSalePayment(PaymentMethod),
+    /// A quick sale button, by its place in the grid, was pressed or its key typed.
+    QuickSale(usize),
+    DismissError,
     CommitToDb
 }
 
-impl App {    
-    fn transactionify_and_insert<T>(&mut self, object: T) -> anyhow::Result<()> where
-        T: CanBuildObjectMapper+AsTransactionRecord+WrapInDateTime + 'static,
-    {
-        self.database.insert(object.as_transaction_record().wrapped_in_date_time())?;
-        self.database.insert(object.wrapped_in_date_time())
+impl App {
+    fn refresh_backups(&mut self) {
+        match self.database.backups(&self.settings.backups) {
+            Ok(backups) => self.backups.set_backups(backups),
+            Err(err) => {
+                error!("Error listing backups: {}", err);
+                self.backups.set_status(format!("Unable to list backups: {err:#}"));
+            }
+        }
     }
 
-    /*
-    self.transactionify_and_insert(self.sale_screen.admission().clone())
-self.transactionify_and_insert(self.sale_screen.donation().clone())
-self.transactionify_and_insert(self.sale_screen.membership().clone())
-self.transactionify_and_insert(self.sale_screen.gift_shop_sale().clone())
-    */
+    /// Reads the next page of the audit log after the entries already read.
+    fn read_older_audit_entries(&mut self) {
+        match self.database.audit_log(self.audit_entries.len()) {
+            Ok(entries) => {
+                self.older_audit_entries = entries.len() == AUDIT_LOG_PAGE;
+                self.audit_entries.extend(entries);
+            }
+            Err(err) => {
+                error!("Error reading the audit log: {}", err);
+                self.error = Some(err);
+            }
+        }
+    }
 
-    fn add_to_cart<T:Into<CartItem>+Display, S: ToModel<ModelType=T>>(&mut self, item: S) -> anyhow::Result<()> {
-        let item = item.to_model()?;
-        self.stringified_cart.push(item.to_string());
-        self.cart.push(item.into());
+    fn back_up(&mut self) {
+        match self.database.rotate_backups(&self.settings.backups) {
+            Ok(path) => self.backups.set_status(format!("Backed up to {}", path.display())),
+            Err(err) => {
+                error!("Error backing up: {:#}", err);
+                self.backups.set_status(format!("Backup failed: {err:#}"));
+                self.error = Some(err);
+            }
+        }
+        self.refresh_backups();
+    }
+
+    fn restore(&mut self, path: PathBuf) {
+        match self.database.restore_from(&path) {
+            Ok(replaced) => {
+                info!("Restored the database from {}", path.display());
+                self.backups.set_status(format!("Restored {}. What was replaced is in {}", path.display(), replaced.display()));
+                self.log_audit(AuditEntry::new(
+                    &self.cashier, AuditAction::RestoreBackup, path.display().to_string(),
+                    format!("replaced data saved to {}", replaced.display()),
+                ));
+                // The restored database kept whatever cart it had, and the one on screen is what counts.
+                self.save_cart();
+                self.refresh_parked_carts();
+            }
+            Err(err) => {
+                error!("Error restoring {}: {:#}", path.display(), err);
+                self.backups.set_status(format!("Unable to restore {}: {err:#}", path.display()));
+                self.error = Some(err);
+            }
+        }
+        self.refresh_backups();
+    }
+
+    /// Writes an X- or Z-report into the report directory and audits the export. Returns where it went.
+    fn write_day_report(&mut self, report: &DayReport) -> anyhow::Result<PathBuf> {
+        let directory = self.settings.report_directory();
+        let filename = directory.join(report.file_name());
+        std::fs::create_dir_all(&directory)?;
+        std::fs::write(&filename, report.render_to_html()?)?;
+
+        info!("Exported file to: {}", filename.display());
+        self.log_audit(AuditEntry::new(&self.cashier, AuditAction::Export, filename.display().to_string(), report.title()));
+        Ok(filename)
+    }
+
+    fn export_comparison(&mut self) {
+        let Some(comparison) = self.comparisons.comparison() else { return; };
+        let directory = self.settings.report_directory();
+        let filename = directory.join(format!("{}_comparison.html", comparison.file_stem()));
+        let written = self.database.chain_hash()
+            .and_then(|chain_hash| comparison.render_to_html(&chain_hash))
+            .and_then(|html| {
+                std::fs::create_dir_all(&directory)?;
+                Ok(std::fs::write(&filename, html)?)
+            });
+        if let Err(err) = written {
+            error!("Error exporting comparison: {}", err);
+            self.comparisons.set_status(format!("Unable to export the comparison: {err:#}"));
+            self.error = Some(err);
+            return;
+        }
+
+        info!("Exported file to: {}", filename.display());
+        self.comparisons.set_status(format!("Saved to {}", filename.display()));
+        self.log_audit(AuditEntry::new(&self.cashier, AuditAction::Export, filename.display().to_string(), "comparison report"));
+    }
+
+    /// Writes whatever the reports screen is showing, either a summary or the sales behind one of its numbers, into the
+    /// report directory. Returns where it went and what it was.
+    fn write_shown_report(&self, format: Format) -> anyhow::Result<(PathBuf, String)> {
+        let Some(report) = self.reports.report() else {
+            return Err(anyhow::anyhow!("no report is being shown"));
+        };
+        let directory = self.settings.report_directory();
+        std::fs::create_dir_all(&directory)?;
+
+        if let Some(drill_down) = self.reports.drill_down() {
+            let filename = directory.join(format!("{}.{}", drill_down.file_stem(), format.extension()));
+            match format {
+                Format::Html => std::fs::write(&filename, drill_down.render_to_html(&self.database.chain_hash()?)?)?,
+                Format::Csv => drill_down.write_csv(std::fs::File::create(&filename)?)?,
+                Format::Xlsx => xlsx_export::export_line_items(&drill_down.items, drill_down.timezone, &filename)?,
+            }
+            return Ok((filename, format!("{} {}", report.period.title(), drill_down.title())));
+        }
+
+        let filename = directory.join(format!("{}_report.{}", report.period.file_stem(), format.extension()));
+        match format {
+            Format::Html => std::fs::write(&filename, self.database.render_to_html(&report.period, &self.settings.operating_hours)?)?,
+            Format::Csv => summary_export::write_csv(&report.summary, std::fs::File::create(&filename)?)?,
+            Format::Xlsx => xlsx_export::export_summary(&report.summary, &report.period, &filename)?,
+        }
+        Ok((filename, format!("{} report", report.period.title())))
+    }
+
+    fn export_shown_report(&mut self, format: Format) {
+        match self.write_shown_report(format) {
+            Ok((filename, description)) => {
+                info!("Exported file to: {}", filename.display());
+                self.reports.set_status(format!("Saved to {}", filename.display()));
+                self.log_audit(AuditEntry::new(&self.cashier, AuditAction::Export, filename.display().to_string(), description));
+            }
+            Err(err) => {
+                error!("Error exporting report: {:#}", err);
+                self.reports.set_status(format!("Unable to export: {err:#}"));
+                self.error = Some(err);
+            }
+        }
+    }
+
+    fn reprint_receipt(&mut self, number: i64) -> anyhow::Result<PathBuf> {
+        let directory = self.settings.report_directory();
+        let filename = directory.join(receipt::file_name(number));
+        let html = receipt::render_to_html(&self.database.sale_lines(number)?, self.settings.operating_hours.timezone)?;
+        std::fs::create_dir_all(&directory)?;
+        std::fs::write(&filename, html)?;
+
+        info!("Exported file to: {}", filename.display());
+        self.log_audit(AuditEntry::new(&self.cashier, AuditAction::Export, filename.display().to_string(), format!("receipt for sale #{number}")));
+        Ok(filename)
+    }
+
+    /// Checks the PIN typed in on the history screen before a sale is changed, e.g. `doing` is "void".
+    fn check_supervisor_pin(&self, doing: &str) -> anyhow::Result<()> {
+        anyhow::ensure!(self.settings.permissions.has_pin(),
+                        "no sale can be changed until a supervisor PIN is set as permissions.supervisor_pin in {}",
+                        self.settings.filepath().display());
+        anyhow::ensure!(self.settings.permissions.may_change_sales(self.history.pin()),
+                        "the supervisor PIN is needed to {doing} a sale");
+        Ok(())
+    }
+
+    /// Voids or refunds a sale from the history screen once the permissions allow it, audits it with the reason
+    /// given, and shows the sale as it now stands.
+    fn take_back_sale(&mut self, number: i64, action: AuditAction) -> anyhow::Result<()> {
+        let Some(sale) = self.history.selected().filter(|x| x.number() == Some(number)).cloned() else {
+            anyhow::bail!("sale #{number} isn't selected");
+        };
+        self.check_supervisor_pin(&action.to_string().to_lowercase())?;
+
+        let before = format!("Sale #{number} ({})", ff("$", sale.total()));
+        let entry = match action {
+            AuditAction::Void => {
+                self.database.void_sale(number)?;
+                AuditEntry::new(&self.cashier, action, format!("Sale #{number}"), self.history.reason()).with_before(before)
+            }
+            _ => {
+                let refunded: f32 = self.database.refund_sale(number)?.iter().map(|x| x.amount).sum();
+                AuditEntry::new(&self.cashier, action, format!("Sale #{number}"), self.history.reason())
+                    .with_before(before)
+                    .with_after(format!("Refunded {}", ff("$", refunded)))
+            }
+        };
+        self.log_audit(entry);
+        self.show_changed_sale(number)
+    }
+
+    /// Saves the correction being made on the history screen once the permissions allow it, and audits it with the
+    /// item as it was and as it is now.
+    fn correct_sale(&mut self, number: i64) -> anyhow::Result<()> {
+        anyhow::ensure!(self.history.selected().is_some_and(|x| x.number() == Some(number)), "sale #{number} isn't selected");
+        let Some((before, after)) = self.history.correction().map(|(x, y)| (x.clone(), y.clone())) else {
+            anyhow::bail!("nothing was changed");
+        };
+        self.check_supervisor_pin("correct")?;
+
+        self.database.correct_item(number, &before, &after)?;
+        self.log_audit(AuditEntry::new(&self.cashier, AuditAction::Correction, format!("Sale #{number}"), self.history.reason())
+            .with_before(before.describe())
+            .with_after(after.describe()));
+        self.show_changed_sale(number)
+    }
+
+    /// Reads the sales made in `period` for the history screen, and what was taken and given back in it.
+    fn read_history(&mut self, period: Period) -> anyhow::Result<()> {
+        let sales = self.database.history(&period)?;
+        self.history.set_sales(period, sales, self.database.takings(&period)?);
+        Ok(())
+    }
+
+    /// Reads the history again after sale `number` changed, and shows it as it now is.
+    fn show_changed_sale(&mut self, number: i64) -> anyhow::Result<()> {
+        if let Some(period) = self.history.loaded() {
+            self.read_history(period)?;
+        }
+        self.history.set_selected(self.database.sale_lines(number)?);
+        self.history.set_items(self.database.sale_items(number)?);
+        Ok(())
+    }
+
+    fn close_day(&mut self) {
+        let closed = DayReport::close_day(&mut self.database, &self.settings.operating_hours, &self.cashier, self.counted_cash.value());
+        let report = match closed {
+            Ok(report) => report,
+            Err(err) => {
+                error!("Error closing the day: {:#}", err);
+                self.day_status = format!("Unable to close the day: {err:#}");
+                self.error = Some(err);
+                return;
+            }
+        };
 
+        let over_short = self.database.day_close().map(|x| x.over_short()).unwrap_or_default();
+        self.day_status = match self.write_day_report(&report) {
+            Ok(path) => format!("{} saved to {}. Over/short: {}", report.title(), path.display(), ff("$", over_short)),
+            Err(err) => {
+                error!("Error exporting {}: {:#}", report.title(), err);
+                let status = format!("The day is closed, but {} couldn't be saved: {err:#}", report.title());
+                self.error = Some(err);
+                status
+            }
+        };
+        self.counted_cash = DecimalInput::new("Counted Cash", 0.0);
+        // A closed day never changes again, so this backup is a permanent copy of it.
+        self.back_up();
+    }
 
+    fn add_to_cart<T:Into<CartItem>, S: ToModel<ModelType=T>>(&mut self, item: S) -> anyhow::Result<()> {
+        self.push_to_cart(item.to_model()?.into());
         Ok(())
     }
 
+    /// Puts `item` at the end of the cart and keeps it, whether it came from a form or a quick sale button.
+    fn push_to_cart(&mut self, item: CartItem) {
+        self.stringified_cart.push(item.to_string());
+        self.cart.push(item);
+        self.save_cart();
+    }
+
+    /// Keeps the cart in the database after every change, so it can be restored if the program closes mid-sale.
+    fn save_cart(&mut self) {
+        if self.cart.is_empty() {
+            self.cart_status.clear();
+        }
+        if let Err(err) = self.database.save_cart(&self.cart) {
+            error!("Error keeping the cart: {:#}", err);
+            self.error = Some(err);
+        }
+    }
+
+    fn refresh_quick_sales(&mut self) {
+        match self.database.quick_sales() {
+            Ok(buttons) => {
+                self.quick_sale_editor.set_buttons(buttons.clone());
+                self.quick_sales = buttons;
+            }
+            Err(err) => {
+                error!("Error reading the quick sale buttons: {:#}", err);
+                self.error = Some(err);
+            }
+        }
+    }
+
+    /// Saves the buttons as edited on the quick sales screen, and audits what they were and are now.
+    fn save_quick_sales(&mut self) {
+        let labels = |buttons: &[QuickSale]| buttons.iter().map(|x| x.label.clone()).collect::<Vec<_>>().join(", ");
+        let before = labels(self.quick_sale_editor.saved());
+        let saved = self.quick_sale_editor.buttons().and_then(|buttons| {
+            self.database.save_quick_sales(&buttons)?;
+            Ok(buttons)
+        });
+        match saved {
+            Ok(buttons) => {
+                info!("Saved {} quick sale buttons", buttons.len());
+                self.log_audit(AuditEntry::new(&self.cashier, AuditAction::SettingsChange, "Quick sale buttons", "edited on the quick sales screen")
+                    .with_before(before)
+                    .with_after(labels(&buttons)));
+                self.refresh_quick_sales();
+                self.quick_sale_editor.set_status("Saved");
+            }
+            Err(err) => {
+                error!("Error saving the quick sale buttons: {:#}", err);
+                self.quick_sale_editor.set_status(format!("Unable to save: {err:#}"));
+                self.error = Some(err);
+            }
+        }
+    }
+
+    fn refresh_parked_carts(&mut self) {
+        match self.database.parked_carts() {
+            Ok(carts) => self.parked_carts = carts,
+            Err(err) => {
+                error!("Error reading the parked carts: {:#}", err);
+                self.error = Some(err);
+            }
+        }
+    }
+
+    /// Sets the cart on screen aside under the label typed in, so the next visitor can be served.
+    fn park_cart(&mut self) {
+        match self.database.park_cart(&self.park_label, &self.cart) {
+            Ok(label) => {
+                info!("Parked the cart as {label:?}");
+                self.error = None;
+                self.cart.clear();
+                self.stringified_cart.clear();
+                self.cart_status.clear();
+                self.selected_index = None;
+                self.park_label.clear();
+            }
+            Err(err) => {
+                error!("Error parking the cart: {:#}", err);
+                self.error = Some(err);
+            }
+        }
+        self.refresh_parked_carts();
+    }
+
+    fn recall_cart(&mut self, label: &str) {
+        match self.database.recall_cart(label) {
+            Ok(cart) => {
+                self.error = None;
+                self.cart_status = format!("Recalled {label}");
+                self.stringified_cart = cart.iter().map(|x| x.to_string()).collect();
+                self.cart = cart;
+                self.selected_index = None;
+            }
+            Err(err) => {
+                error!("Error recalling the cart {label:?}: {:#}", err);
+                self.error = Some(err);
+            }
+        }
+        self.refresh_parked_carts();
+    }
+
+    /// Puts back the cart that was left unfinished when the program last closed.
+    fn restore_cart(&mut self) {
+        let (changed, cart) = match self.database.unfinished_cart() {
+            Ok(Some(unfinished)) => unfinished,
+            Ok(None) => return,
+            Err(err) => {
+                error!("Error reading the unfinished cart: {:#}", err);
+                self.error = Some(err);
+                return;
+            }
+        };
+
+        let timezone = self.settings.operating_hours.timezone;
+        let (changed_clock, now) = (timezone.clock(changed), timezone.clock(Local::now()));
+        let when = if changed_clock.date() == now.date() {
+            changed_clock.format("%-I:%M%P").to_string()
+        } else {
+            changed_clock.format("%b %-d, %-I:%M%P").to_string()
+        };
+        info!("Restored an unfinished cart of {} items from {}", cart.len(), changed);
+        self.cart_status = format!("Restored unfinished cart from {when}");
+        self.stringified_cart = cart.iter().map(|x| x.to_string()).collect();
+        self.cart = cart;
+    }
+
+    fn log_audit(&mut self, entry: AuditEntry) {
+        if let Err(err) = self.database.log_audit(entry) {
+            error!("Error writing to the audit log: {}", err);
+            self.error = Some(err);
+        }
+    }
+
+    /// Records a gift shop item rung up at other than the usual sales tax rate, the one price a cashier can change.
+    fn audit_price_override(&mut self) {
+        let Some(CartItem::GiftShopSale(item)) = self.cart.last() else { return; };
+        if item.sales_tax() == DEFAULT_SALES_TAX {
+            return;
+        }
+        let entry = AuditEntry::new(&self.cashier, AuditAction::PriceOverride, format!("Gift shop: {}", item.item_description), "changed at the till")
+            .with_before(format!("{DEFAULT_SALES_TAX}% sales tax"))
+            .with_after(format!("{}% sales tax, {}", item.sales_tax(), ff("$", item.total_cost())));
+        self.log_audit(entry);
+    }
+
     fn handle_sale_message(&mut self, message: SaleMessage) {
         // For the love of all that is good, do the sale screen update AFTER the transaction is written to the database.
         let err = match message.clone() {
-            SaleMessage::AddAdmission => self.add_to_cart(self.sale_screen.admission().clone()),
+            SaleMessage::AddAdmission => self.add_to_cart(*self.sale_screen.admission()),
             SaleMessage::AddDonation => self.add_to_cart(self.sale_screen.donation().clone()),
-            SaleMessage::AddMembership => self.add_to_cart(self.sale_screen.membership().clone()),
+            SaleMessage::AddMembership => self.add_to_cart(*self.sale_screen.membership()),
             SaleMessage::AddGiftShopSale => self.add_to_cart(self.sale_screen.gift_shop_sale().clone()),
             _ => {Ok(())}
         };
@@ -77,6 +545,9 @@ self.transactionify_and_insert(self.sale_screen.gift_shop_sale().clone())
             self.error = Some(err);
         } else {
             self.error = None;
+            if let SaleMessage::AddGiftShopSale = message {
+                self.audit_price_override();
+            }
         }
         
         self.sale_screen.update(message);
@@ -84,53 +555,257 @@ self.transactionify_and_insert(self.sale_screen.gift_shop_sale().clone())
 
     pub fn update(&mut self, message: Message) {
         match message {
+            Message::ShowScreen(screen) => {
+                if screen == Screen::AuditLog {
+                    self.audit_entries.clear();
+                    self.read_older_audit_entries();
+                }
+                if screen == Screen::Backups {
+                    self.refresh_backups();
+                }
+                self.screen = screen;
+            },
             Message::SaleMessage(s) => self.handle_sale_message(s),
-            Message::RenderDailyReport => {
-                let now = Local::now();
-                let filename = format!("{}{}{}_report.html", now.year(), now.month(), now.day());
-                info!("Exported file to: {}", filename);
-                let _ =std::fs::write(
-                    filename,
-                    self.database.render_to_html()
-                );
+            Message::AuditLogMessage(a) => {
+                if let AuditLogMessage::ShowOlder = a {
+                    self.read_older_audit_entries();
+                }
+                self.audit_log.update(a)
+            },
+            Message::ReportPeriod(p) => self.report_period.update(p),
+            Message::Backups(b) => {
+                match &b {
+                    BackupsMessage::BackUpNow => self.back_up(),
+                    BackupsMessage::ConfirmRestore(path) => self.restore(path.clone()),
+                    _ => {}
+                }
+                self.backups.update(b);
+            },
+            Message::Comparisons(c) => {
+                match &c {
+                    ComparisonsMessage::Compare(current, previous) => {
+                        match Comparison::between(&self.database, &self.settings.operating_hours, *current, *previous) {
+                            Ok(comparison) => self.comparisons.set_comparison(comparison),
+                            Err(err) => {
+                                error!("Error comparing periods: {:#}", err);
+                                self.comparisons.set_status(format!("Unable to compare: {err:#}"));
+                                self.error = Some(err);
+                            }
+                        }
+                    }
+                    ComparisonsMessage::Export => self.export_comparison(),
+                    _ => {}
+                }
+                self.comparisons.update(c);
+            },
+            Message::Reports(r) => {
+                match &r {
+                    ReportsMessage::Show(period) => match self.database.ledger(period) {
+                        Ok(ledger) => {
+                            let summary = SummaryDicts::new(&ledger, &self.settings.operating_hours, period);
+                            self.reports.set_report(*period, summary, ledger);
+                        }
+                        Err(err) => {
+                            error!("Error reading the report: {:#}", err);
+                            self.reports.set_status(format!("Unable to read the report: {err:#}"));
+                            self.error = Some(err);
+                        }
+                    },
+                    ReportsMessage::Export(format) => self.export_shown_report(*format),
+                    _ => {}
+                }
+                self.reports.update(r);
+            },
+            Message::History(h) => {
+                self.history.update(h.clone());
+                let status = match &h {
+                    HistoryMessage::Load(period) => self.read_history(*period)
+                        .map(|_| String::new())
+                        .map_err(|x| x.context("unable to read the sales")),
+                    HistoryMessage::Search(_) => match self.history.search() {
+                        Some(search) => self.database.gift_shop_items_like(search).map(|x| {
+                            self.history.set_found(x);
+                            String::new()
+                        }),
+                        None => Ok(String::new()),
+                    },
+                    HistoryMessage::Reprint(number) => self.reprint_receipt(*number)
+                        .map(|path| format!("Saved to {}", path.display())),
+                    HistoryMessage::Void(number) => self.take_back_sale(*number, AuditAction::Void)
+                        .map(|_| format!("Voided sale #{number}")),
+                    HistoryMessage::Refund(number) => self.take_back_sale(*number, AuditAction::Refund)
+                        .map(|_| format!("Refunded sale #{number}")),
+                    HistoryMessage::SaveCorrection(number) => self.correct_sale(*number)
+                        .map(|_| format!("Corrected sale #{number}")),
+                    HistoryMessage::Select(_) => match self.history.selected().and_then(|x| x.number()) {
+                        Some(number) => self.database.sale_items(number).map(|x| {
+                            self.history.set_items(x);
+                            String::new()
+                        }),
+                        None => Ok(String::new()),
+                    },
+                    _ => Ok(String::new()),
+                };
+                match status {
+                    Ok(status) if status.is_empty() => {}
+                    Ok(status) => self.history.set_status(status),
+                    Err(err) => {
+                        error!("Error in the sales history: {:#}", err);
+                        self.history.set_status(format!("{err:#}"));
+                        self.error = Some(err);
+                    }
+                }
+            },
+            Message::QuickSales(q) => {
+                if let QuickSalesMessage::Save = q {
+                    self.save_quick_sales();
+                }
+                self.quick_sale_editor.update(q);
+            },
+            Message::AutomaticBackup => self.back_up(),
+            Message::ClockTick => {
+                self.database.roll_over_day();
+            }
+            Message::RenderReport(period) => {
+                let directory = self.settings.report_directory();
+                let filename = directory.join(format!("{}_report.html", period.file_stem()));
+                let written = self.database.render_to_html(&period, &self.settings.operating_hours)
+                    .and_then(|html| {
+                        std::fs::create_dir_all(&directory)?;
+                        Ok(std::fs::write(&filename, html)?)
+                    });
+                if let Err(err) = written {
+                    error!("Error exporting report: {}", err);
+                    self.error = Some(err);
+                    return;
+                }
+
+                info!("Exported file to: {}", filename.display());
+                self.log_audit(AuditEntry::new(&self.cashier, AuditAction::Export, filename.display().to_string(), format!("{} report", period.title())));
+            },
+            Message::ExportCsv(period) => {
+                match csv_export::export(&self.database, &period, &self.settings.operating_hours, self.settings.export_directory()) {
+                    Ok(directory) => {
+                        info!("Exported CSV files to: {}", directory.display());
+                        self.log_audit(AuditEntry::new(&self.cashier, AuditAction::Export, directory.display().to_string(), format!("{} CSV export", period.title())));
+                    },
+                    Err(err) => {
+                        error!("Error exporting CSV files: {}", err);
+                        self.error = Some(err);
+                    }
+                }
             },
+            Message::ExportXlsx(period) => {
+                match xlsx_export::export(&self.database, &period, &self.settings.operating_hours, self.settings.export_directory()) {
+                    Ok(path) => {
+                        info!("Exported spreadsheet to: {}", path.display());
+                        self.log_audit(AuditEntry::new(&self.cashier, AuditAction::Export, path.display().to_string(), format!("{} spreadsheet", period.title())));
+                    },
+                    Err(err) => {
+                        error!("Error exporting spreadsheet: {}", err);
+                        self.error = Some(err);
+                    }
+                }
+            },
+            Message::CountedCash(c) => self.counted_cash.update(c),
+            Message::PrintXReport => {
+                let counted_cash = Some(self.counted_cash.value()).filter(|x| *x > 0.0);
+                let written = DayReport::x_report(&mut self.database, &self.settings.operating_hours, &self.cashier, counted_cash)
+                    .and_then(|report| self.write_day_report(&report).map(|path| (report, path)));
+                match written {
+                    Ok((report, path)) => self.day_status = format!("{} saved to {}", report.title(), path.display()),
+                    Err(err) => {
+                        error!("Error exporting the X-report: {:#}", err);
+                        self.day_status = format!("Unable to save the X-report: {err:#}");
+                        self.error = Some(err);
+                    }
+                }
+            },
+            Message::CloseDay => self.confirming_close = true,
+            Message::CancelCloseDay => self.confirming_close = false,
+            Message::ConfirmCloseDay => {
+                self.confirming_close = false;
+                self.close_day();
+            },
+            Message::CashierChanged(c) => self.cashier = c,
             Message::CartSelection(c) => self.selected_index = Some(c),
+            Message::RemovalReasonChanged(r) => self.removal_reason = r,
             Message::DeleteSelected => {
                 let Some(index) = self.selected_index else {return;};
-                self.cart.remove(index);
+                let removed = self.cart.remove(index);
                 self.stringified_cart.remove(index);
                 self.selected_index = None;
+                self.save_cart();
+
+                let entry = AuditEntry::new(&self.cashier, AuditAction::CartRemoval, "Cart", &self.removal_reason)
+                    .with_before(format!("{} ({})", removed, ff("$", removed.total_cost())));
+                self.removal_reason.clear();
+                self.log_audit(entry);
+            },
+            Message::ParkLabelChanged(l) => self.park_label = l,
+            Message::ParkCart => self.park_cart(),
+            Message::RecallCart(label) => self.recall_cart(&label),
+            Message::QuickSalePayment(p) => self.quick_sale_payment = Some(p),
+            Message::DismissError => self.error = None,
+            Message::QuickSale(index) => {
+                // The keys only ring up sales while the sales screen is showing.
+                if self.screen != Screen::Sales {
+                    return;
+                }
+                let item = match self.quick_sales.get(index) {
+                    Some(button) => button.to_cart_item(self.quick_sale_payment)
+                        .map_err(|x| x.context(format!("unable to ring up {}", button.label))),
+                    None => Err(anyhow::anyhow!("there is no quick sale button {}, add one on the Quick Sales tab", index + 1)),
+                };
+                match item {
+                    Ok(item) => {
+                        self.error = None;
+                        self.push_to_cart(item);
+                    }
+                    Err(err) => {
+                        error!("Error ringing up quick sale {}: {:#}", index + 1, err);
+                        self.error = Some(err);
+                    }
+                }
             },
             Message::CommitToDb => {
                 if self.cart.is_empty() {
                     return;
                 }
+                // Checked up front so a closed day leaves the cart alone rather than failing item by item.
+                self.database.roll_over_day();
+                if let Some(close) = self.database.day_close() {
+                    let err = anyhow::anyhow!(
+                        "the business day of {} was closed with Z-report #{}, so the cart can be saved once the next one starts",
+                        close.business_date, close.number,
+                    );
+                    error!("Error inserting sale into db: {:#}", err);
+                    self.error = Some(err);
+                    return;
+                }
 
-                for item_index in (0..self.cart.len()).rev() {
-                    let err = match &self.cart[item_index] {
-                        CartItem::Admission(x) => self.transactionify_and_insert(x.clone()),
-                        CartItem::Membership(x) => self.transactionify_and_insert(x.clone()),
-                        CartItem::Donation(x) => self.transactionify_and_insert(x.clone()),
-                        CartItem::GiftShopSale(x) => self.transactionify_and_insert(x.clone()),
-                    };
-
-                    if let Err(err) = err {
-                        error!("Error inserting item into db: {}", err);
-                        self.error = Some(err);
-                    } else {
+                // The whole cart is saved as one numbered sale, or not at all, in which case it stays in the cart.
+                match self.database.record_sale(&self.cashier, &self.cart) {
+                    Ok(sale) => {
+                        info!("Saved sale #{}", sale.number);
                         self.error = None;
+                        self.cart.clear();
+                        self.stringified_cart.clear();
+                        self.cart_status.clear();
+                        self.selected_index = None;
+                    }
+                    Err(err) => {
+                        error!("Error inserting sale into db: {}", err);
+                        self.error = Some(err);
                     }
-
-                    self.cart.remove(item_index);
-                    self.stringified_cart.remove(item_index);
                 }
             }
         }
     }
 
-    fn summary_box(name: impl AsRef<str>, value: impl AsRef<str>) -> Element<'static, Message> {
+    fn summary_box(name: impl AsRef<str>, value: impl Display) -> Element<'static, Message> {
         let name = name.as_ref().to_string();
-        let value = value.as_ref().to_string();
+        let value = value.to_string();
         container(iced::widget::column![
             text(name).align_x(Horizontal::Center),
             text(value).size(TEXT_SIZE + 4).align_x(Horizontal::Center)
@@ -139,12 +814,11 @@ self.transactionify_and_insert(self.sale_screen.gift_shop_sale().clone())
             .into()
     }
 
-    fn summary_row(&self, header: impl AsRef<str>, values: impl IntoIterator<Item=(impl AsRef<str>, impl AsRef<str>)>) -> Element<'static, Message> {
+    fn summary_row(&self, header: impl AsRef<str>, values: impl IntoIterator<Item=(impl AsRef<str>, impl Display)>) -> Element<'static, Message> {
         let mut grid = iced::widget::column![].spacing(RULE_HEIGHT);
-        let mut values = values.into_iter();
 
         let mut row = iced::widget::Row::new().spacing(RULE_HEIGHT);
-        while let Some((name, value)) = values.next() {
+        for (name, value) in values {
             row = row.push(Self::summary_box(name, value));
             if row.children().len() == 5 {
                 grid = grid.push(row);
@@ -164,52 +838,231 @@ self.transactionify_and_insert(self.sale_screen.gift_shop_sale().clone())
         ].padding(RULE_HEIGHT).align_x(Horizontal::Center).into()
     }
 
-    fn summary(&self) -> Element<Message> {
-        let summaries = SummaryDicts::new(&self.database);
+    fn summary(&self) -> Element<'_, Message> {
+        let summaries = self.database.daily_summary();
         iced::widget::column![
             self.summary_row("Daily Summary", &summaries.summary),
             self.summary_row("Daily Payments Breakdown", &summaries.payments),
             self.summary_row("Daily Admission Breakdown", &summaries.admissions),
             self.summary_row("Daily Membership Sales Breakdown", &summaries.memberships),
+            self.summary_row("Daily Attendance by Hour", &summaries.attendance_by_hour),
+        ].spacing(RULE_HEIGHT).into()
+    }
+
+    fn end_of_day(&self) -> Element<'_, Message> {
+        let actions: Element<'_, Message> = match (self.database.day_close(), self.confirming_close) {
+            (Some(close), _) => text(format!(
+                "The business day of {} was closed by {} with Z-report #{}.", close.business_date, close.closed_by, close.number
+            )).size(TEXT_SIZE).into(),
+            (None, true) => row![
+                text(format!(
+                    "Close the business day of {} with {} counted? Nothing in it can be changed afterwards.{}",
+                    self.database.business_day().from, ff("$", self.counted_cash.value()),
+                    match self.parked_carts.len() {
+                        0 => String::new(),
+                        1 => " A parked cart was never sold, and the Z-report will list it.".to_string(),
+                        count => format!(" {count} parked carts were never sold, and the Z-report will list them."),
+                    }
+                )).size(TEXT_SIZE),
+                button("Close Day").on_press(Message::ConfirmCloseDay),
+                button("Cancel").on_press(Message::CancelCloseDay),
+            ].spacing(RULE_HEIGHT).into(),
+            (None, false) => row![
+                self.counted_cash.view().map(Message::CountedCash),
+                button("Print X-Report").on_press(Message::PrintXReport),
+                button("Close Day").on_press(Message::CloseDay),
+            ].spacing(RULE_HEIGHT).into(),
+        };
+
+        iced::widget::column![
+            text("End of Day").size(HEADER_SIZE),
+            horizontal_rule(RULE_HEIGHT),
+            actions,
+            text(&self.day_status).size(TEXT_SIZE),
+        ].spacing(RULE_HEIGHT).padding(RULE_HEIGHT).align_x(Horizontal::Center).width(Length::Fill).into()
+    }
+
+    /// Parks the cart on screen, and lists the parked ones so any can be recalled once the screen is clear.
+    fn parking(&self) -> Element<'_, Message> {
+        let parked = self.parked_carts.iter().fold(iced::widget::column![].spacing(RULE_HEIGHT), |column, cart| {
+            column.push(row![
+                text(cart.describe(self.settings.operating_hours.timezone)).size(TEXT_SIZE).width(Length::Fill),
+                button("Recall").on_press_maybe(self.cart.is_empty().then(|| Message::RecallCart(cart.label.clone()))),
+            ].spacing(RULE_HEIGHT))
+        });
+
+        iced::widget::column![
+            row![
+                text_input("Label, e.g. a name", &self.park_label).on_input(Message::ParkLabelChanged).on_submit(Message::ParkCart),
+                button("Park Cart").on_press_maybe((!self.cart.is_empty()).then_some(Message::ParkCart)),
+            ].spacing(RULE_HEIGHT),
+            text(if self.parked_carts.is_empty() { "No parked carts" } else { "Parked Carts" }).size(TEXT_SIZE),
+            parked,
         ].spacing(RULE_HEIGHT).into()
     }
 
+    /// One button per quick sale, five to a row, with the payment method for those that don't have their own.
+    fn quick_sale_grid(&self) -> Element<'_, Message> {
+        let mut grid = iced::widget::column![].spacing(RULE_HEIGHT);
+        for (chunk_index, chunk) in self.quick_sales.chunks(5).enumerate() {
+            grid = grid.push(chunk.iter().enumerate().fold(Row::new().spacing(RULE_HEIGHT), |row, (index, x)| {
+                let index = chunk_index * 5 + index;
+                let label = if index < 9 { format!("{}. {}", index + 1, x.label) } else { x.label.clone() };
+                let ready = x.to_cart_item(self.quick_sale_payment).is_ok();
+                row.push(button(text(label)).on_press_maybe(ready.then_some(Message::QuickSale(index))))
+            }));
+        }
+
+        iced::widget::column![
+            text("Quick Sales").size(HEADER_SIZE),
+            row![
+                text("Pay by: ").size(TEXT_SIZE),
+                pick_list(PaymentMethod::VARIANTS, self.quick_sale_payment, Message::QuickSalePayment),
+            ].spacing(RULE_HEIGHT),
+            grid,
+        ].spacing(RULE_HEIGHT).padding(RULE_HEIGHT).into()
+    }
+
+    /// Whether there is a cart to save and a business day open to save it in.
+    fn may_commit(&self) -> bool {
+        !self.cart.is_empty() && self.database.day_close().is_none()
+    }
+
     fn cost_of_cart(&self) -> String {
         ff("$", self.cart.iter().map(|x| x.total_cost()).sum())
     }
 
-    pub fn view(&self) -> Element<Message> {
-        container(scrollable(iced::widget::column![
+    fn navigation(&self) -> Element<'_, Message> {
+        let tab = |label: &'static str, screen: Screen| {
+            button(label).on_press_maybe((self.screen != screen).then_some(ShowScreen(screen)))
+        };
+
+        row![
+            tab("Sales", Screen::Sales),
+            tab("Reports", Screen::Reports),
+            tab("History", Screen::History),
+            tab("Audit Log", Screen::AuditLog),
+            tab("Compare", Screen::Compare),
+            tab("Backups", Screen::Backups),
+            tab("Quick Sales", Screen::QuickSales),
+            tab("About", Screen::About),
+        ].spacing(RULE_HEIGHT).into()
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        let backups = match self.settings.backups.interval_minutes {
+            0 => Subscription::none(),
+            minutes => iced::time::every(Duration::from_secs(minutes as u64 * 60)).map(|_| Message::AutomaticBackup),
+        };
+        // Checked every minute rather than timed to the boundary, so sleeping or a clock change can't make it miss one.
+        let clock = iced::time::every(Duration::from_secs(60)).map(|_| Message::ClockTick);
+        // Keys typed into a text box never get here, so a 2 in a price doesn't ring up the second button.
+        let quick_sales = iced::keyboard::on_key_press(|key: Key, modifiers: Modifiers| match key.as_ref() {
+            Key::Character(c) if modifiers.is_empty() => c.parse::<usize>().ok().filter(|x| (1..=9).contains(x)).map(|x| Message::QuickSale(x - 1)),
+            _ => None,
+        });
+
+        Subscription::batch([backups, clock, quick_sales])
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let body = match self.screen {
+            Screen::Sales => self.sales_view(),
+            Screen::Reports => self.reports.view(self.settings.operating_hours.today()).map(Message::Reports),
+            Screen::History => self.history.view(self.settings.operating_hours.today()).map(Message::History),
+            Screen::AuditLog => self.audit_log.view(&self.audit_entries, self.older_audit_entries, self.settings.operating_hours.timezone).map(Message::AuditLogMessage),
+            Screen::Compare => self.comparisons.view(self.settings.operating_hours.today()).map(Message::Comparisons),
+            Screen::Backups => self.backups.view().map(Message::Backups),
+            Screen::QuickSales => self.quick_sale_editor.view().map(Message::QuickSales),
+            Screen::About => about::view(&self.settings),
+        };
+
+        // Whatever last went wrong stays on every screen until it is dismissed or the same thing works.
+        let error = self.error.as_ref().map(|err| row![
+            text(format!("Error: {err:#}")).size(TEXT_SIZE).style(text::danger).width(Length::Fill),
+            button("Dismiss").on_press(Message::DismissError),
+        ].spacing(RULE_HEIGHT));
+
+        container(iced::widget::column![
+            self.navigation(),
+        ].push_maybe(error).push(body).spacing(RULE_HEIGHT)).padding(RULE_HEIGHT).into()
+    }
+
+    fn sales_view(&self) -> Element<'_, Message> {
+        scrollable(iced::widget::column![
             iced::widget::row![
                 iced::widget::column![
+                    row![
+                        text("Cashier: ").size(TEXT_SIZE),
+                        text_input("Your name", &self.cashier).on_input(CashierChanged),
+                    ].spacing(RULE_HEIGHT),
                     text("Cart").size(HEADER_SIZE),
+                    text(&self.cart_status).size(TEXT_SIZE),
                     selection_list(&self.stringified_cart, |x, _| {CartSelection(x)}).height(400),
+                    text_input("Reason for removing an item", &self.removal_reason).on_input(RemovalReasonChanged),
                     iced::widget::row![
                         text(format!("Amount Due: {}", self.cost_of_cart())).size(TEXT_SIZE),
                         button("Remove Selected Item").on_press_maybe(self.selected_index.map(|_| DeleteSelected)),
-                        button("Save Transaction and Clear Cart").on_press(CommitToDb),
-                    ].spacing(RULE_HEIGHT)
+                        button("Save Transaction and Clear Cart").on_press_maybe(self.may_commit().then_some(CommitToDb)),
+                    ].spacing(RULE_HEIGHT),
+                    self.parking(),
                 ].padding(RULE_HEIGHT as f32).spacing(RULE_HEIGHT).width(Length::FillPortion(1)),
-                container(self.sale_screen.view().map(Message::SaleMessage)).width(Length::FillPortion(2))
+                container(iced::widget::column![
+                    self.quick_sale_grid(),
+                    self.sale_screen.view().map(Message::SaleMessage),
+                ]).width(Length::FillPortion(2))
             ],
             self.summary(),
+            self.end_of_day(),
             text("Exports").size(HEADER_SIZE).width(Length::Fill).align_x(Horizontal::Center),
             horizontal_rule(RULE_HEIGHT),
-            container(button("Export Daily Report").on_press(RenderDailyReport)).width(Length::Fill).align_x(Horizontal::Center)
-        ].spacing(2 * RULE_HEIGHT))).padding(RULE_HEIGHT).into()
-
+            container(row![
+                self.report_period.view().map(Message::ReportPeriod),
+                button("Export Report").on_press_maybe(self.report_period.period(self.settings.operating_hours.today()).map(RenderReport)),
+                button("Export CSV").on_press_maybe(self.report_period.period(self.settings.operating_hours.today()).map(ExportCsv)),
+                button("Export Spreadsheet").on_press_maybe(self.report_period.period(self.settings.operating_hours.today()).map(ExportXlsx)),
+            ].spacing(RULE_HEIGHT)).width(Length::Fill).align_x(Horizontal::Center)
+        ].spacing(2 * RULE_HEIGHT)).into()
     }
 }
 
-impl Default for App {
-    fn default() -> Self {
-        Self {
+impl App {
+    /// Takes a backup straight away, so there is always one from the start of each session.
+    pub fn new(database: Database, settings: Settings) -> Self {
+        let mut app = Self {
+            report_period: PeriodPicker::new(settings.operating_hours.today()),
+            comparisons: Comparisons::new(settings.operating_hours.today()),
+            reports: Reports::new(settings.operating_hours.today()),
+            history: History::new(settings.operating_hours.today(), settings.operating_hours.timezone),
+            screen: Default::default(),
             sale_screen: Default::default(),
-            database: Database::new(),
+            database,
+            settings,
             error: None,
+            cashier: String::new(),
             cart: vec![],
             stringified_cart: vec![],
+            cart_status: String::new(),
+            park_label: String::new(),
+            parked_carts: vec![],
+            quick_sales: vec![],
+            quick_sale_payment: None,
+            quick_sale_editor: Default::default(),
             selected_index: None,
-        }
+            removal_reason: String::new(),
+            audit_log: Default::default(),
+            audit_entries: vec![],
+            older_audit_entries: false,
+            backups: Default::default(),
+            counted_cash: DecimalInput::new("Counted Cash", 0.0),
+            confirming_close: false,
+            day_status: String::new(),
+        };
+
+        app.restore_cart();
+        app.refresh_parked_carts();
+        app.refresh_quick_sales();
+        app.back_up();
+        app
     }
 }
\ No newline at end of file
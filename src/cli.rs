@@ -0,0 +1,290 @@
+use std::path::{Path, PathBuf};
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use itertools::Itertools;
+use crate::database::Database;
+use crate::model::audit_entry::{AuditAction, AuditEntry};
+use crate::report::comparison::Comparison;
+use crate::report::period::Period;
+use crate::report::{csv_export, summary_export, xlsx_export};
+use crate::settings::Settings;
+use crate::view::summary_dicts::SummaryDicts;
+
+/// Who the audit log says did anything done from here.
+const COMMAND_LINE_ACTOR: &str = "Command Line";
+
+/// Run without a command to open the till. The commands work without a display, so they can run from a scheduled task.
+#[derive(Debug, Parser)]
+#[command(name = "silverplume_point_of_sale", version, about = "Museum Point Of Sale")]
+pub struct Cli {
+    /// Where the database, settings, reports, backups and exports live. Defaults to a per-user directory.
+    #[arg(long, global = true)]
+    pub data_dir: Option<PathBuf>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Print the summary report for a range of days.
+    Report {
+        #[command(flatten)]
+        range: DateRange,
+        #[arg(long, value_enum, default_value_t = ReportFormat::Html)]
+        format: ReportFormat,
+        /// Write the report here instead of to standard output.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Print a range of days side by side with another, by default the same days a year earlier.
+    Compare {
+        #[command(flatten)]
+        range: DateRange,
+        /// First day of the range to compare with, as YYYY-MM-DD.
+        #[arg(long, requires = "against_to")]
+        against_from: Option<NaiveDate>,
+        /// Last day of the range to compare with, as YYYY-MM-DD.
+        #[arg(long, requires = "against_from")]
+        against_to: Option<NaiveDate>,
+        #[arg(long, value_enum, default_value_t = ReportFormat::Html)]
+        format: ReportFormat,
+        /// Write the comparison here instead of to standard output.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Write the raw data for a range of days as CSV files or a spreadsheet.
+    Export {
+        #[command(flatten)]
+        range: DateRange,
+        #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+        format: ExportFormat,
+        /// Where to write the export. Defaults to the exports folder in the data directory.
+        #[arg(long)]
+        directory: Option<PathBuf>,
+    },
+    /// Copy the database somewhere safe while it is in use.
+    Backup {
+        /// Where to write the copy. Without it, this takes a backup like the timer does: into the backups folder and the
+        /// second destination, deleting old backups the retention policy no longer keeps.
+        #[arg(long)]
+        to: Option<PathBuf>,
+    },
+    /// Replace everything in the database with a backup. What is replaced is backed up first.
+    Restore {
+        backup: PathBuf,
+    },
+    /// Walk the transaction journal and exit non-zero if anything doesn't add up.
+    Verify,
+    /// Add rows from CSV files written by `export`. Rows that are already present are skipped.
+    Import {
+        /// A single table's CSV file, or a folder of them as written by `export`.
+        path: PathBuf,
+        /// The table a single file belongs to. Defaults to the file's name.
+        #[arg(long)]
+        table: Option<String>,
+    },
+}
+
+/// Both ends are inclusive and default to today.
+#[derive(Debug, Args)]
+pub struct DateRange {
+    /// First day to include, as YYYY-MM-DD.
+    #[arg(long)]
+    from: Option<NaiveDate>,
+    /// Last day to include, as YYYY-MM-DD.
+    #[arg(long)]
+    to: Option<NaiveDate>,
+}
+
+impl DateRange {
+    fn period(&self, today: NaiveDate) -> Period {
+        Period::custom(self.from.unwrap_or(today), self.to.unwrap_or(today))
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ReportFormat {
+    Html,
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    Csv,
+    Xlsx,
+}
+
+/// How a command went, which becomes the process's exit code.
+pub enum Outcome {
+    Success,
+    /// The command ran but found a problem, like a journal that doesn't verify.
+    Failure,
+}
+
+impl Command {
+    pub fn run(self, settings: &Settings) -> anyhow::Result<Outcome> {
+        match self {
+            Command::Report { range, format, output } => report(settings, &range.period(settings.operating_hours.today()), format, output.as_deref()),
+            Command::Compare { range, against_from, against_to, format, output } => {
+                let period = range.period(settings.operating_hours.today());
+                let against = against_from.zip(against_to).map(|(from, to)| Period::custom(from, to));
+                compare(settings, period, against.unwrap_or_else(|| period.year_before()), format, output.as_deref())
+            }
+            Command::Export { range, format, directory } => {
+                export(settings, &range.period(settings.operating_hours.today()), format, &directory.unwrap_or_else(|| settings.export_directory()))
+            }
+            Command::Backup { to } => backup(settings, to),
+            Command::Restore { backup } => restore(settings, &backup),
+            Command::Verify => verify(settings),
+            Command::Import { path, table } => import(settings, &path, table),
+        }
+    }
+}
+
+fn report(settings: &Settings, period: &Period, format: ReportFormat, output: Option<&Path>) -> anyhow::Result<Outcome> {
+    let database = Database::new(settings)?;
+    let operating_hours = &settings.operating_hours;
+
+    let mut rendered = Vec::new();
+    match format {
+        ReportFormat::Html => rendered = database.render_to_html(period, operating_hours)?.into_bytes(),
+        ReportFormat::Csv => {
+            let summary = SummaryDicts::new(&database.ledger(period)?, operating_hours, period);
+            summary_export::write_csv(&summary, &mut rendered)?
+        }
+        ReportFormat::Json => {
+            let summary = SummaryDicts::new(&database.ledger(period)?, operating_hours, period);
+            serde_json::to_writer_pretty(&mut rendered, &summary_export::to_json(&summary, period, &database.chain_hash()?))?;
+            rendered.push(b'\n');
+        }
+    }
+
+    match output {
+        Some(path) => std::fs::write(path, rendered)?,
+        None => std::io::Write::write_all(&mut std::io::stdout(), &rendered)?,
+    }
+    Ok(Outcome::Success)
+}
+
+fn compare(settings: &Settings, current: Period, previous: Period, format: ReportFormat, output: Option<&Path>) -> anyhow::Result<Outcome> {
+    let database = Database::new(settings)?;
+    let comparison = Comparison::between(&database, &settings.operating_hours, current, previous)?;
+
+    let mut rendered = vec![];
+    match format {
+        ReportFormat::Html => rendered = comparison.render_to_html(&database.chain_hash()?)?.into_bytes(),
+        ReportFormat::Csv => comparison.write_csv(&mut rendered)?,
+        ReportFormat::Json => {
+            serde_json::to_writer_pretty(&mut rendered, &comparison.to_json())?;
+            rendered.push(b'\n');
+        }
+    }
+
+    match output {
+        Some(path) => std::fs::write(path, rendered)?,
+        None => std::io::Write::write_all(&mut std::io::stdout(), &rendered)?,
+    }
+    Ok(Outcome::Success)
+}
+
+fn export(settings: &Settings, period: &Period, format: ExportFormat, directory: &Path) -> anyhow::Result<Outcome> {
+    let database = Database::new(settings)?;
+    let exported = match format {
+        ExportFormat::Csv => csv_export::export(&database, period, &settings.operating_hours, directory)?,
+        ExportFormat::Xlsx => xlsx_export::export(&database, period, &settings.operating_hours, directory)?,
+    };
+
+    println!("Exported {} to {}", period.describe(), exported.display());
+    Ok(Outcome::Success)
+}
+
+fn backup(settings: &Settings, to: Option<PathBuf>) -> anyhow::Result<Outcome> {
+    let database = Database::new(settings)?;
+    let to = match to {
+        Some(to) => {
+            database.backup_to(&to)?;
+            to
+        }
+        None => database.rotate_backups(&settings.backups)?,
+    };
+    println!("Backed up the database to {}", to.display());
+    Ok(Outcome::Success)
+}
+
+fn restore(settings: &Settings, backup: &Path) -> anyhow::Result<Outcome> {
+    let mut database = Database::new(settings)?;
+    let replaced = database.restore_from(backup)?;
+    database.log_audit(AuditEntry::new(
+        COMMAND_LINE_ACTOR, AuditAction::RestoreBackup, backup.display().to_string(),
+        format!("replaced data saved to {}", replaced.display()),
+    ))?;
+
+    println!("Restored {}. What was replaced is in {}", backup.display(), replaced.display());
+    Ok(Outcome::Success)
+}
+
+fn verify(settings: &Settings) -> anyhow::Result<Outcome> {
+    let report = Database::new(settings)?.verify_journal()?;
+    println!("{report}");
+    Ok(if report.is_intact() { Outcome::Success } else { Outcome::Failure })
+}
+
+fn import(settings: &Settings, path: &Path, table: Option<String>) -> anyhow::Result<Outcome> {
+    let files = if path.is_dir() {
+        Database::sales_tables().iter()
+            .map(|x| (x.table_name().to_string(), path.join(format!("{}.csv", x.table_name()))))
+            .filter(|(_, file)| file.exists())
+            .collect_vec()
+    } else {
+        let table = table
+            .or_else(|| path.file_stem().map(|x| x.to_string_lossy().to_string()))
+            .ok_or_else(|| anyhow::anyhow!("pass --table to say which table {} belongs to", path.display()))?;
+        vec![(table, path.to_path_buf())]
+    };
+    anyhow::ensure!(!files.is_empty(), "no table CSV files found in {}", path.display());
+
+    let mut database = Database::new(settings)?;
+    for (table, file) in files {
+        let mut reader = csv::Reader::from_path(&file)?;
+        let columns = reader.headers()?.iter().map(str::to_string).collect_vec();
+        let rows: Vec<Vec<String>> = reader.records()
+            .map(|x| Ok(x?.iter().map(str::to_string).collect()))
+            .collect::<Result<_, csv::Error>>()?;
+
+        let (imported, skipped) = database.import_rows(&table, &columns.iter().map(String::as_str).collect_vec(), rows)
+            .map_err(|err| err.context(format!("importing {}", file.display())))?;
+        println!("{table}: imported {imported} rows, skipped {skipped} already present");
+    }
+    Ok(Outcome::Success)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::admission::Admission;
+    use crate::model::admission::kind::Kind;
+    use crate::settings::paths::DataDir;
+    use super::*;
+
+    #[test]
+    fn exports_import_into_a_fresh_data_dir_and_report() {
+        let root = std::env::temp_dir().join(format!("pos_cli_round_trip_{}", std::process::id()));
+        let settings = |name: &str| Settings::load(DataDir::resolve(Some(root.join(name)))).unwrap();
+        let (source, destination) = (settings("source"), settings("destination"));
+
+        let mut database = Database::new(&source).unwrap();
+        database.record_sale("Sam", &[Admission::new(Kind::ChildUnderSix, None, 1).into()]).unwrap();
+        let period = *database.business_day();
+        let exported = csv_export::export(&database, &period, &source.operating_hours, root.join("exports")).unwrap();
+
+        assert!(matches!(import(&destination, &exported, None).unwrap(), Outcome::Success));
+        let imported = Database::new(&destination).unwrap();
+        let admissions = imported.ledger(&period).unwrap().admissions;
+        assert_eq!(admissions.len(), 1);
+        assert_eq!((admissions[0].payment_method, admissions[0].sale), (None, Some(1)));
+        let output = root.join("report.json");
+        assert!(matches!(report(&destination, &period, ReportFormat::Json, Some(&output)).unwrap(), Outcome::Success));
+        assert!(imported.verify_journal().unwrap().is_intact());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,230 @@
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use std::ffi::CStr;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use chrono::{Datelike, NaiveDateTime};
+use log::info;
+use sqlite::Connection;
+use sqlite3_sys as ffi;
+use crate::settings::backups::BackupSettings;
+
+const PREFIX: &str = "pos_";
+const EXTENSION: &str = ".db";
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d_%H%M%S";
+/// Length of a timestamp written with `TIMESTAMP_FORMAT`, e.g. `2025-07-03_140000`.
+const TIMESTAMP_LENGTH: usize = 17;
+/// Pages copied per step. Between steps the source is unlocked, so the till never waits long on a backup.
+const PAGES_PER_STEP: i32 = 256;
+/// How long a backup waits on a locked database before giving up, so a lock that is never released can't hang the till.
+const LOCKED_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A backup file, recognised by its name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BackupFile {
+    pub path: PathBuf,
+    pub taken: NaiveDateTime,
+    /// Whatever follows the timestamp, e.g. `_before_v2`. Only backups without one are rotated.
+    pub suffix: String,
+}
+
+impl BackupFile {
+    pub fn file_name(directory: &Path, taken: NaiveDateTime, suffix: &str) -> PathBuf {
+        directory.join(format!("{PREFIX}{}{suffix}{EXTENSION}", taken.format(TIMESTAMP_FORMAT)))
+    }
+
+    fn parse(path: PathBuf) -> Option<Self> {
+        let name = path.file_name()?.to_str()?.strip_prefix(PREFIX)?.strip_suffix(EXTENSION)?;
+        let (timestamp, suffix) = name.split_at_checked(TIMESTAMP_LENGTH)?;
+        let taken = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT).ok()?;
+        let suffix = suffix.to_string();
+        Some(Self { path, taken, suffix })
+    }
+
+    fn is_rotated(&self) -> bool {
+        self.suffix.is_empty()
+    }
+}
+
+/// Every backup in `directory`, newest first. A directory that doesn't exist has no backups.
+pub fn list(directory: &Path) -> anyhow::Result<Vec<BackupFile>> {
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut backups = entries
+        .map(|x| Ok(x?.path()))
+        .collect::<std::io::Result<Vec<_>>>()?
+        .into_iter()
+        .filter_map(BackupFile::parse)
+        .collect::<Vec<_>>();
+    backups.sort_by_key(|x| Reverse(x.taken));
+    Ok(backups)
+}
+
+/// Which of `taken`, sorted newest first, the policy keeps: the newest in each of the most recent `keep_daily` days,
+/// `keep_weekly` weeks and `keep_monthly` months. The newest of all is always kept, even when every limit is 0.
+pub fn retained(taken: &[NaiveDateTime], policy: &BackupSettings) -> Vec<bool> {
+    let mut keep = vec![false; taken.len()];
+    if let Some(newest) = keep.first_mut() {
+        *newest = true;
+    }
+    let mut mark = |limit: usize, period: &dyn Fn(&NaiveDateTime) -> (i32, u32)| {
+        let mut seen = HashSet::new();
+        for (index, x) in taken.iter().enumerate() {
+            if seen.len() == limit {
+                break;
+            }
+            if seen.insert(period(x)) {
+                keep[index] = true;
+            }
+        }
+    };
+
+    mark(policy.keep_daily, &|x| (x.year(), x.ordinal()));
+    mark(policy.keep_weekly, &|x| (x.iso_week().year(), x.iso_week().week()));
+    mark(policy.keep_monthly, &|x| (x.year(), x.month()));
+    keep
+}
+
+/// Deletes the rotated backups in `directory` that the policy no longer keeps. Returns how many were deleted.
+pub fn prune(directory: &Path, policy: &BackupSettings) -> anyhow::Result<usize> {
+    let rotated = list(directory)?.into_iter().filter(BackupFile::is_rotated).collect::<Vec<_>>();
+    let keep = retained(&rotated.iter().map(|x| x.taken).collect::<Vec<_>>(), policy);
+
+    let mut deleted = 0;
+    for (backup, keep) in rotated.iter().zip(keep) {
+        if !keep {
+            std::fs::remove_file(&backup.path)?;
+            info!("Deleted old backup {}", backup.path.display());
+            deleted += 1;
+        }
+    }
+    Ok(deleted)
+}
+
+fn error_message(connection: *mut ffi::sqlite3) -> String {
+    // SAFETY: sqlite3_errmsg always returns a valid, nul-terminated string owned by the connection.
+    unsafe { CStr::from_ptr(ffi::sqlite3_errmsg(connection)) }.to_string_lossy().to_string()
+}
+
+/// Copies every page of `source` over `destination` with SQLite's online backup API. The copy is consistent even while
+/// the till keeps writing to `source`; if it does, the backup starts over from the changed page.
+pub fn copy(source: &Connection, destination: &Connection) -> anyhow::Result<()> {
+    copy_waiting(source, destination, LOCKED_TIMEOUT)
+}
+
+/// `copy`, giving up once either database has been locked for `timeout` in all.
+fn copy_waiting(source: &Connection, destination: &Connection, timeout: Duration) -> anyhow::Result<()> {
+    let main = c"main";
+    let started = Instant::now();
+    // SAFETY: both connections are open for the whole call and are only used through this backup until it finishes.
+    unsafe {
+        let backup = ffi::sqlite3_backup_init(destination.as_raw(), main.as_ptr(), source.as_raw(), main.as_ptr());
+        if backup.is_null() {
+            anyhow::bail!("unable to start the backup: {}", error_message(destination.as_raw()));
+        }
+
+        let mut status = ffi::SQLITE_OK;
+        while matches!(status, ffi::SQLITE_OK | ffi::SQLITE_BUSY | ffi::SQLITE_LOCKED) {
+            status = ffi::sqlite3_backup_step(backup, PAGES_PER_STEP);
+            if matches!(status, ffi::SQLITE_BUSY | ffi::SQLITE_LOCKED) {
+                if started.elapsed() >= timeout {
+                    ffi::sqlite3_backup_finish(backup);
+                    anyhow::bail!("backup gave up after the database stayed locked for {} seconds", timeout.as_secs_f32());
+                }
+                std::thread::sleep(Duration::from_millis(50));
+            }
+        }
+
+        ffi::sqlite3_backup_finish(backup);
+        if status != ffi::SQLITE_DONE {
+            anyhow::bail!("backup failed: {}", error_message(destination.as_raw()));
+        }
+    }
+    Ok(())
+}
+
+/// Backs `source` up into a new file at `path`.
+pub fn write(source: &Connection, path: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    copy(source, &Connection::open(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use super::*;
+
+    fn at(day: u32, month: u32, hour: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, month, day).unwrap().and_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn file_names_round_trip() {
+        let path = BackupFile::file_name(Path::new("backups"), at(3, 7, 14), "_before_v2");
+        assert_eq!(path, PathBuf::from("backups/pos_2025-07-03_140000_before_v2.db"));
+        let parsed = BackupFile::parse(path).unwrap();
+        assert_eq!((parsed.taken, parsed.suffix.as_str()), (at(3, 7, 14), "_before_v2"));
+        assert!(BackupFile::parse(PathBuf::from("backups/notes.txt")).is_none());
+    }
+
+    #[test]
+    fn keeps_the_newest_of_each_day_week_and_month() {
+        let policy = BackupSettings { keep_daily: 2, keep_weekly: 2, keep_monthly: 2, ..Default::default() };
+        // Newest first: two on Wednesday 2 July, one on Tuesday 1 July, one on Monday 23 June, one on 10 May.
+        let taken = [at(2, 7, 16), at(2, 7, 10), at(1, 7, 16), at(23, 6, 12), at(10, 5, 12)];
+        assert_eq!(retained(&taken, &policy), [true, false, true, true, false]);
+    }
+
+    #[test]
+    fn the_newest_backup_is_kept_even_with_every_limit_at_zero() {
+        let policy = BackupSettings { keep_daily: 0, keep_weekly: 0, keep_monthly: 0, ..Default::default() };
+        assert_eq!(retained(&[at(2, 7, 16), at(2, 7, 10), at(1, 6, 12)], &policy), [true, false, false]);
+        assert!(retained(&[], &policy).is_empty());
+
+        let directory = std::env::temp_dir().join(format!("pos_prune_all_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        for taken in [at(1, 6, 12), at(2, 7, 16)] {
+            std::fs::write(BackupFile::file_name(&directory, taken, ""), "").unwrap();
+        }
+        assert_eq!(prune(&directory, &policy).unwrap(), 1);
+        let left = list(&directory).unwrap().into_iter().map(|x| x.taken).collect::<Vec<_>>();
+        std::fs::remove_dir_all(&directory).unwrap();
+        assert_eq!(left, [at(2, 7, 16)]);
+    }
+
+    #[test]
+    fn copies_a_live_database() {
+        let source = Connection::open(":memory:").unwrap();
+        source.execute("CREATE TABLE sales (price REAL); INSERT INTO sales VALUES (4.5);").unwrap();
+        let destination = Connection::open(":memory:").unwrap();
+        copy(&source, &destination).unwrap();
+
+        let mut statement = destination.prepare("SELECT price FROM sales").unwrap();
+        statement.next().unwrap();
+        assert_eq!(statement.read::<f64, _>("price").unwrap(), 4.5);
+    }
+
+    #[test]
+    fn gives_up_on_a_database_that_stays_locked() {
+        let path = std::env::temp_dir().join(format!("pos_locked_backup_{}.db", std::process::id()));
+        let destination = Connection::open(&path).unwrap();
+        let holder = Connection::open(&path).unwrap();
+        holder.execute("BEGIN EXCLUSIVE;").unwrap();
+
+        let source = Connection::open(":memory:").unwrap();
+        source.execute("CREATE TABLE sales (price REAL);").unwrap();
+        let started = Instant::now();
+        assert!(copy_waiting(&source, &destination, Duration::from_millis(200)).is_err());
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        holder.execute("ROLLBACK;").unwrap();
+        drop((destination, holder));
+        std::fs::remove_file(path).unwrap();
+    }
+}
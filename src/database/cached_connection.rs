@@ -0,0 +1,62 @@
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use self_cell::self_cell;
+use sqlite::{Connection, State, Statement, Value};
+
+type Statements<'a> = RefCell<HashMap<String, Statement<'a>>>;
+
+self_cell!(
+    /// A connection that keeps every statement run through `execute_cached` prepared, so inserting into the same table
+    /// again only binds new values. The statements are finalized before the connection closes.
+    pub struct CachedConnection {
+        owner: Connection,
+        #[not_covariant]
+        dependent: Statements,
+    }
+);
+
+impl CachedConnection {
+    pub fn open(connection: Connection) -> Self {
+        Self::new(connection, |_| RefCell::default())
+    }
+
+    pub fn connection(&self) -> &Connection {
+        self.borrow_owner()
+    }
+
+    /// Runs `sql` to completion with `values` bound to its placeholders, preparing it only the first time.
+    pub fn execute_cached(&self, sql: &str, values: &[Value]) -> anyhow::Result<()> {
+        self.with_dependent(|connection, statements| {
+            let mut statements = statements.borrow_mut();
+            let statement = match statements.entry(sql.to_string()) {
+                Entry::Occupied(x) => x.into_mut(),
+                Entry::Vacant(x) => x.insert(connection.prepare(sql)?),
+            };
+
+            statement.reset()?;
+            statement.bind(values)?;
+            while statement.next()? != State::Done {}
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_statements() {
+        let cached = CachedConnection::open(Connection::open(":memory:").unwrap());
+        cached.connection().execute("CREATE TABLE items (name TEXT, price REAL);").unwrap();
+        for (name, price) in [("it's", 1.5), ("mug", 8.55)] {
+            cached.execute_cached("INSERT INTO items VALUES (?, ?)", &[name.into(), price.into()]).unwrap();
+        }
+
+        cached.with_dependent(|_, statements| assert_eq!(statements.borrow().len(), 1));
+        let mut statement = cached.connection().prepare("SELECT name FROM items WHERE price = 1.5").unwrap();
+        statement.next().unwrap();
+        assert_eq!(statement.read::<String, _>("name").unwrap(), "it's");
+    }
+}
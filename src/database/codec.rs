@@ -0,0 +1,37 @@
+//! Codecs for `#[db(with = "...")]` fields that don't map onto a column type by themselves. A codec is a module with
+//! `fn to_sql(&T) -> impl Mappable` and `fn from_sql(Value) -> anyhow::Result<T>`.
+
+/// Stores anything that can be displayed and parsed back, like the strum enums, as its text.
+pub mod text {
+    use std::fmt::Display;
+    use std::str::FromStr;
+    use sqlite::Value;
+    use crate::database::from_sql::FromColumn;
+
+    pub fn to_sql<T: Display>(value: &T) -> String {
+        value.to_string()
+    }
+
+    pub fn from_sql<T: FromStr>(value: Value) -> anyhow::Result<T> where T::Err: Display {
+        let text = String::from_column(value)?;
+        text.parse().map_err(|err| anyhow::anyhow!("{text:?} is not valid: {err}"))
+    }
+}
+
+/// Stores anything serde can write as JSON text, for values with more to them than one column holds.
+pub mod json {
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use sqlite::Value;
+    use crate::database::from_sql::FromColumn;
+
+    pub fn to_sql<T: Serialize>(value: &T) -> String {
+        // Only maps with keys that aren't strings fail to serialize, and nothing stored this way has one.
+        serde_json::to_string(value).expect("value can't be written as JSON")
+    }
+
+    pub fn from_sql<T: DeserializeOwned>(value: Value) -> anyhow::Result<T> {
+        let text = String::from_column(value)?;
+        serde_json::from_str(&text).map_err(|err| anyhow::anyhow!("{text:?} is not valid: {err}"))
+    }
+}
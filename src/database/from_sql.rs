@@ -1,17 +1,108 @@
-use std::error::Error;
-use sqlite::{Row, Value};
+use sqlite::{Row, RowIndex, Value};
 
 pub trait FromSql {
     fn from_sql(row: Row) -> anyhow::Result<Self> where Self: Sized;
 }
 
-pub fn from_option<'a, T>(value: &'a Value) -> anyhow::Result<Option<T>>
-where
-    T: TryFrom<&'a Value>,
-    <T as TryFrom<&'a Value>>::Error: Error + Send + Sync + 'static, {
-    if let Value::Null = value { 
-        return Ok(None);
+/// Takes one column's value out of the row, or says which column was missing.
+pub fn take_column(row: &mut Row, column: &str) -> anyhow::Result<Value> {
+    anyhow::ensure!(RowIndex::contains(&column, row), "no column named {column}");
+    Ok(row.take(column))
+}
+
+/// How a single stored field is read back out of its column.
+pub trait FromColumn: Sized {
+    fn from_column(value: Value) -> anyhow::Result<Self>;
+}
+
+macro_rules! from_column_via_integer {
+    ($ty:ty) => {
+        impl FromColumn for $ty {
+            fn from_column(value: Value) -> anyhow::Result<Self> {
+                Ok(<$ty>::try_from(i64::try_from(&value)?)?)
+            }
+        }
+    };
+}
+
+from_column_via_integer!(i8);
+from_column_via_integer!(i16);
+from_column_via_integer!(i32);
+from_column_via_integer!(i64);
+from_column_via_integer!(u16);
+
+impl FromColumn for bool {
+    fn from_column(value: Value) -> anyhow::Result<Self> {
+        Ok(i64::try_from(&value)? != 0)
     }
-    
-    Ok(Some(T::try_from(value)?))
-}
\ No newline at end of file
+}
+
+/// REAL columns hand back whole numbers as integers, so both are accepted.
+impl FromColumn for f64 {
+    fn from_column(value: Value) -> anyhow::Result<Self> {
+        match value {
+            Value::Integer(i) => Ok(i as f64),
+            value => Ok(f64::try_from(&value)?),
+        }
+    }
+}
+impl FromColumn for f32 {
+    fn from_column(value: Value) -> anyhow::Result<Self> {
+        Ok(f64::from_column(value)? as f32)
+    }
+}
+
+impl FromColumn for String {
+    fn from_column(value: Value) -> anyhow::Result<Self> {
+        Ok(String::try_from(value)?)
+    }
+}
+
+impl<T: FromColumn> FromColumn for Option<T> {
+    fn from_column(value: Value) -> anyhow::Result<Self> {
+        match value {
+            Value::Null => Ok(None),
+            value => Ok(Some(T::from_column(value)?)),
+        }
+    }
+}
+
+/// For types that already convert from `&Value`, like the enums stored by name.
+#[macro_export]
+macro_rules! from_column_via_try_from {
+    ($ty:ty) => {
+        impl $crate::database::from_sql::FromColumn for $ty {
+            fn from_column(value: sqlite::Value) -> anyhow::Result<Self> {
+                Ok(<$ty>::try_from(&value)?)
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlite::Connection;
+    use crate::database::database_object::CanBuildObjectMapper;
+    use crate::model::admission::Admission;
+    use crate::model::admission::kind::Kind;
+    use super::*;
+
+    #[test]
+    fn derived_models_round_trip() {
+        let connection = Connection::open(":memory:").unwrap();
+        let admission = Admission::new(Kind::ChildUnderSix, None, 3);
+        let mapper = admission.build_object_mapper();
+        connection.execute(mapper.schema()).unwrap();
+        let mut statement = connection.prepare(mapper.insert()).unwrap();
+        statement.bind(&mapper.values()[..]).unwrap();
+        statement.next().unwrap();
+
+        let row = connection.prepare("SELECT * FROM admissions").unwrap().into_iter().next().unwrap().unwrap();
+        assert_eq!(Admission::from_sql(row).unwrap(), admission);
+
+        connection.execute("UPDATE admissions SET kind = 'Pirate'").unwrap();
+        let row = connection.prepare("SELECT * FROM admissions").unwrap().into_iter().next().unwrap().unwrap();
+        let error = format!("{:#}", Admission::from_sql(row).unwrap_err());
+        assert!(error.contains("admissions.kind"), "{error}");
+    }
+}
@@ -24,6 +24,7 @@ schema!(i8,  "TINYINT");
 schema!(i16, "SMALLINT");
 schema!(i32, "INT");
 schema!(i64, "BIGINT");
+schema!(u16, "INT");
 
 schema!(f32, "REAL");
 schema!(f64, "DOUBLE");
@@ -0,0 +1,377 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+use sha2::{Digest, Sha256};
+use pos_derive::DbObject;
+use sqlite::{Connection, Row, Value};
+use crate::database::database_object::CanBuildObjectMapper;
+use crate::database::from_sql::FromSql;
+
+/// The previous hash of the very first entry in the chain.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// The payload of an entry recording that its row was deleted on purpose. No stored row renders to this.
+pub const DELETED_PAYLOAD: &str = "DELETED";
+
+/// Column alias used to carry a row's rowid alongside its values. It is never part of a payload.
+const ROW_ID_COLUMN: &str = "journal_row_id";
+
+/// One link in the journal. Every committed row gets exactly one entry, and each entry hashes the one before it, so
+/// editing, deleting or re-ordering anything after the fact breaks the chain.
+#[derive(Debug, Clone, PartialEq, Eq, DbObject)]
+#[db(table = "journal")]
+pub struct JournalEntry {
+    pub table_name: String,
+    pub row_id: i64,
+    pub payload: String,
+    pub previous_hash: String,
+    pub hash: String,
+}
+
+impl JournalEntry {
+    pub fn new(table_name: impl AsRef<str>, row_id: i64, payload: String, previous_hash: String) -> Self {
+        let mut entry = Self {
+            table_name: table_name.as_ref().to_string(),
+            row_id,
+            payload,
+            previous_hash,
+            hash: String::new(),
+        };
+        entry.hash = entry.compute_hash();
+        entry
+    }
+
+    pub fn compute_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.previous_hash.as_bytes());
+        hasher.update(b"\n");
+        hasher.update(self.table_name.as_bytes());
+        hasher.update(b"\n");
+        hasher.update(self.row_id.to_string().as_bytes());
+        hasher.update(b"\n");
+        hasher.update(self.payload.as_bytes());
+
+        hasher.finalize().iter().fold(String::with_capacity(64), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        })
+    }
+}
+
+fn canonical_value(value: &Value) -> String {
+    match value {
+        Value::Null => "NULL".to_string(),
+        Value::Integer(i) => i.to_string(),
+        // Debug formatting round-trips exactly, so a float can't drift between writing and verifying.
+        Value::Float(f) => format!("{f:?}"),
+        Value::String(s) => format!("{s:?}"),
+        Value::Binary(b) => b.iter().fold("x".to_string(), |mut hex, byte| {
+            let _ = write!(hex, "{byte:02x}");
+            hex
+        }),
+    }
+}
+
+/// A deterministic text rendering of a stored row, column by column in table order.
+fn canonical_row(row: &Row) -> String {
+    row.iter()
+        .filter(|(name, _)| *name != ROW_ID_COLUMN)
+        .map(|(name, value)| format!("{name}={}", canonical_value(value)))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn read_payload(connection: &Connection, table_name: &str, row_id: i64) -> anyhow::Result<String> {
+    let statement = connection.prepare(format!("SELECT rowid AS {ROW_ID_COLUMN}, * FROM {table_name} WHERE rowid = :row_id"))?;
+    let row = statement.into_iter()
+        .bind((":row_id", row_id))?
+        .next()
+        .ok_or(anyhow::Error::msg(format!("{table_name} has no row {row_id} to journal.")))??;
+
+    Ok(canonical_row(&row))
+}
+
+pub fn last_hash(connection: &Connection) -> anyhow::Result<String> {
+    let statement = connection.prepare(format!("SELECT hash FROM {} ORDER BY rowid DESC LIMIT 1", JournalEntry::TABLE_NAME))?;
+    let Some(row) = statement.into_iter().next() else {
+        return Ok(GENESIS_HASH.to_string());
+    };
+
+    Ok(row?.try_read::<&str, _>("hash")?.to_string())
+}
+
+pub fn exists(connection: &Connection) -> anyhow::Result<bool> {
+    let statement = connection.prepare("SELECT COUNT(*) AS tables FROM sqlite_master WHERE type = 'table' AND name = :name")?;
+    let tables: i64 = statement.into_iter()
+        .bind((":name", JournalEntry::TABLE_NAME))?
+        .next()
+        .ok_or(anyhow::Error::msg("COUNT returned no rows."))??
+        .try_read("tables")?;
+
+    Ok(tables > 0)
+}
+
+/// The entry that would append the row `row_id` of `table_name` to the chain.
+pub fn next_entry(connection: &Connection, table_name: &str, row_id: i64) -> anyhow::Result<JournalEntry> {
+    Ok(JournalEntry::new(
+        table_name,
+        row_id,
+        read_payload(connection, table_name, row_id)?,
+        last_hash(connection)?,
+    ))
+}
+
+/// The entry that would record the row `row_id` of `table_name` as deleted.
+pub fn deletion_entry(connection: &Connection, table_name: &str, row_id: i64) -> anyhow::Result<JournalEntry> {
+    Ok(JournalEntry::new(table_name, row_id, DELETED_PAYLOAD.to_string(), last_hash(connection)?))
+}
+
+/// Appends the row `row_id` of `table_name` to the chain and returns the new chain hash. Call this inside the same
+/// transaction as the insert it is recording.
+pub fn append(connection: &Connection, table_name: &str, row_id: i64) -> anyhow::Result<String> {
+    let entry = next_entry(connection, table_name, row_id)?;
+    let mapper = entry.build_object_mapper();
+    let mut statement = connection.prepare(mapper.insert())?;
+    statement.bind(&mapper.values()[..])?;
+    statement.next()?;
+    Ok(entry.hash)
+}
+
+/// Journals every row already in `tables`. Used once, when the journal is first created on an existing database.
+pub fn adopt_existing_rows(connection: &Connection, tables: &[&str]) -> anyhow::Result<usize> {
+    let mut adopted = 0;
+    for table_name in tables {
+        let statement = connection.prepare(format!("SELECT rowid AS {ROW_ID_COLUMN} FROM {table_name} ORDER BY rowid"))?;
+        let row_ids = statement.into_iter()
+            .map(|row| Ok(row?.try_read::<i64, _>(ROW_ID_COLUMN)?))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        for row_id in row_ids {
+            append(connection, table_name, row_id)?;
+            adopted += 1;
+        }
+    }
+
+    Ok(adopted)
+}
+
+/// The latest payload journaled for each row of `table_name`, by rowid. Deleted rows are left out.
+fn latest_payloads(connection: &Connection, table_name: &str) -> anyhow::Result<HashMap<i64, String>> {
+    let statement = connection.prepare(format!(
+        "SELECT row_id, payload FROM {} WHERE table_name = :table_name ORDER BY rowid", JournalEntry::TABLE_NAME
+    ))?;
+    let mut payloads = HashMap::new();
+    for row in statement.into_iter().bind((":table_name", table_name))? {
+        let row = row?;
+        let row_id = row.try_read::<i64, _>("row_id")?;
+        match row.try_read::<&str, _>("payload")? {
+            DELETED_PAYLOAD => payloads.remove(&row_id),
+            payload => payloads.insert(row_id, payload.to_string()),
+        };
+    }
+    Ok(payloads)
+}
+
+/// The rows of `table_name` that still match their latest journal entry, by rowid, along with that payload.
+pub fn intact_payloads(connection: &Connection, table_name: &str) -> anyhow::Result<HashMap<i64, String>> {
+    let mut journaled = latest_payloads(connection, table_name)?;
+    let statement = connection.prepare(format!("SELECT rowid AS {ROW_ID_COLUMN}, * FROM {table_name}"))?;
+    let mut intact = HashMap::new();
+    for row in statement.into_iter() {
+        let row = row?;
+        let row_id = row.try_read::<i64, _>(ROW_ID_COLUMN)?;
+        if let Some(payload) = journaled.remove(&row_id) && payload == canonical_row(&row) {
+            intact.insert(row_id, payload);
+        }
+    }
+    Ok(intact)
+}
+
+/// Journals again each row in `intact` whose contents no longer match the payload it had, after a migration rewrote
+/// it. Returns how many were journaled again.
+pub fn rejournal(connection: &Connection, table_name: &str, intact: &HashMap<i64, String>) -> anyhow::Result<usize> {
+    let mut row_ids = intact.keys().copied().collect::<Vec<_>>();
+    row_ids.sort();
+
+    let mut rejournaled = 0;
+    for row_id in row_ids {
+        if read_payload(connection, table_name, row_id)? != intact[&row_id] {
+            append(connection, table_name, row_id)?;
+            rejournaled += 1;
+        }
+    }
+    Ok(rejournaled)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct JournalReport {
+    pub entries: usize,
+    /// Journal positions (1-based) whose hash or previous hash doesn't match the chain.
+    pub broken_links: Vec<usize>,
+    /// Rows in a table that were never journaled.
+    pub unjournaled_rows: Vec<(String, i64)>,
+    /// Rows whose current contents differ from what was journaled.
+    pub altered_rows: Vec<(String, i64)>,
+    /// Journaled rows that no longer exist.
+    pub deleted_rows: Vec<(String, i64)>,
+    pub final_hash: String,
+}
+
+impl JournalReport {
+    pub fn is_intact(&self) -> bool {
+        self.broken_links.is_empty()
+            && self.unjournaled_rows.is_empty()
+            && self.altered_rows.is_empty()
+            && self.deleted_rows.is_empty()
+    }
+}
+
+impl Display for JournalReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Journal entries checked: {}", self.entries)?;
+        for position in &self.broken_links {
+            writeln!(f, "Broken link at journal entry {position}")?;
+        }
+        for (table, row_id) in &self.unjournaled_rows {
+            writeln!(f, "Row {row_id} of {table} is missing from the journal")?;
+        }
+        for (table, row_id) in &self.altered_rows {
+            writeln!(f, "Row {row_id} of {table} was changed after it was journaled")?;
+        }
+        for (table, row_id) in &self.deleted_rows {
+            writeln!(f, "Row {row_id} of {table} was deleted after it was journaled")?;
+        }
+        writeln!(f, "Status: {}", if self.is_intact() { "INTACT" } else { "TAMPERED" })?;
+        write!(f, "Final chain hash: {}", self.final_hash)
+    }
+}
+
+/// Walks the whole chain, then checks every row of `tables` against what the journal says it should be.
+pub fn verify(connection: &Connection, tables: &[&str]) -> anyhow::Result<JournalReport> {
+    let mut report = JournalReport {
+        final_hash: GENESIS_HASH.to_string(),
+        ..Default::default()
+    };
+    let mut journaled: HashMap<(String, i64), String> = HashMap::new();
+
+    let statement = connection.prepare(format!("SELECT * FROM {} ORDER BY rowid", JournalEntry::TABLE_NAME))?;
+    for row in statement.into_iter() {
+        let entry = JournalEntry::from_sql(row?)?;
+        report.entries += 1;
+
+        if entry.previous_hash != report.final_hash || entry.compute_hash() != entry.hash {
+            report.broken_links.push(report.entries);
+        }
+
+        // Carry on from the stored hash so one bad link doesn't flag everything after it.
+        report.final_hash = entry.hash;
+        // A row can be journaled again after it is changed, so only its latest entry counts.
+        let key = (entry.table_name, entry.row_id);
+        if entry.payload == DELETED_PAYLOAD {
+            journaled.remove(&key);
+        } else {
+            journaled.insert(key, entry.payload);
+        }
+    }
+
+    for table_name in tables {
+        let statement = connection.prepare(format!("SELECT rowid AS {ROW_ID_COLUMN}, * FROM {table_name} ORDER BY rowid"))?;
+        for row in statement.into_iter() {
+            let row = row?;
+            let key = (table_name.to_string(), row.try_read::<i64, _>(ROW_ID_COLUMN)?);
+            match journaled.remove(&key) {
+                None => report.unjournaled_rows.push(key),
+                Some(payload) if payload != canonical_row(&row) => report.altered_rows.push(key),
+                Some(_) => {}
+            }
+        }
+    }
+
+    report.deleted_rows = journaled.into_keys()
+        .filter(|(table_name, _)| tables.contains(&table_name.as_str()))
+        .collect();
+    report.deleted_rows.sort();
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::last_insert_row_id;
+
+    fn demo() -> Connection {
+        let connection = Connection::open(":memory:").unwrap();
+        let journal_schema = JournalEntry::new("", 0, String::new(), GENESIS_HASH.to_string()).build_object_mapper().schema();
+        connection.execute(format!("CREATE TABLE things (name TEXT NOT NULL, price REAL NOT NULL);\n{journal_schema}")).unwrap();
+        for (name, price) in [("Postcard", 1.5), ("Mug", 12.0)] {
+            connection.execute(format!("INSERT INTO things (name, price) VALUES ('{name}', {price});")).unwrap();
+            append(&connection, "things", last_insert_row_id(&connection).unwrap()).unwrap();
+        }
+        connection
+    }
+
+    #[test]
+    fn untouched_chain_is_intact() {
+        let connection = demo();
+        let report = verify(&connection, &["things"]).unwrap();
+        assert!(report.is_intact(), "{report}");
+        assert_eq!(report.entries, 2);
+        assert_eq!(report.final_hash, last_hash(&connection).unwrap());
+    }
+
+    #[test]
+    fn edited_row_is_reported() {
+        let connection = demo();
+        connection.execute("UPDATE things SET price = 0.5 WHERE name = 'Mug';").unwrap();
+        let report = verify(&connection, &["things"]).unwrap();
+        assert_eq!(report.altered_rows, vec![("things".to_string(), 2)]);
+        assert!(report.broken_links.is_empty());
+    }
+
+    #[test]
+    fn deleted_journal_entry_breaks_the_chain() {
+        let connection = demo();
+        connection.execute("DELETE FROM journal WHERE rowid = 1;").unwrap();
+        let report = verify(&connection, &["things"]).unwrap();
+        assert_eq!(report.broken_links, vec![1]);
+        assert_eq!(report.unjournaled_rows, vec![("things".to_string(), 1)]);
+    }
+
+    #[test]
+    fn journaled_changes_are_intact() {
+        let connection = demo();
+        connection.execute("UPDATE things SET price = 0.5 WHERE name = 'Mug';").unwrap();
+        append(&connection, "things", 2).unwrap();
+        connection.execute("DELETE FROM things WHERE name = 'Postcard';").unwrap();
+        let deletion = deletion_entry(&connection, "things", 1).unwrap().build_object_mapper();
+        let mut statement = connection.prepare(deletion.insert()).unwrap();
+        statement.bind(&deletion.values()[..]).unwrap();
+        statement.next().unwrap();
+
+        let report = verify(&connection, &["things"]).unwrap();
+        assert!(report.is_intact(), "{report}");
+        assert_eq!(report.entries, 4);
+    }
+
+    #[test]
+    fn rewritten_rows_are_journaled_again_unless_already_tampered_with() {
+        let connection = demo();
+        connection.execute("UPDATE things SET price = 0.5 WHERE name = 'Mug';").unwrap();
+
+        let intact = intact_payloads(&connection, "things").unwrap();
+        connection.execute("UPDATE things SET name = upper(name);").unwrap();
+        assert_eq!(rejournal(&connection, "things", &intact).unwrap(), 1);
+
+        let report = verify(&connection, &["things"]).unwrap();
+        assert_eq!(report.altered_rows, vec![("things".to_string(), 2)]);
+        assert!(report.broken_links.is_empty());
+    }
+
+    #[test]
+    fn unjournaled_row_is_reported() {
+        let connection = demo();
+        connection.execute("INSERT INTO things (name, price) VALUES ('Sticker', 2.0);").unwrap();
+        let report = verify(&connection, &["things"]).unwrap();
+        assert_eq!(report.unjournaled_rows, vec![("things".to_string(), 3)]);
+    }
+}
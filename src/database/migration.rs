@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use log::info;
+use sqlite::{Connection, Value};
+use crate::database::journal;
+use crate::database::object_mapper::ObjectMapper;
+use crate::database::to_sql::ToSql;
+
+/// One step forward for the schema. Once released, a migration's SQL must never change; fix mistakes with a new one.
+pub struct Migration {
+    pub version: i64,
+    pub description: &'static str,
+    pub sql: &'static str,
+    /// Tables whose existing rows the SQL rewrites. Rows there that matched the journal beforehand are journaled again
+    /// afterwards, so the rewrite isn't mistaken for tampering. Rows that didn't match stay flagged.
+    pub rewrites: &'static [&'static str],
+}
+
+/// Every migration, oldest first. Versions count up from 1 without gaps.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "Create the sales, audit log and journal tables",
+        sql: include_str!("migrations/0001_initial_schema.sql"),
+        rewrites: &[],
+    },
+    Migration {
+        version: 2,
+        description: "Store membership quantities as INT like the other tables",
+        sql: include_str!("migrations/0002_membership_quantity_int.sql"),
+        rewrites: &[],
+    },
+    Migration {
+        version: 3,
+        description: "Add day closes for Z-reports",
+        sql: include_str!("migrations/0003_day_closes.sql"),
+        rewrites: &[],
+    },
+    Migration {
+        version: 4,
+        description: "Store times in UTC and index them",
+        sql: include_str!("migrations/0004_utc_date_times.sql"),
+        rewrites: &["admissions", "memberships", "donations", "gift_shop_sales", "transaction_records", "audit_log", "day_closes"],
+    },
+    Migration {
+        version: 5,
+        description: "Number sales and record refunds",
+        sql: include_str!("migrations/0005_sales.sql"),
+        // The new sale column is part of every existing row's journal payload, as NULL.
+        rewrites: &["admissions", "memberships", "donations", "gift_shop_sales", "transaction_records"],
+    },
+    Migration {
+        version: 6,
+        description: "Keep the unfinished cart",
+        sql: include_str!("migrations/0006_unfinished_cart.sql"),
+        rewrites: &[],
+    },
+    Migration {
+        version: 7,
+        description: "Park carts",
+        sql: include_str!("migrations/0007_parked_carts.sql"),
+        rewrites: &[],
+    },
+    Migration {
+        version: 8,
+        description: "Add quick sale buttons",
+        sql: include_str!("migrations/0008_quick_sales.sql"),
+        rewrites: &[],
+    },
+];
+
+const VERSION_TABLE: &str = "schema_version";
+
+/// The newest schema this build understands.
+pub fn latest_version() -> i64 {
+    MIGRATIONS.last().map_or(0, |x| x.version)
+}
+
+fn create_version_table(connection: &Connection) -> anyhow::Result<()> {
+    Ok(connection.execute(format!(
+        "CREATE TABLE IF NOT EXISTS {VERSION_TABLE} (\n\tversion BIGINT NOT NULL,\n\tdescription TEXT NOT NULL,\n\tapplied_at TEXT NOT NULL\n);"
+    ))?)
+}
+
+/// The version the database is at, 0 if no migration has ever been applied.
+pub fn current_version(connection: &Connection) -> anyhow::Result<i64> {
+    create_version_table(connection)?;
+    let statement = connection.prepare(format!("SELECT COALESCE(MAX(version), 0) AS version FROM {VERSION_TABLE}"))?;
+    Ok(statement.into_iter()
+        .next()
+        .ok_or(anyhow::Error::msg("MAX returned no rows."))??
+        .try_read("version")?)
+}
+
+/// Whether anything but the version table has been created yet, i.e. whether there is data a migration could lose.
+pub fn has_tables(connection: &Connection) -> anyhow::Result<bool> {
+    let statement = connection.prepare("SELECT COUNT(*) AS tables FROM sqlite_master WHERE type = 'table' AND name != :name")?;
+    let tables: i64 = statement.into_iter()
+        .bind((":name", VERSION_TABLE))?
+        .next()
+        .ok_or(anyhow::Error::msg("COUNT returned no rows."))??
+        .try_read("tables")?;
+
+    Ok(tables > 0)
+}
+
+/// The migrations still to be applied to a database at `version`. A database newer than this build is an error, since
+/// writing to it could throw away whatever the newer build added.
+pub fn pending(version: i64) -> anyhow::Result<&'static [Migration]> {
+    if version > latest_version() {
+        anyhow::bail!(
+            "The database is at schema version {version}, but this copy of the program only understands up to version {}. \
+            It was last opened by a newer version of the program; run that version instead. Nothing has been changed.",
+            latest_version()
+        );
+    }
+
+    Ok(&MIGRATIONS[MIGRATIONS.partition_point(|x| x.version <= version)..])
+}
+
+/// What each of `tables` journaled intact before a rewrite. Empty while there is no journal yet, since then every row
+/// is journaled once the migrations are done.
+fn intact_rows<'a>(connection: &Connection, tables: &[&'a str]) -> anyhow::Result<Vec<(&'a str, HashMap<i64, String>)>> {
+    if !journal::exists(connection)? {
+        return Ok(vec![]);
+    }
+    tables.iter().map(|x| Ok((*x, journal::intact_payloads(connection, x)?))).collect()
+}
+
+/// Applies the migration and records it, all in one transaction.
+pub fn apply(connection: &Connection, migration: &Migration) -> anyhow::Result<()> {
+    info!("Migrating the database to version {}: {}", migration.version, migration.description);
+    connection.execute("BEGIN TRANSACTION;")?;
+    let res = intact_rows(connection, migration.rewrites)
+        .and_then(|intact| {
+            connection.execute(migration.sql)?;
+            for (table_name, payloads) in intact {
+                journal::rejournal(connection, table_name, &payloads)?;
+            }
+            Ok(())
+        })
+        .and_then(|_| {
+            let mut statement = connection.prepare(format!(
+                "INSERT INTO {VERSION_TABLE} (version, description, applied_at) VALUES (?, ?, ?)"
+            ))?;
+            statement.bind::<&[Value]>(&[
+                migration.version.into(),
+                migration.description.into(),
+                chrono::Local::now().to_sql(),
+            ][..])?;
+            statement.next()?;
+            Ok(())
+        });
+
+    match res {
+        Ok(_) => Ok(connection.execute("COMMIT;")?),
+        Err(err) => {
+            connection.execute("ROLLBACK;")?;
+            Err(err.context(format!("migrating to version {}", migration.version)))
+        }
+    }
+}
+
+/// Declared type of every column in `table_name`.
+fn column_types(connection: &Connection, table_name: &str) -> anyhow::Result<HashMap<String, String>> {
+    let statement = connection.prepare(format!("PRAGMA table_info({table_name})"))?;
+    Result::from_iter(statement.into_iter().map(|row| {
+        let row = row?;
+        Ok((row.try_read::<&str, _>("name")?.to_string(), row.try_read::<&str, _>("type")?.to_string()))
+    }))
+}
+
+/// Checks that every column the models write exists in the migrated database with the type the model declares, so a
+/// field added without a migration is caught at startup instead of by the first failed INSERT.
+pub fn check_models(connection: &Connection, models: &[ObjectMapper]) -> anyhow::Result<()> {
+    let expected = Connection::open(":memory:")?;
+    for model in models {
+        expected.execute(model.schema())?;
+        let actual = column_types(connection, model.table_name())?;
+        for (column, kind) in column_types(&expected, model.table_name())? {
+            match actual.get(&column) {
+                Some(found) if *found == kind => {}
+                Some(found) => anyhow::bail!("{}.{column} is {found} in the database but {kind} in the model; it needs a migration", model.table_name()),
+                None => anyhow::bail!("{}.{column} is missing from the database; it needs a migration", model.table_name()),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+
+    #[test]
+    fn migrations_count_up_from_one() {
+        for (index, migration) in MIGRATIONS.iter().enumerate() {
+            assert_eq!(migration.version, index as i64 + 1, "{}", migration.description);
+        }
+    }
+
+    #[test]
+    fn migrations_match_the_models() {
+        let connection = Connection::open(":memory:").unwrap();
+        for migration in pending(current_version(&connection).unwrap()).unwrap() {
+            apply(&connection, migration).unwrap();
+        }
+
+        assert_eq!(current_version(&connection).unwrap(), latest_version());
+        assert!(pending(latest_version()).unwrap().is_empty());
+        check_models(&connection, &Database::models()).unwrap();
+    }
+
+    #[test]
+    fn newer_database_is_refused() {
+        assert!(pending(latest_version() + 1).is_err());
+    }
+}
@@ -0,0 +1,56 @@
+-- The tables as they were before migrations existed. Every statement is IF NOT EXISTS so installs that predate
+-- schema_version pick up this version without changes.
+CREATE TABLE IF NOT EXISTS admissions (
+	kind TEXT NOT NULL,
+	payment_method TEXT,
+	quantity INT NOT NULL,
+	date_time TEXT NOT NULL,
+	hour TINYINT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS memberships (
+	kind TEXT NOT NULL,
+	payment_method TEXT NOT NULL,
+	quantity BIGINT NOT NULL,
+	date_time TEXT NOT NULL,
+	hour TINYINT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS donations (
+	payment_method TEXT NOT NULL,
+	price REAL NOT NULL,
+	date_time TEXT NOT NULL,
+	hour TINYINT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS gift_shop_sales (
+	item_description TEXT NOT NULL,
+	price REAL NOT NULL,
+	payment_method TEXT NOT NULL,
+	quantity INT NOT NULL,
+	sales_tax REAL NOT NULL,
+	date_time TEXT NOT NULL,
+	hour TINYINT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS transaction_records (
+	kind TEXT NOT NULL,
+	description TEXT NOT NULL,
+	quantity INT NOT NULL,
+	total_cost REAL NOT NULL,
+	date_time TEXT NOT NULL,
+	hour TINYINT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS audit_log (
+	actor TEXT NOT NULL,
+	action TEXT NOT NULL,
+	subject TEXT NOT NULL,
+	before TEXT,
+	after TEXT,
+	reason TEXT NOT NULL,
+	date_time TEXT NOT NULL,
+	hour TINYINT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS journal (
+	table_name TEXT NOT NULL,
+	row_id BIGINT NOT NULL,
+	payload TEXT NOT NULL,
+	previous_hash TEXT NOT NULL,
+	hash TEXT NOT NULL
+);
@@ -0,0 +1,13 @@
+-- Memberships stored their quantity as BIGINT while every other table used INT. Rebuild the table so the column
+-- matches, keeping each rowid so the journal still lines up.
+CREATE TABLE memberships_rebuilt (
+	kind TEXT NOT NULL,
+	payment_method TEXT NOT NULL,
+	quantity INT NOT NULL,
+	date_time TEXT NOT NULL,
+	hour TINYINT NOT NULL
+);
+INSERT INTO memberships_rebuilt (rowid, kind, payment_method, quantity, date_time, hour)
+SELECT rowid, kind, payment_method, quantity, date_time, hour FROM memberships;
+DROP TABLE memberships;
+ALTER TABLE memberships_rebuilt RENAME TO memberships;
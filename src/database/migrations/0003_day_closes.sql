@@ -0,0 +1,12 @@
+-- One row per closed business day, holding its Z-report. The unique index makes closing a day twice impossible.
+CREATE TABLE IF NOT EXISTS day_closes (
+	number BIGINT NOT NULL,
+	business_date TEXT NOT NULL,
+	closed_by TEXT NOT NULL,
+	expected_cash REAL NOT NULL,
+	counted_cash REAL NOT NULL,
+	report TEXT NOT NULL,
+	date_time TEXT NOT NULL,
+	hour TINYINT NOT NULL
+);
+CREATE UNIQUE INDEX IF NOT EXISTS day_closes_business_date ON day_closes (business_date);
@@ -0,0 +1,19 @@
+-- Times were stored as RFC 3339 text in whatever offset the computer had when they were written, so comparing them as
+-- text went wrong across daylight saving changes. Rewrite every one in UTC to the millisecond, which sorts as text in
+-- time order, and index them since every report selects by time. strftime returns NULL for anything it can't read,
+-- which the NOT NULL columns turn into an error rather than a lost time.
+UPDATE admissions SET date_time = strftime('%Y-%m-%dT%H:%M:%fZ', date_time);
+UPDATE memberships SET date_time = strftime('%Y-%m-%dT%H:%M:%fZ', date_time);
+UPDATE donations SET date_time = strftime('%Y-%m-%dT%H:%M:%fZ', date_time);
+UPDATE gift_shop_sales SET date_time = strftime('%Y-%m-%dT%H:%M:%fZ', date_time);
+UPDATE transaction_records SET date_time = strftime('%Y-%m-%dT%H:%M:%fZ', date_time);
+UPDATE audit_log SET date_time = strftime('%Y-%m-%dT%H:%M:%fZ', date_time);
+UPDATE day_closes SET date_time = strftime('%Y-%m-%dT%H:%M:%fZ', date_time);
+
+CREATE INDEX IF NOT EXISTS admissions_date_time ON admissions (date_time);
+CREATE INDEX IF NOT EXISTS memberships_date_time ON memberships (date_time);
+CREATE INDEX IF NOT EXISTS donations_date_time ON donations (date_time);
+CREATE INDEX IF NOT EXISTS gift_shop_sales_date_time ON gift_shop_sales (date_time);
+CREATE INDEX IF NOT EXISTS transaction_records_date_time ON transaction_records (date_time);
+CREATE INDEX IF NOT EXISTS audit_log_date_time ON audit_log (date_time);
+CREATE INDEX IF NOT EXISTS day_closes_date_time ON day_closes (date_time);
@@ -0,0 +1,38 @@
+-- Carts are recorded as numbered sales, so they can be found, reprinted, voided and refunded later. Every row a sale
+-- adds carries its number; rows from before this have none. Refunds are their own rows, recorded on the day the money
+-- went back, so closed days never change.
+CREATE TABLE IF NOT EXISTS sales (
+	number BIGINT NOT NULL,
+	cashier TEXT NOT NULL,
+	total_cost REAL NOT NULL,
+	status TEXT NOT NULL,
+	date_time TEXT NOT NULL,
+	hour TINYINT NOT NULL
+);
+CREATE UNIQUE INDEX IF NOT EXISTS sales_number ON sales (number);
+CREATE INDEX IF NOT EXISTS sales_date_time ON sales (date_time);
+
+CREATE TABLE IF NOT EXISTS refunds (
+	sale BIGINT NOT NULL,
+	kind TEXT NOT NULL,
+	description TEXT NOT NULL,
+	quantity INT NOT NULL,
+	payment_method TEXT,
+	sales_tax REAL NOT NULL,
+	amount REAL NOT NULL,
+	date_time TEXT NOT NULL,
+	hour TINYINT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS refunds_sale ON refunds (sale);
+CREATE INDEX IF NOT EXISTS refunds_date_time ON refunds (date_time);
+
+ALTER TABLE admissions ADD COLUMN sale BIGINT;
+ALTER TABLE memberships ADD COLUMN sale BIGINT;
+ALTER TABLE donations ADD COLUMN sale BIGINT;
+ALTER TABLE gift_shop_sales ADD COLUMN sale BIGINT;
+ALTER TABLE transaction_records ADD COLUMN sale BIGINT;
+CREATE INDEX IF NOT EXISTS admissions_sale ON admissions (sale);
+CREATE INDEX IF NOT EXISTS memberships_sale ON memberships (sale);
+CREATE INDEX IF NOT EXISTS donations_sale ON donations (sale);
+CREATE INDEX IF NOT EXISTS gift_shop_sales_sale ON gift_shop_sales (sale);
+CREATE INDEX IF NOT EXISTS transaction_records_sale ON transaction_records (sale);
@@ -0,0 +1,7 @@
+-- What is in the cart while a sale is being rung up, so a crash or a closed window doesn't lose it. Saving the sale
+-- empties it in the same transaction. Nothing in it has been sold yet, so it isn't journaled.
+CREATE TABLE IF NOT EXISTS unfinished_cart (
+	item TEXT NOT NULL,
+	date_time TEXT NOT NULL,
+	hour TINYINT NOT NULL
+);
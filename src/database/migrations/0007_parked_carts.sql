@@ -0,0 +1,2 @@
+-- Carts can be parked under a label and recalled later. They are kept with the cart on screen, which has no label.
+ALTER TABLE unfinished_cart ADD COLUMN parked TEXT;
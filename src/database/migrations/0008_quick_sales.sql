@@ -0,0 +1,20 @@
+-- The one-tap buttons on the sales screen, in the order they are shown. They aren't sales, so they aren't journaled.
+-- The starting set covers the most common admissions, paid however the cashier picks at the till. Gift shop buttons
+-- ring items up at their own sales tax rate, which starts out as the usual one.
+CREATE TABLE IF NOT EXISTS quick_sales (
+	position INT NOT NULL,
+	label TEXT NOT NULL,
+	category TEXT NOT NULL,
+	kind TEXT NOT NULL,
+	quantity INT NOT NULL,
+	price REAL NOT NULL,
+	payment_method TEXT,
+	sales_tax REAL NOT NULL DEFAULT 8.55
+);
+INSERT INTO quick_sales (position, label, category, kind, quantity, price, payment_method) VALUES
+	(0, '1 Adult', 'Admission', 'Adult', 1, 0.0, NULL),
+	(1, '2 Adults', 'Admission', 'Adult', 2, 0.0, NULL),
+	(2, '1 Senior', 'Admission', 'Senior', 1, 0.0, NULL),
+	(3, '2 Seniors', 'Admission', 'Senior', 2, 0.0, NULL),
+	(4, '1 Child (6-12)', 'Admission', 'Child (6-12)', 1, 0.0, NULL),
+	(5, '1 Child (Under 6)', 'Admission', 'Child (Under 6)', 1, 0.0, NULL);
@@ -1,20 +1,43 @@
 use std::any;
-use std::any::Any;
-use chrono::{Duration, Local};
+use std::collections::{btree_map, BTreeMap, BTreeSet};
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Local, NaiveDate};
+use indexmap::IndexMap;
 use itertools::Itertools;
 use log::{error, info};
 use minijinja::Environment;
 use serde::Serialize;
-use sqlite::{Connection, Value};
+use sqlite::{Connection, OpenFlags, Row, State, Value};
+use crate::database::backup::BackupFile;
+use crate::database::cached_connection::CachedConnection;
 use crate::database::database_object::CanBuildObjectMapper;
-use crate::database::from_sql::FromSql;
+use crate::database::from_sql::{FromColumn, FromSql};
+use crate::database::journal::{JournalEntry, JournalReport, GENESIS_HASH};
+use crate::database::object_mapper::ObjectMapper;
+use crate::database::query::{Aggregate, BoundSql, Comparison, Order, Query, DATE_TIME_COLUMN};
 use crate::database::to_sql::ToSql;
 use crate::model::admission::Admission;
-use crate::model::date_time_wrapper::DateTimeWrapper;
+use crate::model::audit_entry::{AuditEntry, UNKNOWN_ACTOR};
+use crate::model::cart_item::CartItem;
+use crate::model::date_time_wrapper::{read_date_time, DateTimeWrapper, WrapInDateTime};
+use crate::model::day_close::DayClose;
 use crate::model::donation::Donation;
 use crate::model::gift_shop_sale::GiftShopSale;
+use crate::model::has_total_cost::HasTotalCost;
+use crate::model::ledger::{Ledger, LedgerRow};
+use crate::model::line_item::LineItem;
 use crate::model::membership::Membership;
+use crate::model::quick_sale::QuickSale;
+use crate::model::refund::Refund;
+use crate::model::sale::{Sale, SaleLines, SaleStatus};
 use crate::model::transaction_record::TransactionRecord;
+use crate::model::unfinished_cart::{ParkedCart, UnfinishedCartItem};
+use crate::report::charts::ReportCharts;
+use crate::report::period::Period;
+use crate::settings::operating_hours::OperatingHours;
+use crate::settings::backups::BackupSettings;
+use crate::settings::Settings;
 use crate::view::summary_dicts::SummaryDicts;
 
 pub mod has_schema;
@@ -22,119 +45,1077 @@ pub mod object_mapper;
 pub mod to_sql;
 pub mod from_sql;
 pub mod database_object;
+pub mod journal;
+pub mod migration;
+pub mod backup;
+pub mod cached_connection;
+pub mod codec;
+pub mod query;
+
+/// How many audit log entries are read at a time.
+pub const AUDIT_LOG_PAGE: usize = 500;
 
 pub struct Database {
-    database: Connection,
-    daily_admissions: Vec<Admission>,
-    daily_memberships: Vec<Membership>,
-    daily_donations: Vec<Donation>,
-    daily_gift_shop_sales: Vec<GiftShopSale>,
-    daily_transactions: Vec<TransactionRecord>
+    database: CachedConnection,
+    /// The business day `daily` and `daily_summary` cover.
+    day: Period,
+    daily: Ledger,
+    daily_summary: SummaryDicts,
+    /// How `day` was closed, once it has been.
+    day_close: Option<DayClose>,
+    operating_hours: OperatingHours,
+    backup_directory: PathBuf,
 }
 
 
 impl Database {
-    const FILEPATH: &'static str = "pos.db";
+    const JOURNALED_TABLES: [&'static str; 9] = [
+        <Admission as CanBuildObjectMapper>::TABLE_NAME,
+        <Membership as CanBuildObjectMapper>::TABLE_NAME,
+        <Donation as CanBuildObjectMapper>::TABLE_NAME,
+        <GiftShopSale as CanBuildObjectMapper>::TABLE_NAME,
+        <TransactionRecord as CanBuildObjectMapper>::TABLE_NAME,
+        <AuditEntry as CanBuildObjectMapper>::TABLE_NAME,
+        <DayClose as CanBuildObjectMapper>::TABLE_NAME,
+        <Sale as CanBuildObjectMapper>::TABLE_NAME,
+        <Refund as CanBuildObjectMapper>::TABLE_NAME,
+    ];
+
+    /// Opens the database wherever the settings put it, creating and migrating it as needed.
+    pub fn new(settings: &Settings) -> anyhow::Result<Self> {
+        let path = settings.database_path();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        Self::open(Connection::open(&path)?, settings)
+    }
+
+    /// A fresh database that only lives in memory, for tests.
+    #[cfg(test)]
+    pub fn in_memory() -> Self {
+        Self::open(Connection::open(":memory:").unwrap(), &Settings::default()).unwrap()
+    }
 
-    pub fn new() -> Self {
-        let conn = Connection::open(Self::FILEPATH).expect("Can't open database");
-        Self::create_schemas(&conn);
+    fn open(conn: Connection, settings: &Settings) -> anyhow::Result<Self> {
+        let journal_existed = journal::exists(&conn)?;
+        Self::migrate(&conn, &settings.backup_directory())?;
+        if !journal_existed {
+            Self::start_journal(&conn)?;
+        }
+        let day = Period::day(settings.operating_hours.today());
         let mut start = Self {
-            database: conn,
-            daily_admissions: vec![],
-            daily_memberships: vec![],
-            daily_donations: vec![],
-            daily_gift_shop_sales: vec![],
-            daily_transactions: vec![],
+            database: CachedConnection::open(conn),
+            day,
+            daily: Ledger::default(),
+            daily_summary: SummaryDicts::empty(&settings.operating_hours, &day),
+            day_close: None,
+            operating_hours: settings.operating_hours.clone(),
+            backup_directory: settings.backup_directory(),
         };
 
         start.read_entire_day();
-        start
+        Ok(start)
     }
 
+    /// Reads today's rows and rebuilds their summary from scratch. Inserts keep both up to date after that, so this
+    /// is only needed at startup, when the day rolls over, and when rows are changed or brought in wholesale.
     fn read_entire_day(&mut self) {
-        let _ = self.select_since(<Admission as CanBuildObjectMapper>::TABLE_NAME, Duration::days(1))
-            .map(|x| self.daily_admissions = x).map_err(|x| {error!("err reading admissions: {}", x); x});
-        let _ = self.select_since(<Membership as CanBuildObjectMapper>::TABLE_NAME, Duration::days(1))
-            .map(|x| self.daily_memberships = x).map_err(|x| {error!("err reading memberships: {}", x); x});
-        let _ = self.select_since(<Donation as CanBuildObjectMapper>::TABLE_NAME, Duration::days(1))
-            .map(|x| self.daily_donations = x).map_err(|x| {error!("err reading donations{}", x); x});
-        let _ = self.select_since(<GiftShopSale as CanBuildObjectMapper>::TABLE_NAME, Duration::days(1))
-            .map(|x| self.daily_gift_shop_sales = x).map_err(|x| {error!("err reading gift shop sales: {}", x); x});
-        let _ = self.select_since(<TransactionRecord as CanBuildObjectMapper>::TABLE_NAME, Duration::days(1))
-            .map(|x| self.daily_transactions = x).map_err(|x| {error!("err reading transactions: {}", x); x});
-    }
-    fn create_schemas(connection: &Connection) {
-        let defaults = [
-            DateTimeWrapper::new(Admission::default()).build_object_mapper().schema(),
-            DateTimeWrapper::new(Donation::default()).build_object_mapper().schema(),
-            DateTimeWrapper::new(GiftShopSale::default()).build_object_mapper().schema(),
-            DateTimeWrapper::new(Membership::default()).build_object_mapper().schema(),
-            DateTimeWrapper::new(TransactionRecord::default()).build_object_mapper().schema(),
-        ];
-        
-        info!("Creating schemas");
-        connection.execute(defaults.iter().join("\n")).expect("Unable to create database.")
+        self.day = Period::day(self.operating_hours.today());
+        let (from, to) = (self.day.start(&self.operating_hours), self.day.end(&self.operating_hours));
+        let _ = self.select(&Query::new().between(from, to))
+            .map(|x| self.daily.admissions = x).map_err(|x| {error!("err reading admissions: {}", x); x});
+        let _ = self.select(&Query::new().between(from, to))
+            .map(|x| self.daily.memberships = x).map_err(|x| {error!("err reading memberships: {}", x); x});
+        let _ = self.select(&Query::new().between(from, to))
+            .map(|x| self.daily.donations = x).map_err(|x| {error!("err reading donations{}", x); x});
+        let _ = self.select(&Query::new().between(from, to))
+            .map(|x| self.daily.gift_shop_sales = x).map_err(|x| {error!("err reading gift shop sales: {}", x); x});
+        let _ = self.select(&Query::new().between(from, to))
+            .map(|x| self.daily.transactions = x).map_err(|x| {error!("err reading transactions: {}", x); x});
+        let _ = self.select(&Query::new().between(from, to))
+            .map(|x| self.daily.refunds = x).map_err(|x| {error!("err reading refunds: {}", x); x});
+        self.daily_summary = SummaryDicts::new(&self.daily, &self.operating_hours, &self.day);
+        let _ = self.close_of(self.day.from)
+            .map(|x| self.day_close = x.map(|x| x.element().clone())).map_err(|x| {error!("err reading day closes: {}", x); x});
+    }
+
+    /// Starts over with the new business day's rows once the current one has ended. Returns whether it did.
+    pub fn roll_over_day(&mut self) -> bool {
+        if self.day.contains(Local::now(), &self.operating_hours) {
+            return false;
+        }
+
+        info!("Starting the business day of {}", self.operating_hours.today());
+        self.read_entire_day();
+        true
+    }
+
+    /// Adds a row that was just inserted to today's ledger and summary, or starts a new day if this one is over.
+    fn add_to_day<T: LedgerRow>(&mut self, row: DateTimeWrapper<T>) {
+        // Rolling over reads the new day's rows fresh, and that includes this one.
+        if !self.roll_over_day() && self.day.contains(row.date_time(), &self.operating_hours) {
+            T::summarize(&row, &mut self.daily_summary);
+            T::rows(&mut self.daily).push(row);
+        }
+    }
+
+    /// The close of the business day `date`, if it has been closed.
+    fn close_of(&self, date: NaiveDate) -> anyhow::Result<Option<DateTimeWrapper<DayClose>>> {
+        Ok(self.select(&Query::<DayClose>::new().equals("business_date", date.to_string()).limit(1))?.pop())
+    }
+
+    /// Fails if `at` falls in a business day that has been closed, since nothing in a closed day may change.
+    fn ensure_open(&self, at: DateTime<Local>) -> anyhow::Result<()> {
+        let date = self.operating_hours.business_date(self.operating_hours.timezone.clock(at));
+        if let Some(close) = self.close_of(date)? {
+            anyhow::bail!("the business day of {date} was closed with Z-report #{}, so it can't be changed", close.number);
+        }
+        Ok(())
+    }
+
+    /// How the current business day was closed, or `None` while it is still open.
+    pub fn day_close(&self) -> Option<&DayClose> {
+        self.day_close.as_ref()
+    }
+
+    /// The number the next Z-report will get. They count up from 1 and never repeat.
+    pub fn next_close_number(&self) -> anyhow::Result<i64> {
+        let last = self.select(&Query::<DayClose>::new().order_by("number", Order::Descending).limit(1))?;
+        Ok(last.first().map_or(0, |x| x.number) + 1)
+    }
+
+    /// Records that a business day is closed, after which none of its rows can be added, changed or imported.
+    pub fn close_day(&mut self, close: DayClose) -> anyhow::Result<()> {
+        if let Some(existing) = self.close_of(close.business_date)? {
+            anyhow::bail!("the business day of {} was already closed with Z-report #{}", close.business_date, existing.number);
+        }
+
+        info!("Closing the business day of {} with Z-report #{}", close.business_date, close.number);
+        self.insert_and_journal(DayClose::TABLE_NAME, close.clone().wrapped_in_date_time().build_object_mapper())?;
+        if close.business_date == self.day.from {
+            self.day_close = Some(close);
+        }
+        Ok(())
+    }
+
+    /// The business day `daily` and the daily summary cover.
+    pub fn business_day(&self) -> &Period {
+        &self.day
+    }
+    /// Object mappers for default rows of every table that records sales, which is enough to know each table's name
+    /// and columns.
+    pub fn sales_tables() -> [ObjectMapper; 7] {
+        [
+            DateTimeWrapper::new(Admission::default()).build_object_mapper(),
+            DateTimeWrapper::new(Membership::default()).build_object_mapper(),
+            DateTimeWrapper::new(Donation::default()).build_object_mapper(),
+            DateTimeWrapper::new(GiftShopSale::default()).build_object_mapper(),
+            DateTimeWrapper::new(TransactionRecord::default()).build_object_mapper(),
+            DateTimeWrapper::new(Sale::default()).build_object_mapper(),
+            DateTimeWrapper::new(Refund::default()).build_object_mapper(),
+        ]
+    }
+
+    /// Object mappers for every table the program writes to.
+    pub fn models() -> Vec<ObjectMapper> {
+        Self::sales_tables().into_iter().chain([
+            DateTimeWrapper::new(AuditEntry::default()).build_object_mapper(),
+            JournalEntry::new("", 0, String::new(), GENESIS_HASH.to_string()).build_object_mapper(),
+            DateTimeWrapper::new(DayClose::default()).build_object_mapper(),
+            DateTimeWrapper::new(UnfinishedCartItem { item: CartItem::Admission(Default::default()), parked: None }).build_object_mapper(),
+            QuickSale::default().build_object_mapper(),
+        ]).collect()
+    }
+
+    /// Brings the schema up to date, backing up first if there is anything to lose, then checks it against the models.
+    fn migrate(connection: &Connection, backup_directory: &Path) -> anyhow::Result<()> {
+        let pending = migration::pending(migration::current_version(connection)?)?;
+        if let Some(first) = pending.first() && migration::has_tables(connection)? {
+            let path = BackupFile::file_name(backup_directory, Local::now().naive_local(), &format!("_before_v{}", first.version));
+            backup::write(connection, &path)?;
+            info!("Backed up the database to {} before migrating", path.display());
+        }
+
+        for step in pending {
+            migration::apply(connection, step)?;
+        }
+        migration::check_models(connection, &Self::models())
     }
     
-    pub fn insert<T: CanBuildObjectMapper+Any>(&mut self, object: DateTimeWrapper<T>) -> anyhow::Result<()> {
-        info!("Logging a {}", any::type_name::<T>());
-        let res = Ok(self.database.execute(object.build_object_mapper().insert())?);
+    /// Rows that were already in the database when the journal was introduced are journaled once, up front, so the
+    /// chain covers the whole history.
+    fn start_journal(connection: &Connection) -> anyhow::Result<()> {
+        connection.execute("BEGIN TRANSACTION;")?;
+        match journal::adopt_existing_rows(connection, &Self::JOURNALED_TABLES) {
+            Ok(adopted) => {
+                connection.execute("COMMIT;")?;
+                if adopted > 0 {
+                    info!("Journaled {adopted} pre-existing rows");
+                }
+                Ok(())
+            }
+            Err(err) => {
+                connection.execute("ROLLBACK;")?;
+                Err(err)
+            }
+        }
+    }
+
+    fn connection(&self) -> &Connection {
+        self.database.connection()
+    }
+
+    /// Inserts the mapped row with its values bound, reusing the table's prepared INSERT.
+    fn insert_mapped(&self, object_mapper: &ObjectMapper) -> anyhow::Result<()> {
+        self.database.execute_cached(&object_mapper.insert(), &object_mapper.values())
+    }
+
+    /// Runs `work` in a transaction, committing if it succeeds and rolling back if it doesn't. Inside another
+    /// transaction it becomes part of that one, and only its own changes are rolled back if it fails.
+    fn in_transaction<R>(&self, work: impl FnOnce() -> anyhow::Result<R>) -> anyhow::Result<R> {
+        self.connection().execute("SAVEPOINT work;")?;
+        match work() {
+            Ok(result) => {
+                self.connection().execute("RELEASE work;")?;
+                Ok(result)
+            }
+            Err(err) => {
+                self.connection().execute("ROLLBACK TO work; RELEASE work;")?;
+                Err(err)
+            }
+        }
+    }
+
+    /// Inserts the row and journals it, for use inside a transaction.
+    fn insert_journaled(&self, table_name: &str, object_mapper: ObjectMapper) -> anyhow::Result<()> {
+        self.insert_mapped(&object_mapper)?;
+        let entry = journal::next_entry(self.connection(), table_name, last_insert_row_id(self.connection())?)?;
+        self.insert_mapped(&entry.build_object_mapper())
+    }
+
+    /// Inserts the row and its journal entry together, so neither can exist without the other.
+    fn insert_and_journal(&self, table_name: &str, object_mapper: ObjectMapper) -> anyhow::Result<()> {
+        self.in_transaction(|| self.insert_journaled(table_name, object_mapper))
+    }
+
+    fn insert_rows<T: CanBuildObjectMapper>(&self, rows: &[DateTimeWrapper<T>]) -> anyhow::Result<()> {
+        rows.iter().try_for_each(|x| self.insert_journaled(T::TABLE_NAME, x.build_object_mapper()))
+    }
+
+    /// Adds rows that were just inserted to today's ledger and summary.
+    fn add_ledger_to_day(&mut self, ledger: Ledger) {
+        // Rolling over reads the new day's rows fresh, and that includes these.
+        if self.roll_over_day() {
+            return;
+        }
+        ledger.admissions.into_iter().for_each(|x| self.add_to_day(x));
+        ledger.memberships.into_iter().for_each(|x| self.add_to_day(x));
+        ledger.donations.into_iter().for_each(|x| self.add_to_day(x));
+        ledger.gift_shop_sales.into_iter().for_each(|x| self.add_to_day(x));
+        ledger.transactions.into_iter().for_each(|x| self.add_to_day(x));
+        ledger.refunds.into_iter().for_each(|x| self.add_to_day(x));
+    }
+
+    /// The number the next sale will get. They count up from 1 and never repeat.
+    fn next_sale_number(&self) -> anyhow::Result<i64> {
+        let last = self.select(&Query::<Sale>::new().order_by("number", Order::Descending).limit(1))?;
+        Ok(last.first().map_or(0, |x| x.number) + 1)
+    }
+
+    /// The quick sale buttons, in the order they are shown.
+    pub fn quick_sales(&self) -> anyhow::Result<Vec<QuickSale>> {
+        self.fetch(Query::<QuickSale>::new().order_by("position", Order::Ascending).select()?, QuickSale::from_sql)
+    }
+
+    /// Replaces the quick sale buttons with `buttons`, numbering them in the order given.
+    pub fn save_quick_sales(&self, buttons: &[QuickSale]) -> anyhow::Result<()> {
+        for button in buttons {
+            button.check()?;
+        }
+        self.in_transaction(|| {
+            let delete = Query::<QuickSale>::new().delete()?;
+            self.database.execute_cached(&delete.sql, &delete.values)?;
+            buttons.iter().enumerate().try_for_each(|(position, x)| {
+                let button = QuickSale { position: position as u16, ..x.clone() };
+                self.insert_mapped(&button.build_object_mapper())
+            })
+        })
+    }
+
+    /// Keeps `cart` as the unfinished cart in place of whatever was kept before, so it survives the program closing.
+    pub fn save_cart(&self, cart: &[CartItem]) -> anyhow::Result<()> {
+        self.in_transaction(|| {
+            self.clear_cart()?;
+            self.insert_cart(cart, None)
+        })
+    }
+
+    fn insert_cart(&self, cart: &[CartItem], parked: Option<&str>) -> anyhow::Result<()> {
+        cart.iter().try_for_each(|x| {
+            let row = UnfinishedCartItem { item: x.clone(), parked: parked.map(str::to_string) };
+            self.insert_mapped(&row.wrapped_in_date_time().build_object_mapper())
+        })
+    }
+
+    /// Empties the unfinished cart, leaving parked carts alone.
+    fn clear_cart(&self) -> anyhow::Result<()> {
+        let delete = Query::<UnfinishedCartItem>::new().equals("parked", None::<String>).delete()?;
+        self.database.execute_cached(&delete.sql, &delete.values)
+    }
+
+    /// The cart that was left unfinished when the program last closed, and when it last changed. None if it was empty.
+    pub fn unfinished_cart(&self) -> anyhow::Result<Option<(DateTime<Local>, Vec<CartItem>)>> {
+        let query = Query::<UnfinishedCartItem>::new().equals("parked", None::<String>).order_by("rowid", Order::Ascending);
+        let rows = self.select(&query)?;
+        let Some(changed) = rows.iter().map(|x| x.date_time()).max() else {
+            return Ok(None);
+        };
+        Ok(Some((changed, rows.into_iter().map(|x| x.element().item.clone()).collect())))
+    }
+
+    /// Sets `cart` aside under `label`, or "Cart 1", "Cart 2" and so on if there is no label, and empties the
+    /// unfinished cart. Returns the label it was parked under.
+    pub fn park_cart(&self, label: &str, cart: &[CartItem]) -> anyhow::Result<String> {
+        anyhow::ensure!(!cart.is_empty(), "there is nothing in the cart");
+        let taken = self.parked_carts()?.into_iter().map(|x| x.label).collect_vec();
+        let label = match label.trim() {
+            "" => (1..).map(|x| format!("Cart {x}")).find(|x| !taken.contains(x)).unwrap_or_default(),
+            label => label.to_string(),
+        };
+        anyhow::ensure!(!taken.contains(&label), "a cart called {label:?} is already parked");
+
+        info!("Parking a cart of {} items as {label:?}", cart.len());
+        self.in_transaction(|| {
+            self.clear_cart()?;
+            self.insert_cart(cart, Some(&label))
+        })?;
+        Ok(label)
+    }
+
+    /// Every parked cart, oldest first.
+    pub fn parked_carts(&self) -> anyhow::Result<Vec<ParkedCart>> {
+        let query = Query::<UnfinishedCartItem>::new()
+            .filter("parked", Comparison::NotEqual, None::<String>)
+            .order_by("rowid", Order::Ascending);
+        let mut carts: IndexMap<String, ParkedCart> = IndexMap::new();
+        for row in self.select(&query)? {
+            let label = row.parked.clone().unwrap_or_default();
+            carts.entry(label.clone())
+                .or_insert_with(|| ParkedCart { label, parked: row.date_time(), items: vec![] })
+                .items
+                .push(row.item.clone());
+        }
+        Ok(carts.into_values().collect())
+    }
+
+    /// Makes the cart parked under `label` the unfinished cart again, and returns it. The unfinished cart has to be
+    /// empty first, so nothing in it is lost.
+    pub fn recall_cart(&self, label: &str) -> anyhow::Result<Vec<CartItem>> {
+        anyhow::ensure!(self.unfinished_cart()?.is_none(), "park or save the cart on screen first");
+        let query = Query::<UnfinishedCartItem>::new().equals("parked", label);
+        let recall = query.update(&[("parked", Value::Null)])?;
+        self.database.execute_cached(&recall.sql, &recall.values)?;
+
+        info!("Recalled the cart parked as {label:?}");
+        self.unfinished_cart()?.map(|(_, cart)| cart).ok_or_else(|| anyhow::anyhow!("no cart called {label:?} is parked"))
+    }
+
+    /// Records everything in the cart as one numbered sale by `cashier`, with a transaction record for each item. It
+    /// all goes in one transaction, along with emptying the unfinished cart, so a sale is never half recorded or
+    /// restored after it was saved. Returns the sale.
+    pub fn record_sale(&mut self, cashier: &str, cart: &[CartItem]) -> anyhow::Result<Sale> {
+        anyhow::ensure!(!cart.is_empty(), "there is nothing in the cart");
+        self.ensure_open(Local::now())?;
+
+        let cashier = cashier.trim();
+        let sale = Sale {
+            number: self.next_sale_number()?,
+            cashier: if cashier.is_empty() { UNKNOWN_ACTOR.to_string() } else { cashier.to_string() },
+            total_cost: cart.iter().map(|x| x.total_cost()).sum(),
+            status: SaleStatus::Completed,
+        };
+        let mut rows = Ledger::default();
+        for item in cart {
+            let item = item.in_sale(sale.number);
+            rows.transactions.push(item.as_transaction_record().wrapped_in_date_time());
+            match item {
+                CartItem::Admission(x) => rows.admissions.push(x.wrapped_in_date_time()),
+                CartItem::Membership(x) => rows.memberships.push(x.wrapped_in_date_time()),
+                CartItem::Donation(x) => rows.donations.push(x.wrapped_in_date_time()),
+                CartItem::GiftShopSale(x) => rows.gift_shop_sales.push(x.wrapped_in_date_time()),
+            }
+        }
+
+        info!("Recording sale #{} of {} items", sale.number, cart.len());
+        self.in_transaction(|| {
+            self.insert_journaled(Sale::TABLE_NAME, sale.clone().wrapped_in_date_time().build_object_mapper())?;
+            self.insert_rows(&rows.admissions)?;
+            self.insert_rows(&rows.memberships)?;
+            self.insert_rows(&rows.donations)?;
+            self.insert_rows(&rows.gift_shop_sales)?;
+            self.insert_rows(&rows.transactions)?;
+            self.clear_cart()
+        })?;
+        self.add_ledger_to_day(rows);
+        Ok(sale)
+    }
+
+    /// A sale with every line it rang up and every refund given for it since.
+    pub fn sale_lines(&self, number: i64) -> anyhow::Result<SaleLines> {
+        let sale = self.select(&Query::<Sale>::new().equals("number", number).limit(1))?.pop()
+            .ok_or_else(|| anyhow::anyhow!("there is no sale #{number}"))?;
+        let ledger = Ledger {
+            admissions: self.select(&Query::new().equals("sale", number))?,
+            memberships: self.select(&Query::new().equals("sale", number))?,
+            donations: self.select(&Query::new().equals("sale", number))?,
+            gift_shop_sales: self.select(&Query::new().equals("sale", number))?,
+            transactions: vec![],
+            refunds: self.select(&Query::new().equals("sale", number))?,
+        };
+
+        Ok(SaleLines { sale: Some(sale), lines: LineItem::from_ledger(&ledger) })
+    }
+
+    /// Every sale made or refunded in `period` with all of its lines, newest first. Rows from before sales were
+    /// numbered come as a line each.
+    pub fn history(&self, period: &Period) -> anyhow::Result<Vec<SaleLines>> {
+        let (from, to) = (period.start(&self.operating_hours), period.end(&self.operating_hours));
+        let ledger = self.ledger(period)?;
+
+        let mut sales: BTreeMap<i64, SaleLines> = self.select(&Query::<Sale>::new().between(from, to))?
+            .into_iter()
+            .map(|x| (x.number, SaleLines { sale: Some(x), lines: vec![] }))
+            .collect();
+        let mut unnumbered = vec![];
+        let mut elsewhere = vec![];
+        for line in LineItem::from_ledger(&Ledger { refunds: vec![], ..ledger.clone() }) {
+            match line.sale {
+                Some(number) => match sales.get_mut(&number) {
+                    Some(sale) => sale.lines.push(line),
+                    None => elsewhere.push(number),
+                },
+                None => unnumbered.push(SaleLines { sale: None, lines: vec![line] }),
+            }
+        }
+
+        // Refunds for this period's sales, whenever they were given.
+        if let (Some(first), Some(last)) = (sales.keys().next().copied(), sales.keys().last().copied()) {
+            let refunds = self.select(&Query::<Refund>::new()
+                .filter("sale", Comparison::GreaterOrEqual, first)
+                .filter("sale", Comparison::LessOrEqual, last))?;
+            for line in LineItem::from_ledger(&Ledger { refunds, ..Default::default() }) {
+                if let Some(sale) = line.sale.and_then(|x| sales.get_mut(&x)) {
+                    sale.lines.push(line);
+                }
+            }
+        }
+        // Refunds given in this period for earlier sales bring the whole sale along.
+        elsewhere.extend(ledger.refunds.iter().map(|x| x.sale));
+        for number in elsewhere.into_iter().unique() {
+            if let btree_map::Entry::Vacant(entry) = sales.entry(number) {
+                entry.insert(self.sale_lines(number)?);
+            }
+        }
+
+        let mut history = sales.into_values().chain(unnumbered).collect_vec();
+        history.sort_by_key(|x| Reverse(x.date_time()));
+        Ok(history)
+    }
+
+    /// The gift shop item descriptions containing `search`, ignoring case. `%` and `_` in it match anything, as in SQL.
+    pub fn gift_shop_items_like(&self, search: &str) -> anyhow::Result<BTreeSet<String>> {
+        let query = Query::<GiftShopSale>::new().filter("item_description", Comparison::Like, format!("%{search}%"));
+        Ok(self.select(&query)?.into_iter().map(|x| x.element().item_description.clone()).collect())
+    }
+
+    /// What was taken in `period` and what was given back in it.
+    pub fn takings(&self, period: &Period) -> anyhow::Result<(f64, f64)> {
+        let records = Query::<TransactionRecord>::new().between(period.start(&self.operating_hours), period.end(&self.operating_hours));
+        let sum = |comparison| -> anyhow::Result<f64> {
+            let totals = self.total(&records.clone().filter("total_cost", comparison, 0.0), Aggregate::Sum("total_cost"), None)?;
+            Ok(totals.first().map(|(_, total)| *total).unwrap_or_default())
+        };
+        Ok((sum(Comparison::Greater)?, -sum(Comparison::Less)?))
+    }
+
+    /// Takes back a sale from the current business day as if it had never been rung up. Its rows are deleted, with
+    /// the deletions journaled, and the sale itself is kept, marked as voided. Sales from earlier days are refunded
+    /// instead, even if their day was never closed, so no earlier day's totals change. Returns the sale as it was.
+    pub fn void_sale(&mut self, number: i64) -> anyhow::Result<SaleLines> {
+        let voided = self.sale_lines(number)?;
+        let Some(sale) = &voided.sale else {
+            anyhow::bail!("there is no sale #{number}");
+        };
+        anyhow::ensure!(sale.status == SaleStatus::Completed, "sale #{number} was already voided");
+        anyhow::ensure!(!voided.is_refunded(), "sale #{number} was refunded, so it can't be voided");
+        self.roll_over_day();
+        anyhow::ensure!(self.day.contains(sale.date_time(), &self.operating_hours),
+                        "sale #{number} is from an earlier business day, so it can't be voided; refund it instead");
+
+        info!("Voiding sale #{number}");
+        self.in_transaction(|| {
+            self.delete(&Query::<Admission>::new().equals("sale", number))?;
+            self.delete(&Query::<Membership>::new().equals("sale", number))?;
+            self.delete(&Query::<Donation>::new().equals("sale", number))?;
+            self.delete(&Query::<GiftShopSale>::new().equals("sale", number))?;
+            self.delete(&Query::<TransactionRecord>::new().equals("sale", number))?;
+            self.update(&Query::<Sale>::new().equals("number", number), &[("status", SaleStatus::Voided.to_string().to_sql())])
+        })?;
 
-        //todo: This is horribly inefficient, I should just be inserting where it makes sense.
         self.read_entire_day();
-        res
+        Ok(voided)
     }
 
-    pub fn select_since<T: FromSql>(&self, table_name: impl AsRef<str>, since: Duration) -> Result<Vec<T>, anyhow::Error> {
-        let duration = Local::now() - since;
+    /// Gives back everything paid for an earlier sale. Each line gets a refund and a negative transaction record, both
+    /// on the current business day, so the day of the sale stays as it was closed. Returns the refunds.
+    pub fn refund_sale(&mut self, number: i64) -> anyhow::Result<Vec<Refund>> {
+        let lines = self.sale_lines(number)?;
+        let Some(sale) = &lines.sale else {
+            anyhow::bail!("there is no sale #{number}");
+        };
+        anyhow::ensure!(sale.status == SaleStatus::Completed, "sale #{number} was voided, so there is nothing to refund");
+        anyhow::ensure!(!lines.is_refunded(), "sale #{number} was already refunded");
+        self.ensure_open(Local::now())?;
+
+        let refunds = lines.lines.iter().filter(|x| x.total != 0.0).map(|x| Refund::of(x, number)).collect_vec();
+        anyhow::ensure!(!refunds.is_empty(), "nothing was paid for sale #{number}, so there is nothing to refund");
+        let rows = Ledger {
+            transactions: refunds.iter().map(|x| x.as_transaction_record().wrapped_in_date_time()).collect(),
+            refunds: refunds.iter().cloned().map(WrapInDateTime::wrapped_in_date_time).collect(),
+            ..Default::default()
+        };
+
+        info!("Refunding sale #{number}");
+        self.in_transaction(|| {
+            self.insert_rows(&rows.refunds)?;
+            self.insert_rows(&rows.transactions)
+        })?;
+        self.add_ledger_to_day(rows);
+        Ok(refunds)
+    }
+
+    /// What sale `number` is made of as it now stands, as the items it was rung up as.
+    pub fn sale_items(&self, number: i64) -> anyhow::Result<Vec<CartItem>> {
+        fn items<T: CanBuildObjectMapper+FromSql+Clone+Into<CartItem>>(database: &Database, number: i64) -> anyhow::Result<Vec<CartItem>> {
+            let query = Query::<T>::new().equals("sale", number).order_by("rowid", Order::Ascending);
+            Ok(database.select(&query)?.into_iter().map(|x| x.element().clone().into()).collect())
+        }
+
+        [items::<Admission>, items::<Membership>, items::<Donation>, items::<GiftShopSale>]
+            .into_iter()
+            .map(|x| x(self, number))
+            .flatten_ok()
+            .collect()
+    }
+
+    /// Changes one item of sale `number` from `before` to `after`, e.g. to the payment method the visitor actually used.
+    /// The item's row and its transaction record are both rewritten in place and journaled again, along with the
+    /// sale's total, all in one transaction. Sales in a closed business day, or that were voided or refunded, can't be
+    /// corrected.
+    pub fn correct_item(&mut self, number: i64, before: &CartItem, after: &CartItem) -> anyhow::Result<()> {
+        let lines = self.sale_lines(number)?;
+        let Some(sale) = &lines.sale else {
+            anyhow::bail!("there is no sale #{number}");
+        };
+        anyhow::ensure!(sale.status == SaleStatus::Completed, "sale #{number} was voided");
+        anyhow::ensure!(!lines.is_refunded(), "sale #{number} was refunded, so it can't be corrected");
+        let (before, after) = (before.in_sale(number), after.in_sale(number));
+        anyhow::ensure!(before != after, "nothing was changed");
+        anyhow::ensure!(after.as_transaction_record().quantity > 0, "the quantity can't be 0");
+        if let CartItem::Admission(x) = &after {
+            anyhow::ensure!(x.kind.is_free() || x.payment_method.is_some(), "a paid admission needs a payment method");
+        }
+
+        info!("Correcting sale #{number}: {} became {}", before.describe(), after.describe());
+        let total = sale.total_cost - before.total_cost() + after.total_cost();
+        self.in_transaction(|| {
+            match (&before, &after) {
+                (CartItem::Admission(x), CartItem::Admission(y)) => self.replace_row(number, x, y),
+                (CartItem::Membership(x), CartItem::Membership(y)) => self.replace_row(number, x, y),
+                (CartItem::Donation(x), CartItem::Donation(y)) => self.replace_row(number, x, y),
+                (CartItem::GiftShopSale(x), CartItem::GiftShopSale(y)) => self.replace_row(number, x, y),
+                _ => Err(anyhow::anyhow!("a correction can't turn one kind of item into another")),
+            }?;
+            self.replace_row(number, &before.as_transaction_record(), &after.as_transaction_record())?;
+            self.update(&Query::<Sale>::new().equals("number", number), &[("total_cost", total.to_sql())])
+        })?;
+
+        self.read_entire_day();
+        Ok(())
+    }
+
+    /// Rewrites the first row in sale `number` that holds exactly `before` to hold `after` instead, for use inside a
+    /// transaction. Rows that are alike are interchangeable, so it doesn't matter which one that is.
+    fn replace_row<T: CanBuildObjectMapper+FromSql+PartialEq>(&self, number: i64, before: &T, after: &T) -> anyhow::Result<()> {
+        let query = Query::<T>::new().equals("sale", number).order_by("rowid", Order::Ascending);
+        let row_ids = self.fetch(query.row_ids()?, |row| Ok(row.try_read::<i64, _>("rowid")?))?;
+        let row_id = row_ids.into_iter()
+            .zip(self.select(&query)?)
+            .find(|(_, x)| x.element() == before)
+            .map(|(row_id, _)| row_id)
+            .ok_or_else(|| anyhow::anyhow!("sale #{number} no longer has that row in {}", T::TABLE_NAME))?;
+
+        let query = Query::<T>::new().equals("rowid", row_id);
+        self.change(&query, query.replace(&after.build_object_mapper())?, journal::next_entry)?;
+        Ok(())
+    }
+
+    /// Inserts rows exported from another copy of this program, journaling each one. Rows that are already present
+    /// column for column are skipped, so importing the same file twice does no harm. Everything is imported in one
+    /// transaction. Returns how many rows were imported and how many were skipped.
+    pub fn import_rows(&mut self, table_name: &str, columns: &[&str], rows: impl IntoIterator<Item=Vec<String>>) -> anyhow::Result<(usize, usize)> {
+        let table = Self::sales_tables().into_iter().find(|x| x.table_name() == table_name)
+            .ok_or_else(|| anyhow::anyhow!("{table_name} is not a table that can be imported"))?;
+        if let Some(unknown) = columns.iter().find(|x| !table.column_names().contains(*x)) {
+            anyhow::bail!("{table_name} has no column named {unknown}");
+        }
+        let date_time = columns.iter().position(|x| *x == DATE_TIME_COLUMN);
+        // CSV has no NULL, so exports write an empty field for it.
+        let nullable = columns.iter().map(|x| table.is_nullable(x)).collect_vec();
+
+        let counts = self.in_transaction(|| {
+            let mut existing = self.connection().prepare(format!(
+                "SELECT COUNT(*) AS matches FROM {table_name} WHERE {}",
+                columns.iter().map(|x| format!("{x} IS ?")).join(" AND ")
+            ))?;
+            let insert = format!(
+                "INSERT INTO {table_name} ({}) VALUES ({})",
+                columns.join(", "),
+                columns.iter().map(|_| "?").join(", ")
+            );
+
+            let (mut imported, mut skipped) = (0, 0);
+            for row in rows {
+                anyhow::ensure!(row.len() == columns.len(), "expected {} values but found {}", columns.len(), row.len());
+                let mut values = row.into_iter()
+                    .zip(&nullable)
+                    .map(|(x, nullable)| if *nullable && x.is_empty() { Value::Null } else { Value::String(x) })
+                    .collect_vec();
+                // Files exported before times were stored in UTC have them in local time.
+                let at = date_time.map(|index| read_date_time(values[index].clone())).transpose()?;
+                if let (Some(index), Some(at)) = (date_time, at) {
+                    values[index] = at.to_sql();
+                }
+
+                existing.reset()?;
+                existing.bind(&values[..])?;
+                existing.next()?;
+                if existing.read::<i64, _>("matches")? > 0 {
+                    skipped += 1;
+                    continue;
+                }
+
+                if let Some(at) = at {
+                    self.ensure_open(at)?;
+                }
+                self.database.execute_cached(&insert, &values)?;
+                let entry = journal::next_entry(self.connection(), table_name, last_insert_row_id(self.connection())?)?;
+                self.insert_mapped(&entry.build_object_mapper())?;
+                imported += 1;
+            }
+            Ok((imported, skipped))
+        })?;
+
+        self.read_entire_day();
+        Ok(counts)
+    }
+
+    /// Writes a consistent copy of the whole database to `path`, even while it is in use.
+    pub fn backup_to(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        backup::write(self.connection(), path.as_ref())
+    }
+
+    /// A new file in the backup directory named after the current time, followed by `suffix`.
+    pub fn backup_path(&self, suffix: &str) -> PathBuf {
+        BackupFile::file_name(&self.backup_directory, Local::now().naive_local(), suffix)
+    }
+
+    /// Takes a backup, copies it to the second destination if there is one, then prunes both per the policy. Returns
+    /// where the backup went. The backup itself is kept even if the second destination can't be written.
+    pub fn rotate_backups(&self, policy: &BackupSettings) -> anyhow::Result<PathBuf> {
+        let path = self.backup_path("");
+        self.backup_to(&path)?;
+        backup::prune(&self.backup_directory, policy)?;
+        info!("Backed up the database to {}", path.display());
+
+        if let Some(second) = &policy.second_destination {
+            let copied = std::fs::create_dir_all(second)
+                .and_then(|_| std::fs::copy(&path, second.join(path.file_name().unwrap_or_default())))
+                .map_err(anyhow::Error::from)
+                .and_then(|_| backup::prune(second, policy));
+            if let Err(err) = copied {
+                return Err(err.context(format!("backed up to {} but not to {}", path.display(), second.display())));
+            }
+        }
+        Ok(path)
+    }
+
+    /// Backups in the backup directory and the second destination, newest first.
+    pub fn backups(&self, policy: &BackupSettings) -> anyhow::Result<Vec<BackupFile>> {
+        let mut backups = backup::list(&self.backup_directory)?;
+        if let Some(second) = &policy.second_destination {
+            backups.extend(backup::list(second).unwrap_or_else(|err| {
+                error!("err listing backups in {}: {}", second.display(), err);
+                vec![]
+            }));
+        }
+        backups.sort_by_key(|x| Reverse(x.taken));
+        Ok(backups)
+    }
+
+    /// Replaces everything in the database with the backup at `path`, after backing up what is being replaced. The
+    /// restored data is migrated like any other database, so a backup taken by an older version can be restored.
+    /// Returns the backup of what was replaced.
+    pub fn restore_from(&mut self, path: impl AsRef<Path>) -> anyhow::Result<PathBuf> {
+        let restoring = Connection::open_with_flags(path.as_ref(), OpenFlags::new().with_read_only())?;
+        migration::pending(migration::current_version(&restoring)?)?;
+
+        let replaced = self.backup_path("_before_restore");
+        self.backup_to(&replaced)?;
+        backup::copy(&restoring, self.connection())?;
+        Self::migrate(self.connection(), &self.backup_directory)?;
+        self.read_entire_day();
+        Ok(replaced)
+    }
+
+    pub fn log_audit(&mut self, entry: AuditEntry) -> anyhow::Result<()> {
+        info!("Logging a {}", any::type_name::<AuditEntry>());
+        self.insert_and_journal(AuditEntry::TABLE_NAME, entry.wrapped_in_date_time().build_object_mapper())
+    }
+
+    /// Up to `AUDIT_LOG_PAGE` entries of the audit log, newest first, skipping the `skip` newest.
+    pub fn audit_log(&self, skip: usize) -> anyhow::Result<Vec<DateTimeWrapper<AuditEntry>>> {
+        self.select(&Query::<AuditEntry>::new()
+            .order_by(DATE_TIME_COLUMN, Order::Descending)
+            .limit(AUDIT_LOG_PAGE as i64)
+            .offset(skip as i64))
+    }
+
+    /// Runs the statement and reads each row it returns.
+    fn fetch<R>(&self, statement: BoundSql, mut read: impl FnMut(Row) -> anyhow::Result<R>) -> anyhow::Result<Vec<R>> {
+        let response = self.connection().prepare(statement.sql)?;
+        Result::from_iter(response
+            .into_iter()
+            .bind(&statement.values[..])?
+            .map(|x| read(x?))
+        )
+    }
+
+    /// Every row the query matches, with the time it was recorded.
+    pub fn select<T: CanBuildObjectMapper+FromSql>(&self, query: &Query<T>) -> anyhow::Result<Vec<DateTimeWrapper<T>>> {
+        self.fetch(query.select()?, DateTimeWrapper::from_sql)
+    }
+
+    /// The aggregate over the rows the query matches, as `(group, total)` pairs. Without `group_by` there is one pair
+    /// and its group is NULL.
+    pub fn total<T: CanBuildObjectMapper>(&self, query: &Query<T>, aggregate: Aggregate, group_by: Option<&'static str>) -> anyhow::Result<Vec<(Value, f64)>> {
+        self.fetch(query.aggregate(aggregate, group_by)?, |mut row| {
+            Ok((row.take("grouped"), f64::from_column(row.take("total"))?))
+        })
+    }
+
+    /// Sets `changes` on the rows the query matches and journals each of them again, all in one transaction or as part
+    /// of the one already open. Returns how many rows changed. Today's ledger isn't read again, so anything changing
+    /// today's rows does that once it is done.
+    pub fn update<T: CanBuildObjectMapper>(&self, query: &Query<T>, changes: &[(&'static str, Value)]) -> anyhow::Result<usize> {
+        self.change(query, query.update(changes)?, journal::next_entry)
+    }
+
+    /// Deletes the rows the query matches and journals each deletion, like `update`. Returns how many rows were
+    /// deleted.
+    pub fn delete<T: CanBuildObjectMapper>(&self, query: &Query<T>) -> anyhow::Result<usize> {
+        self.change(query, query.delete()?, journal::deletion_entry)
+    }
+
+    /// Runs `statement` against the rows the query matches and journals each of them, all in one transaction. Fails
+    /// without changing anything if any of them is in a closed day.
+    fn change<T: CanBuildObjectMapper>(
+        &self,
+        query: &Query<T>,
+        statement: BoundSql,
+        journal_entry: fn(&Connection, &str, i64) -> anyhow::Result<JournalEntry>,
+    ) -> anyhow::Result<usize> {
+        let table_name = query.table_name();
+        anyhow::ensure!(Self::JOURNALED_TABLES.contains(&table_name) && table_name != DayClose::TABLE_NAME,
+                        "{table_name} can't be changed");
+
+        self.in_transaction(|| {
+            let rows = self.fetch(query.row_ids_and_times()?, |mut row| {
+                Ok((row.try_read::<i64, _>("rowid")?, read_date_time(row.take(DATE_TIME_COLUMN))?))
+            })?;
+            for (_, date_time) in &rows {
+                self.ensure_open(*date_time)?;
+            }
+            let row_ids = rows.into_iter().map(|(row_id, _)| row_id).collect_vec();
+            let mut change = self.connection().prepare(&statement.sql)?;
+            change.bind(&statement.values[..])?;
+            while change.next()? != State::Done {}
+
+            for row_id in &row_ids {
+                self.insert_mapped(&journal_entry(self.connection(), table_name, *row_id)?.build_object_mapper())?;
+            }
+            Ok(row_ids.len())
+        })
+    }
+
+    /// Just the named columns of rows in `period`, oldest first, exactly as stored.
+    pub fn select_columns_between(&self, table_name: impl AsRef<str>, columns: &[&str], period: &Period) -> anyhow::Result<Vec<Vec<Value>>> {
         let table_name = table_name.as_ref();
-        let response = self.database.prepare(format!("SELECT * FROM {table_name} WHERE date_time >:max_age"))?;
+        let (from, to) = (period.start(&self.operating_hours), period.end(&self.operating_hours));
+        let response = self.connection().prepare(format!(
+            "SELECT {} FROM {table_name} WHERE date_time >= :from AND date_time < :to ORDER BY date_time",
+            columns.join(", ")
+        ))?;
         let binding: &[(&'static str, Value)] = &[
-            (":max_age", duration.to_sql().into())
+            (":from", from.to_sql()),
+            (":to", to.to_sql()),
         ];
 
         Result::from_iter(response
             .into_iter()
             .bind(binding)?
-            .filter_map(|x| x.ok())
-            .map(|x| T::from_sql(x))
+            .map(|x| Ok(Vec::from(x?)))
         )
     }
 
-    pub fn daily_admissions(&self) -> &Vec<Admission> {
-        &self.daily_admissions
-    }
-    pub fn daily_memberships(&self) -> &Vec<Membership> {
-        &self.daily_memberships
+    pub fn ledger(&self, period: &Period) -> anyhow::Result<Ledger> {
+        if *period == self.day {
+            return Ok(self.daily.clone());
+        }
+
+        let (from, to) = (period.start(&self.operating_hours), period.end(&self.operating_hours));
+        Ok(Ledger {
+            admissions: self.select(&Query::new().between(from, to))?,
+            memberships: self.select(&Query::new().between(from, to))?,
+            donations: self.select(&Query::new().between(from, to))?,
+            gift_shop_sales: self.select(&Query::new().between(from, to))?,
+            transactions: self.select(&Query::new().between(from, to))?,
+            refunds: self.select(&Query::new().between(from, to))?,
+        })
     }
-    pub fn daily_donations(&self) -> &Vec<Donation> {
-        &self.daily_donations
+
+    /// Today's summary, kept up to date as rows are inserted.
+    pub fn daily_summary(&self) -> &SummaryDicts {
+        &self.daily_summary
     }
-    pub fn daily_gift_shop_sales(&self) -> &Vec<GiftShopSale> {
-        &self.daily_gift_shop_sales
+
+    /// The hash at the end of the journal, which anyone holding an older copy of it can check this one against.
+    pub fn chain_hash(&self) -> anyhow::Result<String> {
+        journal::last_hash(self.connection())
     }
-    pub fn daily_transactions(&self) -> &Vec<TransactionRecord> {
-        &self.daily_transactions
+
+    pub fn verify_journal(&self) -> anyhow::Result<JournalReport> {
+        journal::verify(self.connection(), &Self::JOURNALED_TABLES)
     }
-    pub fn render_to_html(&self) -> String {
-        const TEMPLATE_STR: &'static str = include_str!("../summary.html");
+
+    pub fn render_to_html(&self, period: &Period, operating_hours: &OperatingHours) -> anyhow::Result<String> {
+        const TEMPLATE_STR: &str = include_str!("../summary.html");
 
         let mut templates = Environment::new();
-       templates.add_template("summary", TEMPLATE_STR).unwrap();
+        templates.add_template("summary", TEMPLATE_STR)?;
         #[derive(Serialize)]
         struct Context {
             frequency: String,
+            period: String,
             fields: SummaryDicts,
+            charts: ReportCharts,
+            chain_hash: String,
         }
 
-
+        let ledger = self.ledger(period)?;
+        let fields = SummaryDicts::new(&ledger, operating_hours, period);
         let context = Context {
-            frequency: "Daily".to_string(),
-            fields: SummaryDicts::new(self)
+            frequency: period.title(),
+            period: period.describe(),
+            charts: ReportCharts::new(&fields, &ledger, operating_hours, period),
+            fields,
+            chain_hash: self.chain_hash().unwrap_or_else(|err| {
+                error!("err reading journal chain hash: {}", err);
+                "UNAVAILABLE".to_string()
+            }),
         };
 
 
-        templates.get_template("summary").unwrap().render(&context).unwrap()
+        Ok(templates.get_template("summary")?.render(&context)?)
+    }
+}
+
+pub(crate) fn last_insert_row_id(connection: &Connection) -> anyhow::Result<i64> {
+    let statement = connection.prepare("SELECT last_insert_rowid() AS row_id")?;
+    Ok(statement.into_iter()
+        .next()
+        .ok_or(anyhow::Error::msg("last_insert_rowid returned no rows."))??
+        .try_read("row_id")?)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use crate::model::admission::kind::Kind;
+    use crate::model::payment_method::PaymentMethod;
+    use crate::model::transaction_record::TransactionKind;
+    use crate::view::summary_dicts::Metric;
+    use super::*;
+
+    fn adults(quantity: u16) -> CartItem {
+        Admission::new(Kind::Adult, Some(PaymentMethod::Cash), quantity).into()
+    }
+
+    /// Moves sale `number` and everything in it back `days` days, as if it had been rung up then.
+    fn backdate(database: &mut Database, number: i64, days: i64) {
+        let at = (Local::now() - Duration::days(days)).to_sql();
+        for (table_name, column) in [(Sale::TABLE_NAME, "number"), (Admission::TABLE_NAME, "sale"), (TransactionRecord::TABLE_NAME, "sale")] {
+            let mut statement = database.connection().prepare(format!("UPDATE {table_name} SET date_time = ? WHERE {column} = ?")).unwrap();
+            statement.bind(&[at.clone(), number.to_sql()][..]).unwrap();
+            while statement.next().unwrap() != State::Done {}
+        }
+        database.read_entire_day();
+    }
+
+    #[test]
+    fn only_todays_sales_can_be_voided() {
+        let mut database = Database::in_memory();
+        let yesterday = database.record_sale("Sam", &[adults(2)]).unwrap().number;
+        backdate(&mut database, yesterday, 1);
+        let today = database.record_sale("Sam", &[adults(1)]).unwrap().number;
+
+        let error = database.void_sale(yesterday).unwrap_err().to_string();
+        assert!(error.contains("refund it instead"), "{error}");
+        assert_eq!(database.sale_lines(yesterday).unwrap().lines.len(), 1);
+
+        database.void_sale(today).unwrap();
+        assert_eq!(database.sale_lines(today).unwrap().sale.unwrap().status, SaleStatus::Voided);
+        assert_eq!(database.daily_summary().summary["Total Attendance"], Metric::Count(0));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn corrections_change_the_item_its_record_and_the_total_together() {
+        let mut database = Database::in_memory();
+        let donation: CartItem = Donation { payment_method: PaymentMethod::Cash, price: 20.0, sale: None }.into();
+        let number = database.record_sale("Sam", &[adults(2), donation.clone()]).unwrap().number;
+        let after: CartItem = Admission::new(Kind::Adult, Some(PaymentMethod::CreditCard), 3).into();
+        database.correct_item(number, &adults(2), &after).unwrap();
+
+        assert_eq!(database.sale_items(number).unwrap(), vec![after.in_sale(number), donation.in_sale(number)]);
+        let records: Vec<DateTimeWrapper<TransactionRecord>> = database.select(&Query::new().equals("sale", number)).unwrap();
+        assert_eq!(records.iter().map(|x| x.element().clone()).collect_vec(),
+                   vec![after.in_sale(number).as_transaction_record(), donation.in_sale(number).as_transaction_record()]);
+        let sale = database.sale_lines(number).unwrap().sale.unwrap();
+        assert_eq!(sale.total_cost, after.total_cost() + 20.0);
+        assert!(database.verify_journal().unwrap().is_intact());
+    }
+
+    #[test]
+    fn corrections_keep_to_the_same_kind_of_item() {
+        let mut database = Database::in_memory();
+        let number = database.record_sale("Sam", &[adults(2)]).unwrap().number;
+        let donation: CartItem = Donation { payment_method: PaymentMethod::Cash, price: 20.0, sale: None }.into();
+
+        let error = database.correct_item(number, &adults(2), &donation).unwrap_err().to_string();
+        assert!(error.contains("one kind of item into another"), "{error}");
+        assert_eq!(database.sale_items(number).unwrap(), vec![adults(2).in_sale(number)]);
+        assert!(database.verify_journal().unwrap().is_intact());
+    }
+
+    #[test]
+    fn closed_days_can_not_be_corrected() {
+        let mut database = Database::in_memory();
+        let number = database.record_sale("Sam", &[adults(2)]).unwrap().number;
+        backdate(&mut database, number, 1);
+        let yesterday = database.business_day().from - chrono::Days::new(1);
+        database.close_day(DayClose { number: 1, business_date: yesterday, ..Default::default() }).unwrap();
+
+        let error = database.correct_item(number, &adults(2), &adults(3)).unwrap_err().to_string();
+        assert!(error.contains("was closed with Z-report #1"), "{error}");
+        assert_eq!(database.sale_items(number).unwrap(), vec![adults(2).in_sale(number)]);
+    }
+
+    /// One of each kind of item, as they go into a cart.
+    fn one_of_everything() -> Vec<CartItem> {
+        vec![
+            adults(2),
+            Admission::new(Kind::ChildUnderSix, None, 1).into(),
+            Membership::new(crate::model::membership::kind::Kind::Family, PaymentMethod::CreditCard, 1).into(),
+            Donation { payment_method: PaymentMethod::Cash, price: 20.0, sale: None }.into(),
+            GiftShopSale::new("Mining \"history\" postcard".to_string(), 1.25, PaymentMethod::CreditCard, 4, 7.5).into(),
+        ]
+    }
+
+    #[test]
+    fn the_unfinished_cart_comes_back_as_it_was_kept() {
+        let mut database = Database::in_memory();
+        assert_eq!(database.unfinished_cart().unwrap(), None);
+
+        let cart = one_of_everything();
+        database.save_cart(&cart).unwrap();
+        assert_eq!(database.unfinished_cart().unwrap().map(|(_, x)| x), Some(cart.clone()));
+        database.save_cart(&cart[..2]).unwrap();
+        assert_eq!(database.unfinished_cart().unwrap().map(|(_, x)| x), Some(cart[..2].to_vec()));
+
+        database.record_sale("Sam", &cart[..2]).unwrap();
+        assert_eq!(database.unfinished_cart().unwrap(), None);
+    }
+
+    #[test]
+    fn parked_carts_are_labelled_and_recalled_one_at_a_time() {
+        let database = Database::in_memory();
+        let cart = one_of_everything();
+        assert_eq!(database.park_cart("", &cart[..1]).unwrap(), "Cart 1");
+        assert_eq!(database.park_cart(" Smith family ", &cart[1..3]).unwrap(), "Smith family");
+        assert_eq!(database.park_cart("", &cart[3..]).unwrap(), "Cart 2");
+        assert!(database.park_cart("Jones", &[]).is_err());
+        assert_eq!(database.parked_carts().unwrap().iter().map(|x| x.label.as_str()).collect_vec(), ["Cart 1", "Smith family", "Cart 2"]);
+
+        database.save_cart(&cart[..1]).unwrap();
+        let error = database.recall_cart("Smith family").unwrap_err().to_string();
+        assert!(error.contains("park or save the cart on screen first"), "{error}");
+        assert_eq!(database.parked_carts().unwrap().len(), 3);
+
+        database.save_cart(&[]).unwrap();
+        assert!(database.recall_cart("Jones").unwrap_err().to_string().contains("no cart called \"Jones\""));
+        assert_eq!(database.recall_cart("Smith family").unwrap(), cart[1..3].to_vec());
+        assert_eq!(database.unfinished_cart().unwrap().map(|(_, x)| x), Some(cart[1..3].to_vec()));
+        assert_eq!(database.parked_carts().unwrap().iter().map(|x| x.label.as_str()).collect_vec(), ["Cart 1", "Cart 2"]);
+    }
+
+    #[test]
+    fn a_label_already_parked_is_refused() {
+        let database = Database::in_memory();
+        let cart = one_of_everything();
+        database.park_cart("Smith family", &cart[..1]).unwrap();
+        database.save_cart(&cart[1..]).unwrap();
+
+        let error = database.park_cart(" Smith family ", &cart[1..]).unwrap_err().to_string();
+        assert_eq!(error, "a cart called \"Smith family\" is already parked");
+        assert_eq!(database.unfinished_cart().unwrap().map(|(_, x)| x), Some(cart[1..].to_vec()));
+        assert_eq!(database.recall_cart("Smith family").unwrap_err().to_string(), "park or save the cart on screen first");
+        assert_eq!(database.parked_carts().unwrap().iter().map(|x| x.items.len()).collect_vec(), [1]);
+    }
+
+    #[test]
+    fn quick_sales_come_back_in_the_order_saved() {
+        let database = Database::in_memory();
+        assert_eq!(database.quick_sales().unwrap().first().map(|x| x.label.as_str()), Some("1 Adult"));
+
+        let postcard = QuickSale {
+            label: "Postcard".to_string(),
+            category: TransactionKind::GiftShopSale,
+            kind: "Postcard".to_string(),
+            price: 1.25,
+            sales_tax: 0.0,
+            ..Default::default()
+        };
+        let family = QuickSale { label: "Family".to_string(), category: TransactionKind::Membership, kind: "Family".to_string(), ..Default::default() };
+        let two_adults = QuickSale { label: "2 Adults".to_string(), quantity: 2, ..Default::default() };
+        database.save_quick_sales(&[postcard.clone(), family.clone(), two_adults.clone()]).unwrap();
+        let saved = database.quick_sales().unwrap();
+        assert_eq!(saved.iter().map(|x| (x.position, x.label.as_str())).collect_vec(), [(0, "Postcard"), (1, "Family"), (2, "2 Adults")]);
+        assert_eq!(saved[0], postcard);
+
+        database.save_quick_sales(&[two_adults, postcard]).unwrap();
+        assert_eq!(database.quick_sales().unwrap().iter().map(|x| (x.position, x.label.as_str())).collect_vec(), [(0, "2 Adults"), (1, "Postcard")]);
+        assert!(database.save_quick_sales(&[QuickSale { quantity: 0, ..family }]).is_err());
+        assert_eq!(database.quick_sales().unwrap().len(), 2);
+    }
+}
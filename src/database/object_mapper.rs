@@ -1,18 +1,19 @@
 use indexmap::IndexMap;
 use itertools::Itertools;
-use crate::database::has_schema::HasSchema;
+use sqlite::Value;
+use crate::database::has_schema::{HasSchema, NOT_NULL};
 use crate::database::to_sql::ToSql;
 
 pub trait Mappable {
     fn schema(&self, field_name: &str) -> String;
-    fn to_sql(&self) -> String;
+    fn to_sql(&self) -> Value;
 }
 impl<T: HasSchema+ToSql> Mappable for T {
     fn schema(&self, field_name: &str) -> String {
         <Self as HasSchema>::schema(field_name)
     }
 
-    fn to_sql(&self) -> String {
+    fn to_sql(&self) -> Value {
         self.to_sql()
     }
 }
@@ -35,17 +36,37 @@ impl ObjectMapper {
         self
     }
     
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    pub fn column_names(&self) -> impl Iterator<Item=&str> {
+        self.fields.keys().map(String::as_str)
+    }
+
+    /// Whether the column can be NULL, which is how `Option` fields store `None`.
+    pub fn is_nullable(&self, column: &str) -> bool {
+        self.fields.get(column).is_some_and(|x| !x.schema(column).contains(NOT_NULL))
+    }
+
     pub fn schema(&self) -> String {
         format!("CREATE TABLE IF NOT EXISTS {} (\n\t{}\n);", self.table_name, 
                 self.fields.iter().map(|(name, field)| field.schema(name)).join(",\n\t")
         )
     }
     
+    /// An INSERT with a placeholder per field. It only depends on the table, so it can be prepared once and bound to
+    /// `values()` for every row.
     pub fn insert(&self) -> String {
-        format!("INSERT INTO {} ({})\nVALUES ({});", 
-                self.table_name, 
-                self.fields.keys().join(", "), 
-                self.fields.values().map(|x| x.to_sql()).join(", "))
+        format!("INSERT INTO {} ({})\nVALUES ({});",
+                self.table_name,
+                self.fields.keys().join(", "),
+                self.fields.values().map(|_| "?").join(", "))
+    }
+
+    /// The values to bind to `insert()`, in the same order.
+    pub fn values(&self) -> Vec<Value> {
+        self.fields.values().map(|x| x.to_sql()).collect()
     }
 }
 
@@ -70,6 +91,15 @@ mod tests {
     
     #[test]
     fn insert() {
-        assert_eq!(demo().insert(), "INSERT INTO test (name, age, birthday)\nVALUES ('Sally', 24, 1351683413616383413);");
+        assert_eq!(demo().insert(), "INSERT INTO test (name, age, birthday)\nVALUES (?, ?, ?);");
+    }
+
+    #[test]
+    fn values() {
+        assert_eq!(demo().values(), vec![
+            Value::String("Sally".to_string()),
+            Value::Integer(24),
+            Value::Integer(1351683413616383413),
+        ]);
     }
 }
\ No newline at end of file
@@ -0,0 +1,391 @@
+//! Queries against one model's table, built up from filters instead of hand-written SQL. Every value is bound as a
+//! parameter. Column names can't be, so they have to be plain identifiers and are checked before any SQL is built.
+
+use std::fmt::Display;
+use std::marker::PhantomData;
+use chrono::{DateTime, Local};
+use itertools::Itertools;
+use sqlite::Value;
+use crate::database::database_object::CanBuildObjectMapper;
+use crate::database::object_mapper::ObjectMapper;
+use crate::database::to_sql::ToSql;
+
+/// The column every sales table records its time in.
+pub const DATE_TIME_COLUMN: &str = "date_time";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    /// SQL `LIKE`, so `%` and `_` are wildcards. Case-insensitive for ASCII.
+    Like,
+}
+
+impl Comparison {
+    fn operator(&self) -> &'static str {
+        match self {
+            Comparison::Equal => "IS",
+            Comparison::NotEqual => "IS NOT",
+            Comparison::Less => "<",
+            Comparison::LessOrEqual => "<=",
+            Comparison::Greater => ">",
+            Comparison::GreaterOrEqual => ">=",
+            Comparison::Like => "LIKE",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Ascending,
+    Descending,
+}
+
+impl Display for Order {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Order::Ascending => "ASC",
+            Order::Descending => "DESC",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aggregate {
+    /// How many rows matched.
+    Count,
+    /// The total of a numeric column, which is 0 rather than NULL when nothing matched.
+    Sum(&'static str),
+}
+
+/// SQL and the values to bind to its placeholders, in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundSql {
+    pub sql: String,
+    pub values: Vec<Value>,
+}
+
+#[derive(Debug, Clone)]
+struct Filter {
+    column: &'static str,
+    comparison: Comparison,
+    value: Value,
+}
+
+/// A selection of rows from `T`'s table. Build it up, then hand it to `Database::select`, `total`, `update` or
+/// `delete`.
+#[derive(Debug, Clone)]
+pub struct Query<T> {
+    filters: Vec<Filter>,
+    order: Vec<(&'static str, Order)>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+    model: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for Query<T> {
+    fn default() -> Self {
+        Self {
+            filters: vec![],
+            order: vec![],
+            limit: None,
+            offset: None,
+            model: PhantomData,
+        }
+    }
+}
+
+fn check_column(column: &str) -> anyhow::Result<()> {
+    let valid = column.chars().next().is_some_and(|x| x.is_ascii_alphabetic() || x == '_')
+        && column.chars().all(|x| x.is_ascii_alphanumeric() || x == '_');
+    anyhow::ensure!(valid, "{column:?} is not a column name");
+    Ok(())
+}
+
+impl<T: CanBuildObjectMapper> Query<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn table_name(&self) -> &'static str {
+        T::TABLE_NAME
+    }
+
+    /// Keeps rows where `column` compares to `value`. Filters are combined with AND. Comparing with `None` matches
+    /// NULLs.
+    pub fn filter(mut self, column: &'static str, comparison: Comparison, value: impl ToSql) -> Self {
+        self.filters.push(Filter { column, comparison, value: value.to_sql() });
+        self
+    }
+
+    pub fn equals(self, column: &'static str, value: impl ToSql) -> Self {
+        self.filter(column, Comparison::Equal, value)
+    }
+
+    /// Keeps rows with `from <= date_time < to`.
+    pub fn between(self, from: DateTime<Local>, to: DateTime<Local>) -> Self {
+        self.since(from).filter(DATE_TIME_COLUMN, Comparison::Less, to)
+    }
+
+    /// Keeps rows with `from <= date_time`.
+    pub fn since(self, from: DateTime<Local>) -> Self {
+        self.filter(DATE_TIME_COLUMN, Comparison::GreaterOrEqual, from)
+    }
+
+    /// Sorts by `column`. Each call adds a tie-breaker after the ones before it.
+    pub fn order_by(mut self, column: &'static str, order: Order) -> Self {
+        self.order.push((column, order));
+        self
+    }
+
+    pub fn limit(mut self, limit: i64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: i64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    fn check_columns(&self) -> anyhow::Result<()> {
+        self.filters.iter().map(|x| x.column)
+            .chain(self.order.iter().map(|(column, _)| *column))
+            .try_for_each(check_column)
+    }
+
+    fn where_clause(&self) -> String {
+        if self.filters.is_empty() {
+            return String::new();
+        }
+
+        format!(" WHERE {}", self.filters.iter()
+            .map(|x| format!("{} {} ?", x.column, x.comparison.operator()))
+            .join(" AND "))
+    }
+
+    fn order_and_limit(&self) -> String {
+        let mut sql = String::new();
+        if !self.order.is_empty() {
+            sql += &format!(" ORDER BY {}", self.order.iter().map(|(column, order)| format!("{column} {order}")).join(", "));
+        }
+        // SQLite only takes an OFFSET after a LIMIT, and -1 means no limit.
+        if self.limit.is_some() || self.offset.is_some() {
+            sql += " LIMIT ?";
+        }
+        if self.offset.is_some() {
+            sql += " OFFSET ?";
+        }
+        sql
+    }
+
+    fn values(&self) -> Vec<Value> {
+        let mut values = self.filters.iter().map(|x| x.value.clone()).collect_vec();
+        if self.limit.is_some() || self.offset.is_some() {
+            values.push(Value::Integer(self.limit.unwrap_or(-1)));
+        }
+        values.extend(self.offset.map(Value::Integer));
+        values
+    }
+
+    /// Every column of the matching rows.
+    pub fn select(&self) -> anyhow::Result<BoundSql> {
+        self.select_columns("*")
+    }
+
+    /// Just the rowids of the matching rows.
+    pub fn row_ids(&self) -> anyhow::Result<BoundSql> {
+        self.select_columns("rowid")
+    }
+
+    /// The rowids of the matching rows along with when each was recorded.
+    pub fn row_ids_and_times(&self) -> anyhow::Result<BoundSql> {
+        self.select_columns(&format!("rowid, {DATE_TIME_COLUMN}"))
+    }
+
+    fn select_columns(&self, columns: &str) -> anyhow::Result<BoundSql> {
+        self.check_columns()?;
+        Ok(BoundSql {
+            sql: format!("SELECT {columns} FROM {}{}{}", T::TABLE_NAME, self.where_clause(), self.order_and_limit()),
+            values: self.values(),
+        })
+    }
+
+    /// One row per distinct value of `group_by`, or a single row without it, with the group in a `grouped` column
+    /// and the aggregate in `total`. Ordering and limits apply to the groups.
+    pub fn aggregate(&self, aggregate: Aggregate, group_by: Option<&'static str>) -> anyhow::Result<BoundSql> {
+        self.check_columns()?;
+        let total = match aggregate {
+            Aggregate::Count => "COUNT(*)".to_string(),
+            Aggregate::Sum(column) => {
+                check_column(column)?;
+                format!("TOTAL({column})")
+            }
+        };
+        let (grouped, group_clause) = match group_by {
+            Some(column) => {
+                check_column(column)?;
+                (column, format!(" GROUP BY {column}"))
+            }
+            None => ("NULL", String::new()),
+        };
+
+        Ok(BoundSql {
+            sql: format!("SELECT {grouped} AS grouped, {total} AS total FROM {}{}{group_clause}{}",
+                         T::TABLE_NAME, self.where_clause(), self.order_and_limit()),
+            values: self.values(),
+        })
+    }
+
+    /// Sets `changes` on every matching row. Ordering and limits pick which rows those are.
+    pub fn update(&self, changes: &[(&'static str, Value)]) -> anyhow::Result<BoundSql> {
+        self.set(changes.iter().map(|(column, value)| (*column, value.clone())))
+    }
+
+    /// Sets every column `row` has on the matching rows, so they hold the same as it. Ordering and limits pick which
+    /// rows those are.
+    pub fn replace(&self, row: &ObjectMapper) -> anyhow::Result<BoundSql> {
+        self.set(row.column_names().zip(row.values()))
+    }
+
+    fn set<'a>(&self, changes: impl IntoIterator<Item=(&'a str, Value)>) -> anyhow::Result<BoundSql> {
+        let (columns, values): (Vec<_>, Vec<_>) = changes.into_iter().unzip();
+        anyhow::ensure!(!columns.is_empty(), "nothing to change in {}", T::TABLE_NAME);
+        columns.iter().try_for_each(|column| check_column(column))?;
+        let rows = self.row_ids()?;
+
+        Ok(BoundSql {
+            sql: format!("UPDATE {} SET {} WHERE rowid IN ({})",
+                         T::TABLE_NAME,
+                         columns.iter().map(|column| format!("{column} = ?")).join(", "),
+                         rows.sql),
+            values: values.into_iter().chain(rows.values).collect(),
+        })
+    }
+
+    /// Removes every matching row. Ordering and limits pick which rows those are.
+    pub fn delete(&self) -> anyhow::Result<BoundSql> {
+        let rows = self.row_ids()?;
+        Ok(BoundSql {
+            sql: format!("DELETE FROM {} WHERE rowid IN ({})", T::TABLE_NAME, rows.sql),
+            values: rows.values,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::Database;
+    use crate::model::cart_item::CartItem;
+    use crate::model::donation::Donation;
+    use crate::model::gift_shop_sale::GiftShopSale;
+    use crate::model::payment_method::PaymentMethod;
+    use crate::model::sale::Sale;
+    use crate::model::transaction_record::TransactionRecord;
+    use crate::report::period::Period;
+    use super::*;
+
+    fn donation(price: f32) -> CartItem {
+        Donation { payment_method: PaymentMethod::Cash, price, sale: None }.into()
+    }
+
+    fn gift(description: &str) -> CartItem {
+        GiftShopSale::new(description.to_string(), 4.0, PaymentMethod::Cash, 1, 0.0).into()
+    }
+
+    #[test]
+    fn select() {
+        let query = Query::<GiftShopSale>::new()
+            .equals("payment_method", PaymentMethod::CreditCard)
+            .filter("item_description", Comparison::Like, "%mug%")
+            .order_by("price", Order::Descending)
+            .limit(10)
+            .offset(20);
+
+        assert_eq!(query.select().unwrap(), BoundSql {
+            sql: "SELECT * FROM gift_shop_sales WHERE payment_method IS ? AND item_description LIKE ? ORDER BY price DESC LIMIT ? OFFSET ?".to_string(),
+            values: vec!["Credit Card".into(), "%mug%".into(), Value::Integer(10), Value::Integer(20)],
+        });
+    }
+
+    #[test]
+    fn aggregate_update_and_delete() {
+        let query = Query::<GiftShopSale>::new().equals("quantity", 2);
+
+        assert_eq!(query.aggregate(Aggregate::Sum("price"), Some("payment_method")).unwrap().sql,
+                   "SELECT payment_method AS grouped, TOTAL(price) AS total FROM gift_shop_sales WHERE quantity IS ? GROUP BY payment_method");
+        assert_eq!(query.update(&[("price", Value::Float(1.5))]).unwrap(), BoundSql {
+            sql: "UPDATE gift_shop_sales SET price = ? WHERE rowid IN (SELECT rowid FROM gift_shop_sales WHERE quantity IS ?)".to_string(),
+            values: vec![Value::Float(1.5), Value::Integer(2)],
+        });
+        assert_eq!(query.delete().unwrap().sql,
+                   "DELETE FROM gift_shop_sales WHERE rowid IN (SELECT rowid FROM gift_shop_sales WHERE quantity IS ?)");
+        let mug = GiftShopSale::new("Mug".to_string(), 12.0, PaymentMethod::Cash, 1, 0.0);
+        assert_eq!(query.limit(1).replace(&mug.build_object_mapper()).unwrap().sql,
+                   "UPDATE gift_shop_sales SET item_description = ?, price = ?, payment_method = ?, quantity = ?, sales_tax = ?, sale = ? \
+                   WHERE rowid IN (SELECT rowid FROM gift_shop_sales WHERE quantity IS ? LIMIT ?)");
+    }
+
+    #[test]
+    fn like_matches_part_of_a_column_ignoring_case() {
+        let mut database = Database::in_memory();
+        database.record_sale("Sam", &[gift("Coffee Mug"), gift("Postcard"), gift("mug rack")]).unwrap();
+
+        let query = Query::<GiftShopSale>::new().filter("item_description", Comparison::Like, "%MUG%");
+        let found = database.select(&query).unwrap().into_iter().map(|x| x.element().item_description.clone()).collect::<Vec<_>>();
+        assert_eq!(found, vec!["Coffee Mug", "mug rack"]);
+        assert_eq!(database.gift_shop_items_like("card").unwrap().into_iter().collect::<Vec<_>>(), vec!["Postcard"]);
+    }
+
+    #[test]
+    fn offset_skips_the_first_rows() {
+        let mut database = Database::in_memory();
+        for price in [1.0, 2.0, 3.0] {
+            database.record_sale("Sam", &[donation(price)]).unwrap();
+        }
+
+        let query = Query::<Sale>::new().order_by("number", Order::Ascending).limit(2).offset(1);
+        assert_eq!(database.select(&query).unwrap().iter().map(|x| x.element().total_cost).collect::<Vec<_>>(), vec![2.0, 3.0]);
+        assert!(database.select(&query.offset(3)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn sums_add_up_what_passes_the_filters() {
+        let mut database = Database::in_memory();
+        database.record_sale("Sam", &[donation(20.0), gift("Mug")]).unwrap();
+        let refunded = database.record_sale("Sam", &[donation(5.0)]).unwrap().number;
+        database.refund_sale(refunded).unwrap();
+
+        let query = Query::<TransactionRecord>::new().filter("total_cost", Comparison::Greater, 4.0);
+        assert_eq!(database.total(&query, Aggregate::Sum("total_cost"), None).unwrap(), vec![(Value::Null, 25.0)]);
+        let nothing = Query::<TransactionRecord>::new().filter("total_cost", Comparison::Greater, 100.0);
+        assert_eq!(database.total(&nothing, Aggregate::Sum("total_cost"), None).unwrap(), vec![(Value::Null, 0.0)]);
+        let today = Period::day(database.business_day().from);
+        assert_eq!(database.takings(&today).unwrap(), (29.0, 5.0));
+    }
+
+    #[test]
+    fn updates_and_deletes_are_journaled() {
+        let mut database = Database::in_memory();
+        database.record_sale("Sam", &[donation(20.0), gift("Mug"), gift("Postcard")]).unwrap();
+
+        let mugs = Query::<GiftShopSale>::new().equals("item_description", "Mug");
+        assert_eq!(database.update(&mugs, &[("quantity", Value::Integer(2))]).unwrap(), 1);
+        assert_eq!(database.select(&mugs).unwrap()[0].element().quantity, 2);
+        let postcards = Query::<GiftShopSale>::new().filter("item_description", Comparison::NotEqual, "Mug");
+        assert_eq!(database.delete(&postcards).unwrap(), 1);
+        assert_eq!(database.select(&Query::<GiftShopSale>::new()).unwrap().len(), 1);
+        assert!(database.verify_journal().unwrap().is_intact());
+    }
+
+    #[test]
+    fn column_names_are_checked() {
+        let query = Query::<GiftShopSale>::new().equals("price; DROP TABLE journal", 1);
+        assert!(query.select().is_err());
+        assert!(Query::<GiftShopSale>::new().aggregate(Aggregate::Sum("1 + price"), None).is_err());
+    }
+}
@@ -1,56 +1,87 @@
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, SecondsFormat, SubsecRound, Utc};
+use sqlite::Value;
 
+/// How a field is bound to a statement parameter.
 pub trait ToSql {
-    fn to_sql(&self) -> String;
+    fn to_sql(&self) -> Value;
 }
 
-macro_rules! impl_to_sql_via_to_string {
+macro_rules! impl_to_sql_as_integer {
     ($ty:ty) => {
         impl ToSql for $ty {
-            fn to_sql(&self) -> String {
-                self.to_string()
+            fn to_sql(&self) -> Value {
+                Value::Integer(*self as i64)
             }
         }
     };
 }
 
-impl_to_sql_via_to_string!(i8);
-impl_to_sql_via_to_string!(i16);
-impl_to_sql_via_to_string!(i32);
-impl_to_sql_via_to_string!(i64);
+impl_to_sql_as_integer!(i8);
+impl_to_sql_as_integer!(i16);
+impl_to_sql_as_integer!(i32);
+impl_to_sql_as_integer!(i64);
+impl_to_sql_as_integer!(u16);
 
-impl_to_sql_via_to_string!(f32);
-impl_to_sql_via_to_string!(f64);
+/// Widening an f32 straight to f64 would store 8.55 as 8.550000190734863. Going through its shortest decimal form stores
+/// 8.55, the number that was actually entered.
+impl ToSql for f32 {
+    fn to_sql(&self) -> Value {
+        Value::Float(self.to_string().parse().unwrap_or(*self as f64))
+    }
+}
+impl ToSql for f64 {
+    fn to_sql(&self) -> Value {
+        Value::Float(*self)
+    }
+}
 
 impl ToSql for bool {
-    fn to_sql(&self) -> String {
-        if *self {
-            "TRUE".to_string()
-        } else {
-            "FALSE".to_string()
-        }
+    fn to_sql(&self) -> Value {
+        Value::Integer(*self as i64)
     }
 }
 
 impl ToSql for &str {
-    fn to_sql(&self) -> String {
-        format!("'{}'", self.replace("'", "''"))
+    fn to_sql(&self) -> Value {
+        Value::String(self.to_string())
     }
 }
 impl ToSql for String {
-    fn to_sql(&self) -> String {
-        self.as_str().to_sql()
+    fn to_sql(&self) -> Value {
+        Value::String(self.clone())
     }
 }
 
 impl<T: ToSql> ToSql for Option<T> {
-    fn to_sql(&self) -> String {
-        let Some(value) = &self else {return "NULL".to_string(); };
+    fn to_sql(&self) -> Value {
+        let Some(value) = &self else {return Value::Null; };
         value.to_sql()
     }
 }
+/// Times are stored in UTC to the millisecond, e.g. `2026-07-04T16:30:00.000Z`. Every stored time is the same width
+/// and in the same zone, so comparing them as text compares them in time, whatever the offset was when they were
+/// written. They are only converted to local time when read back.
 impl ToSql for DateTime<Local> {
-    fn to_sql(&self) -> String {
-        format!("'{}'", self.to_rfc3339())
+    fn to_sql(&self) -> Value {
+        // Rounded rather than truncated, the same as SQLite's strftime, which rewrote the times stored before this.
+        Value::String(self.with_timezone(&Utc).round_subsecs(3).to_rfc3339_opts(SecondsFormat::Millis, true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn floats_keep_their_decimal_value() {
+        assert_eq!(8.55_f32.to_sql(), Value::Float(8.55));
+        assert_eq!(Some("it's").to_sql(), Value::String("it's".to_string()));
+        assert_eq!(None::<i32>.to_sql(), Value::Null);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn times_are_stored_in_utc() {
+        let at = DateTime::parse_from_rfc3339("2026-03-08T01:59:59.9996-07:00").unwrap().with_timezone(&Local);
+        assert_eq!(at.to_sql(), Value::String("2026-03-08T09:00:00.000Z".to_string()));
+    }
+}
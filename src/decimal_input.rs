@@ -45,7 +45,7 @@ impl DecimalInput {
             Message::Change(text) => self.handle_change(text),
         }
     }
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         iced::widget::row![
             text(format!("{}: ", self.label)).size(TEXT_SIZE),
             text_input(&self.value.to_string(), &self.field).on_input(Message::Change),
@@ -1,10 +1,21 @@
+use clap::Parser;
+use iced::Task;
+use log::error;
 use crate::app::App;
+use crate::cli::{Cli, Outcome};
+use crate::database::Database;
+use crate::settings::paths::DataDir;
+use crate::settings::Settings;
+use crate::view::startup_error::StartupError;
 mod app;
 mod as_description;
+mod cli;
 mod decimal_input;
 mod sale_screen;
 mod database;
 mod model;
+mod report;
+mod settings;
 mod view;
 mod to_model;
 
@@ -13,14 +24,45 @@ pub const TEXT_SIZE: u16 = 18;
 pub const RULE_HEIGHT: u16 = 8;
 
 fn main() -> iced::Result {
-    //todo: Keep track of hours for admissions entries.
-
     env_logger::init();
+
+    let cli = Cli::parse();
+    let settings = match Settings::load(DataDir::resolve(cli.data_dir)) {
+        Ok(settings) => settings,
+        Err(err) if cli.command.is_some() => {
+            eprintln!("Error: {err:#}");
+            std::process::exit(2);
+        }
+        Err(err) => {
+            error!("Unable to read the settings: {err:#}");
+            return StartupError::run("Unable to read the settings", err);
+        }
+    };
+    if let Some(command) = cli.command {
+        std::process::exit(match command.run(&settings) {
+            Ok(Outcome::Success) => 0,
+            Ok(Outcome::Failure) => 1,
+            Err(err) => {
+                eprintln!("Error: {err:#}");
+                2
+            }
+        });
+    }
+
+    let database = match Database::new(&settings) {
+        Ok(database) => database,
+        Err(err) => {
+            error!("Unable to open the database: {err:#}");
+            return StartupError::run("Unable to open the database", err);
+        }
+    };
+
     iced::application(
         "Museum Point Of Sale",
         App::update,
         App::view
     )
+    .subscription(App::subscription)
     .font(iced_fonts::REQUIRED_FONT_BYTES)
-    .run()
+    .run_with(move || (App::new(database, settings), Task::none()))
 }
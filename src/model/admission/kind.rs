@@ -1,9 +1,8 @@
-use std::str::FromStr;
-use sqlite::Value;
+use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString, VariantArray};
 use crate::as_description::AsDescription;
 
-#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash, VariantArray, Display, EnumString)]
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Hash, VariantArray, Display, EnumString, Serialize, Deserialize)]
 pub enum Kind {
     #[default]
     Adult,
@@ -33,24 +32,6 @@ impl Kind {
     }
 }
 
-impl TryFrom<&Value> for Kind {
-    type Error = sqlite::Error;
-
-    fn try_from(value: &Value) -> Result<Self, Self::Error> {
-        let Value::String(str) = value else { 
-            return Err(sqlite::Error {
-                code: None,
-                message: Some("value is not a string, and therefore cannot be converted to a Kind.".to_string()),
-            })
-        };
-        
-        Ok(Kind::from_str(str).map_err(|x| sqlite::Error {
-            code: None,
-            message: Some(x.to_string()),
-        })?)
-    }
-}
-
 impl AsDescription for Kind {
     fn as_description(&self) -> &str {
         match self {
@@ -1,11 +1,9 @@
 pub mod kind;
 
 use std::fmt::Display;
-use sqlite::Row;
 use crate::as_description::AsDescription;
-use crate::database::database_object::CanBuildObjectMapper;
-use crate::database::from_sql::{from_option, FromSql};
-use crate::database::object_mapper::ObjectMapper;
+use pos_derive::DbObject;
+use serde::{Deserialize, Serialize};
 use crate::model::admission::kind::Kind;
 use crate::model::as_transaction_record::AsTransactionRecord;
 use crate::model::date_time_wrapper::WrapInDateTime;
@@ -14,11 +12,15 @@ use crate::model::has_total_cost::HasTotalCost;
 use crate::model::payment_method::PaymentMethod;
 use crate::model::transaction_record::{TransactionKind, TransactionRecord};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default, DbObject, Serialize, Deserialize)]
+#[db(table = "admissions")]
 pub struct Admission {
+    #[db(with = "crate::database::codec::text")]
     pub kind: Kind,
-    payment_method: Option<PaymentMethod>,
+    pub payment_method: Option<PaymentMethod>,
     pub quantity: u16,
+    /// The sale this was rung up in. Rows from before sales were numbered have none.
+    pub sale: Option<i64>,
 }
 
 impl Admission {
@@ -27,6 +29,7 @@ impl Admission {
             kind,
             payment_method,
             quantity,
+            sale: None,
         }
     }
 }
@@ -44,50 +47,17 @@ impl AsTransactionRecord for Admission {
             self.kind.as_description().to_string(),
             self.quantity,
             self.total_cost()
-        )
+        ).in_sale(self.sale)
     }
 }
 
 impl HasPaymentMethod for Admission {
     fn payment_method(&self) -> Option<PaymentMethod> {
-        self.payment_method.clone()
+        self.payment_method
     }
 }
 
 impl WrapInDateTime for Admission {}
-impl CanBuildObjectMapper for Admission {
-    const TABLE_NAME: &'static str = "admissions";
-
-    fn build_object_mapper(&self) -> ObjectMapper {
-        ObjectMapper::new(Self::TABLE_NAME)
-            .add_field("kind", self.kind.to_string())
-            .add_field("payment_method", self.payment_method)
-            .add_field("quantity", self.quantity as i32)
-    }
-}
-impl FromSql for Admission {
-    fn from_sql(mut row: Row) -> anyhow::Result<Self>
-    where
-        Self: Sized
-    {
-        let x:i64 = row.try_read("quantity")?;
-        Ok(Self {
-            kind: row.try_read("kind")?,
-            payment_method: from_option(&row.take("payment_method"))?,
-            quantity: x as u16,
-        })
-    }
-}
-impl Default for Admission {
-    fn default() -> Admission {
-        Self {
-            kind: Default::default(),
-            payment_method: None,
-            quantity: 0,
-        }
-    }
-}
-
 impl Display for Admission {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Admission for {} {}", self.quantity, self.kind)
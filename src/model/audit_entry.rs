@@ -0,0 +1,122 @@
+use std::fmt::Display;
+use pos_derive::DbObject;
+use sqlite::Value;
+use strum::{Display, EnumString, VariantArray};
+use crate::database::has_schema::{HasSchema, NOT_NULL};
+use crate::database::to_sql::ToSql;
+use crate::model::date_time_wrapper::WrapInDateTime;
+
+/// Anyone who didn't enter a name is recorded as this.
+pub const UNKNOWN_ACTOR: &str = "Unknown";
+
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Default, VariantArray, Display, EnumString)]
+pub enum AuditAction {
+    #[default]
+    #[strum(serialize = "Cart Removal")]
+    CartRemoval,
+    Void,
+    Refund,
+    #[strum(serialize = "Price Override")]
+    PriceOverride,
+    #[strum(serialize = "Settings Change")]
+    SettingsChange,
+    Export,
+    #[strum(serialize = "Restore Backup")]
+    RestoreBackup,
+    Correction,
+}
+
+impl HasSchema for AuditAction {
+    fn schema(field_name: &str) -> String
+    where
+        Self: Sized
+    {
+        format!("{field_name} TEXT {NOT_NULL}")
+    }
+}
+
+impl ToSql for AuditAction {
+    fn to_sql(&self) -> Value {
+        Value::String(self.to_string())
+    }
+}
+
+impl TryFrom<&Value> for AuditAction {
+    type Error = sqlite::Error;
+
+    fn try_from(value: &Value) -> Result<Self, Self::Error> {
+        let Value::String(value) = value else {
+            return Err(sqlite::Error {
+                code: None,
+                message: Some("Value is not a string, and must be.".to_string()),
+            })
+        };
+
+        AuditAction::try_from(value.as_str()).map_err(|_| sqlite::Error {
+            code: None,
+            message: Some("Unable to convert string to audit action.".to_string()),
+        })
+    }
+}
+crate::from_column_via_try_from!(AuditAction);
+
+/// A record of something that changed money or settings outside of a normal sale.
+#[derive(Eq, PartialEq, Debug, Clone, Default, DbObject)]
+#[db(table = "audit_log")]
+pub struct AuditEntry {
+    pub actor: String,
+    pub action: AuditAction,
+    pub subject: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+    pub reason: String,
+}
+
+impl AuditEntry {
+    pub fn new(actor: impl AsRef<str>, action: AuditAction, subject: impl AsRef<str>, reason: impl AsRef<str>) -> Self {
+        let actor = actor.as_ref().trim();
+        Self {
+            actor: if actor.is_empty() { UNKNOWN_ACTOR.to_string() } else { actor.to_string() },
+            action,
+            subject: subject.as_ref().to_string(),
+            before: None,
+            after: None,
+            reason: reason.as_ref().trim().to_string(),
+        }
+    }
+
+    pub fn with_before(mut self, before: impl Display) -> Self {
+        self.before = Some(before.to_string());
+        self
+    }
+
+    pub fn with_after(mut self, after: impl Display) -> Self {
+        self.after = Some(after.to_string());
+        self
+    }
+}
+
+impl WrapInDateTime for AuditEntry {}
+impl Display for AuditEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} by {}: {}", self.action, self.actor, self.subject)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_are_trimmed_and_never_anonymous() {
+        let entry = AuditEntry::new("  ", AuditAction::Void, "Sale #3", " rang up twice ")
+            .with_before("Sale #3 ($16.00)")
+            .with_after("Voided");
+        assert_eq!(entry.actor, UNKNOWN_ACTOR);
+        assert_eq!(entry.reason, "rang up twice");
+        assert_eq!(entry.before.as_deref(), Some("Sale #3 ($16.00)"));
+        assert_eq!(entry.after.as_deref(), Some("Voided"));
+        assert_eq!(entry.to_string(), "Void by Unknown: Sale #3");
+        assert_eq!(AuditAction::try_from(&entry.action.to_sql()).unwrap(), AuditAction::Void);
+    }
+}
@@ -1,11 +1,17 @@
 use std::fmt::Display;
+use serde::{Deserialize, Serialize};
 use crate::model::admission::Admission;
 use crate::model::donation::Donation;
 use crate::model::membership::Membership;
 use crate::model::gift_shop_sale::GiftShopSale;
+use crate::model::as_transaction_record::AsTransactionRecord;
+use crate::model::has_payment_method::HasPaymentMethod;
 use crate::model::has_total_cost::HasTotalCost;
+use crate::model::payment_method::PaymentMethod;
+use crate::model::transaction_record::TransactionRecord;
+use crate::view::adapters::ff;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum CartItem {
     Admission(Admission),
     Membership(Membership),
@@ -13,6 +19,35 @@ pub enum CartItem {
     GiftShopSale(GiftShopSale)
 }
 
+impl CartItem {
+    /// The same item, marked as rung up in sale `sale`.
+    pub fn in_sale(&self, sale: i64) -> CartItem {
+        let mut item = self.clone();
+        match &mut item {
+            CartItem::Admission(x) => x.sale = Some(sale),
+            CartItem::Membership(x) => x.sale = Some(sale),
+            CartItem::Donation(x) => x.sale = Some(sale),
+            CartItem::GiftShopSale(x) => x.sale = Some(sale),
+        }
+        item
+    }
+
+    pub fn as_transaction_record(&self) -> TransactionRecord {
+        match self {
+            CartItem::Admission(x) => x.as_transaction_record(),
+            CartItem::Membership(x) => x.as_transaction_record(),
+            CartItem::Donation(x) => x.as_transaction_record(),
+            CartItem::GiftShopSale(x) => x.as_transaction_record(),
+        }
+    }
+
+    /// What it was, how it was paid for and what it cost, e.g. for the audit log.
+    pub fn describe(&self) -> String {
+        let paid = self.payment_method().map(|x| x.to_string()).unwrap_or("Free".to_string());
+        format!("{self}, {paid}, {}", ff("$", self.total_cost()))
+    }
+}
+
 impl Display for CartItem {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", match self {
@@ -48,6 +83,17 @@ impl From<GiftShopSale> for CartItem {
     }
 }
 
+impl HasPaymentMethod for CartItem {
+    fn payment_method(&self) -> Option<PaymentMethod> {
+        match self {
+            CartItem::Admission(a) => a.payment_method(),
+            CartItem::Membership(m) => m.payment_method(),
+            CartItem::Donation(d) => d.payment_method(),
+            CartItem::GiftShopSale(g) => g.payment_method(),
+        }
+    }
+}
+
 impl HasTotalCost for CartItem {
     fn total_cost(&self) -> f32 {
         match self {
@@ -1,26 +1,44 @@
-use chrono::{DateTime, Local};
+use std::ops::Deref;
+use chrono::{DateTime, Local, Timelike};
 use sqlite::{Row, Value};
 use crate::database::database_object::CanBuildObjectMapper;
 use crate::database::from_sql::FromSql;
 use crate::database::object_mapper::ObjectMapper;
-use crate::model::hour::Hour;
+use crate::model::has_payment_method::HasPaymentMethod;
+use crate::model::has_total_cost::HasTotalCost;
+use crate::model::payment_method::PaymentMethod;
 
+/// A model along with the moment it was recorded. The timestamp is the source of truth for anything time based; reports
+/// derive hourly buckets from it using the configured operating hours.
+#[derive(Debug, Clone)]
 pub struct DateTimeWrapper<T> {
     element: T,
     date_time: DateTime<Local>,
-    hour: Hour
 }
 
 impl<T> DateTimeWrapper<T> {
     pub fn new(element: T) -> DateTimeWrapper<T> {
-        let now = Local::now();
-        let hour = Hour::from(now);
         Self {
             element,
-            date_time: now,
-            hour,
+            date_time: Local::now(),
         }
     }
+
+    pub fn element(&self) -> &T {
+        &self.element
+    }
+
+    pub fn date_time(&self) -> DateTime<Local> {
+        self.date_time
+    }
+}
+
+impl<T> Deref for DateTimeWrapper<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.element
+    }
 }
 
 impl<T: CanBuildObjectMapper> CanBuildObjectMapper for DateTimeWrapper<T> {
@@ -29,7 +47,8 @@ impl<T: CanBuildObjectMapper> CanBuildObjectMapper for DateTimeWrapper<T> {
     fn build_object_mapper(&self) -> ObjectMapper {
         self.element.build_object_mapper()
             .add_field("date_time", self.date_time)
-            .add_field("hour", self.hour)
+            // The hour of day, kept because older databases have this column as NOT NULL. Nothing reads it back.
+            .add_field("hour", self.date_time.hour() as i8)
     }
 }
 
@@ -38,11 +57,11 @@ pub trait WrapInDateTime {
         DateTimeWrapper::new(self)
     }
 }
-fn read_date_time(v: Value) -> anyhow::Result<DateTime<Local>> {
+pub(crate) fn read_date_time(v: Value) -> anyhow::Result<DateTime<Local>> {
     let Value::String(str) = v else {
         return Err(anyhow::Error::msg("Expected date_time to be stored as a string."));
     };
-    
+
     Ok(DateTime::parse_from_rfc3339(str.as_str())?.into())
 }
 impl<T: FromSql> FromSql for DateTimeWrapper<T> {
@@ -51,11 +70,21 @@ impl<T: FromSql> FromSql for DateTimeWrapper<T> {
         Self: Sized
     {
         let date_time = read_date_time(row.take("date_time"))?;
-        let hour = row.try_read("hour")?;
         Ok(Self {
             element: T::from_sql(row)?,
             date_time,
-            hour,
         })
     }
-}
\ No newline at end of file
+}
+
+impl<T: HasTotalCost> HasTotalCost for DateTimeWrapper<T> {
+    fn total_cost(&self) -> f32 {
+        self.element.total_cost()
+    }
+}
+
+impl<T: HasPaymentMethod> HasPaymentMethod for DateTimeWrapper<T> {
+    fn payment_method(&self) -> Option<PaymentMethod> {
+        self.element.payment_method()
+    }
+}
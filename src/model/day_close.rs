@@ -0,0 +1,29 @@
+use chrono::NaiveDate;
+use pos_derive::DbObject;
+use crate::model::date_time_wrapper::WrapInDateTime;
+
+/// The record of closing a business day with a Z-report. Once a day is closed its sales can't change.
+#[derive(Debug, Clone, PartialEq, Default, DbObject)]
+#[db(table = "day_closes")]
+pub struct DayClose {
+    /// Counts up from 1 across every close, so a missing Z-report is obvious.
+    pub number: i64,
+    #[db(with = "crate::database::codec::text")]
+    pub business_date: NaiveDate,
+    pub closed_by: String,
+    /// Cash the day's sales say should be in the drawer.
+    pub expected_cash: f32,
+    /// Cash actually counted in the drawer at close.
+    pub counted_cash: f32,
+    /// The Z-report as JSON, exactly as it was printed.
+    pub report: String,
+}
+
+impl DayClose {
+    /// Positive when the drawer is over, negative when it is short.
+    pub fn over_short(&self) -> f32 {
+        self.counted_cash - self.expected_cash
+    }
+}
+
+impl WrapInDateTime for DayClose {}
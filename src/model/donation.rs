@@ -1,8 +1,6 @@
 use std::fmt::Display;
-use sqlite::Row;
-use crate::database::database_object::CanBuildObjectMapper;
-use crate::database::from_sql::FromSql;
-use crate::database::object_mapper::ObjectMapper;
+use pos_derive::DbObject;
+use serde::{Deserialize, Serialize};
 use crate::model::as_transaction_record::AsTransactionRecord;
 use crate::model::date_time_wrapper::WrapInDateTime;
 use crate::model::has_payment_method::HasPaymentMethod;
@@ -10,10 +8,13 @@ use crate::model::has_total_cost::HasTotalCost;
 use crate::model::payment_method::PaymentMethod;
 use crate::model::transaction_record::{TransactionKind, TransactionRecord};
 
-#[derive(Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, DbObject, Serialize, Deserialize)]
+#[db(table = "donations")]
 pub struct Donation {
     pub payment_method: PaymentMethod,
     pub price: f32,
+    /// The sale this was rung up in. Rows from before sales were numbered have none.
+    pub sale: Option<i64>,
 }
 
 
@@ -24,30 +25,7 @@ impl AsTransactionRecord for Donation {
             "Donation".to_string(),
             1,
             self.price,
-        )
-    }
-}
-
-impl CanBuildObjectMapper for Donation {
-    const TABLE_NAME: &'static str = "donations";
-
-    fn build_object_mapper(&self) -> ObjectMapper {
-        ObjectMapper::new(Self::TABLE_NAME)
-            .add_field("payment_method", self.payment_method.clone())
-            .add_field("price", self.price)
-    }
-}
-
-impl FromSql for Donation {
-    fn from_sql(row: Row) -> anyhow::Result<Self>
-    where
-        Self: Sized
-    {
-        let price: f64 = row.try_read("price")?;
-        Ok(Self {
-            payment_method: row.try_read("payment_method")?,
-            price: price as f32,
-        })
+        ).in_sale(self.sale)
     }
 }
 
@@ -57,6 +35,7 @@ impl Default for Donation {
         Self {
             payment_method: Default::default(),
             price: 0.0,
+            sale: None,
         }
     }
 }
@@ -6,4 +6,9 @@ impl<'a, T: HasTotalCost + 'a, I> HasTotalCost for &'a I where &'a I: IntoIterat
     fn total_cost(&self) -> f32 {
         self.into_iter().map(|x| x.total_cost()).sum()
     }
-}
\ No newline at end of file
+}
+impl<T: HasTotalCost> HasTotalCost for [T] {
+    fn total_cost(&self) -> f32 {
+        self.iter().map(|x| x.total_cost()).sum()
+    }
+}
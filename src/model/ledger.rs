@@ -0,0 +1,46 @@
+use crate::model::admission::Admission;
+use crate::model::date_time_wrapper::DateTimeWrapper;
+use crate::model::donation::Donation;
+use crate::model::gift_shop_sale::GiftShopSale;
+use crate::model::membership::Membership;
+use crate::model::refund::Refund;
+use crate::model::transaction_record::TransactionRecord;
+use crate::view::summary_dicts::SummaryDicts;
+
+/// Every row recorded over some stretch of time, which is what reports are built from.
+#[derive(Debug, Clone, Default)]
+pub struct Ledger {
+    pub admissions: Vec<DateTimeWrapper<Admission>>,
+    pub memberships: Vec<DateTimeWrapper<Membership>>,
+    pub donations: Vec<DateTimeWrapper<Donation>>,
+    pub gift_shop_sales: Vec<DateTimeWrapper<GiftShopSale>>,
+    pub transactions: Vec<DateTimeWrapper<TransactionRecord>>,
+    pub refunds: Vec<DateTimeWrapper<Refund>>,
+}
+
+/// A model that ledgers keep a list of, and how one of its rows counts towards a summary.
+pub trait LedgerRow: Sized {
+    fn rows(ledger: &mut Ledger) -> &mut Vec<DateTimeWrapper<Self>>;
+    fn summarize(row: &DateTimeWrapper<Self>, summary: &mut SummaryDicts);
+}
+
+macro_rules! ledger_row {
+    ($model:ty, $rows:ident, $add:ident) => {
+        impl LedgerRow for $model {
+            fn rows(ledger: &mut Ledger) -> &mut Vec<DateTimeWrapper<Self>> {
+                &mut ledger.$rows
+            }
+
+            fn summarize(row: &DateTimeWrapper<Self>, summary: &mut SummaryDicts) {
+                summary.$add(row)
+            }
+        }
+    };
+}
+
+ledger_row!(Admission, admissions, add_admission);
+ledger_row!(Membership, memberships, add_membership);
+ledger_row!(Donation, donations, add_donation);
+ledger_row!(GiftShopSale, gift_shop_sales, add_gift_shop_sale);
+ledger_row!(TransactionRecord, transactions, add_transaction);
+ledger_row!(Refund, refunds, add_refund);
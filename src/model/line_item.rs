@@ -0,0 +1,68 @@
+use chrono::{DateTime, Local};
+use crate::model::as_transaction_record::AsTransactionRecord;
+use crate::model::date_time_wrapper::DateTimeWrapper;
+use crate::model::has_payment_method::HasPaymentMethod;
+use crate::model::ledger::Ledger;
+use crate::model::payment_method::PaymentMethod;
+use crate::model::refund::Refund;
+use crate::model::transaction_record::TransactionKind;
+
+/// One thing that was sold, in the same shape no matter which table it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineItem {
+    pub date_time: DateTime<Local>,
+    pub kind: TransactionKind,
+    pub description: String,
+    pub quantity: u16,
+    pub payment_method: Option<PaymentMethod>,
+    pub sales_tax: f32,
+    pub total: f32,
+    /// The sale it was part of, if it was rung up after sales were numbered.
+    pub sale: Option<i64>,
+    /// Money given back rather than taken, in which case `sales_tax` and `total` are negative.
+    pub refund: bool,
+}
+
+impl LineItem {
+    fn new<T: AsTransactionRecord + HasPaymentMethod>(item: &DateTimeWrapper<T>, sales_tax: f32) -> Self {
+        let record = item.as_transaction_record();
+        Self {
+            date_time: item.date_time(),
+            kind: record.kind,
+            description: record.description,
+            quantity: record.quantity,
+            payment_method: item.payment_method(),
+            sales_tax,
+            total: record.total_cost,
+            sale: record.sale,
+            refund: false,
+        }
+    }
+
+    fn refund(refund: &DateTimeWrapper<Refund>) -> Self {
+        Self {
+            date_time: refund.date_time(),
+            kind: refund.kind,
+            description: format!("Refund: {}", refund.description),
+            quantity: refund.quantity,
+            payment_method: refund.payment_method,
+            sales_tax: -refund.sales_tax,
+            total: -refund.amount,
+            sale: Some(refund.sale),
+            refund: true,
+        }
+    }
+
+    /// Every line item in the ledger, refunds included, oldest first.
+    pub fn from_ledger(ledger: &Ledger) -> Vec<LineItem> {
+        let mut items: Vec<LineItem> = ledger.admissions.iter().map(|x| Self::new(x, 0.0))
+            .chain(ledger.memberships.iter().map(|x| Self::new(x, 0.0)))
+            .chain(ledger.donations.iter().map(|x| Self::new(x, 0.0)))
+            .chain(ledger.gift_shop_sales.iter().map(|x| Self::new(x, x.compute_tax())))
+            .chain(ledger.refunds.iter().map(Self::refund))
+            .collect();
+
+        items.sort_by_key(|x| x.date_time);
+        items
+    }
+}
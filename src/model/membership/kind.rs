@@ -1,7 +1,7 @@
-use sqlite::Value;
+use serde::{Deserialize, Serialize};
 use strum::{Display, EnumString, VariantArray};
 
-#[derive(Eq, PartialEq, Debug, Clone, Copy, Default, VariantArray, Display, EnumString)]
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Default, VariantArray, Display, EnumString, Serialize, Deserialize)]
 pub enum Kind {
     #[default]
     #[strum(serialize = "Family")]
@@ -28,20 +28,3 @@ impl Kind {
     }
 }
 
-impl TryFrom<&Value> for Kind {
-    type Error = sqlite::Error;
-
-    fn try_from(value: &Value) -> Result<Self, Self::Error> {
-        let Value::String(value) = value else {
-            return Err(sqlite::Error {
-                code: None,
-                message: Some("Value is not a string, and must be.".to_string()),
-            })
-        };
-
-        Kind::try_from(value.as_str()).map_err(|_| sqlite::Error {
-            code: None,
-            message: Some("Unable to convert string to membership kind.".to_string()),
-        })
-    }
-}
\ No newline at end of file
@@ -1,8 +1,6 @@
 use std::fmt::Display;
-use sqlite::Row;
-use crate::database::database_object::CanBuildObjectMapper;
-use crate::database::from_sql::FromSql;
-use crate::database::object_mapper::ObjectMapper;
+use pos_derive::DbObject;
+use serde::{Deserialize, Serialize};
 use crate::model::as_transaction_record::AsTransactionRecord;
 use crate::model::date_time_wrapper::WrapInDateTime;
 use crate::model::has_payment_method::HasPaymentMethod;
@@ -12,11 +10,15 @@ use crate::model::payment_method::PaymentMethod;
 use crate::model::transaction_record::{TransactionKind, TransactionRecord};
 
 pub mod kind;
-#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Default, DbObject, Serialize, Deserialize)]
+#[db(table = "memberships")]
 pub struct Membership {
-    kind: Kind,
-    payment_method: PaymentMethod,
-    pub quantity: u16
+    #[db(with = "crate::database::codec::text")]
+    pub kind: Kind,
+    pub payment_method: PaymentMethod,
+    pub quantity: u16,
+    /// The sale this was rung up in. Rows from before sales were numbered have none.
+    pub sale: Option<i64>,
 }
 
 
@@ -26,6 +28,7 @@ impl Membership {
             kind,
             payment_method,
             quantity,
+            sale: None,
         }
     }
     pub fn matches_type(&self, kind: Kind) -> bool {
@@ -40,45 +43,11 @@ impl AsTransactionRecord for Membership {
             self.kind.to_string(),
             self.quantity,
             self.total_cost()
-        )
+        ).in_sale(self.sale)
     }
 }
 
 impl WrapInDateTime for Membership {}
-impl CanBuildObjectMapper for Membership {
-    const TABLE_NAME: &'static str = "memberships";
-
-    fn build_object_mapper(&self) -> ObjectMapper {
-        ObjectMapper::new(Self::TABLE_NAME)
-            .add_field("kind", self.kind.to_string())
-            .add_field("payment_method", self.payment_method.to_string())
-            .add_field("quantity", self.quantity as i64)
-    }
-}
-
-impl FromSql for Membership {
-    fn from_sql(row: Row) -> anyhow::Result<Self>
-    where
-        Self: Sized
-    {
-        let quantity:i64 = row.try_read("quantity")?;
-        Ok(Self {
-            kind: row.try_read("kind")?,
-            payment_method: row.try_read("payment_method")?,
-            quantity: quantity as u16,
-        })
-    }
-}
-impl Default for Membership {
-    fn default() -> Self {
-        Self {
-            kind: Default::default(),
-            payment_method: Default::default(),
-            quantity: 0,
-        }
-    }
-}
-
 impl HasTotalCost for Membership {
     fn total_cost(&self) -> f32 {
         self.quantity as f32 * self.kind.price()
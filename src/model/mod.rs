@@ -9,7 +9,14 @@ pub mod gift_shop_sale;
 pub mod admission;
 pub mod transaction_record;
 pub mod membership;
-pub mod hour;
 pub(crate) mod has_total_cost;
 pub mod has_payment_method;
-pub mod cart_item;
\ No newline at end of file
+pub mod cart_item;
+pub mod audit_entry;
+pub mod ledger;
+pub mod line_item;
+pub mod day_close;
+pub mod sale;
+pub mod refund;
+pub mod unfinished_cart;
+pub mod quick_sale;
@@ -1,9 +1,10 @@
+use serde::{Deserialize, Serialize};
 use sqlite::Value;
 use strum::{Display, EnumString, VariantArray};
 use crate::database::has_schema::{HasSchema, NOT_NULL};
 use crate::database::to_sql::ToSql;
 
-#[derive(Eq, PartialEq, Debug, Clone, Copy, VariantArray, Display, Default, EnumString)]
+#[derive(Eq, PartialEq, Debug, Clone, Copy, VariantArray, Display, Default, EnumString, Serialize, Deserialize)]
 pub enum PaymentMethod {
     #[default]
     Cash,
@@ -21,8 +22,8 @@ impl HasSchema for PaymentMethod {
 }
 
 impl ToSql for PaymentMethod {
-    fn to_sql(&self) -> String {
-        format!("'{}'", self.to_string())
+    fn to_sql(&self) -> Value {
+        Value::String(self.to_string())
     }
 }
 
@@ -42,4 +43,6 @@ impl TryFrom<&Value> for PaymentMethod {
             message: Some("Unable to convert string to payment method.".to_string()),
         })
     }
-}
\ No newline at end of file
+}
+
+crate::from_column_via_try_from!(PaymentMethod);
@@ -0,0 +1,118 @@
+use pos_derive::DbObject;
+use strum::VariantArray;
+use crate::model::admission::Admission;
+use crate::model::admission::kind::Kind as AdmissionKind;
+use crate::model::cart_item::CartItem;
+use crate::model::donation::Donation;
+use crate::model::gift_shop_sale::{GiftShopSale, DEFAULT_SALES_TAX};
+use crate::model::membership::Membership;
+use crate::model::membership::kind::Kind as MembershipKind;
+use crate::model::payment_method::PaymentMethod;
+use crate::model::transaction_record::TransactionKind;
+
+/// A button on the sales screen that puts a common item straight into the cart, e.g. "2 Adults".
+#[derive(Debug, Clone, PartialEq, DbObject)]
+#[db(table = "quick_sales")]
+pub struct QuickSale {
+    /// Where it is in the grid, counting from 0. The first nine can also be rung up with the keys 1 to 9.
+    pub position: u16,
+    pub label: String,
+    pub category: TransactionKind,
+    /// The admission or membership kind, or the gift shop item's description. Donations have none.
+    pub kind: String,
+    pub quantity: u16,
+    /// What a donation or one gift shop item costs. Admissions and memberships have set prices.
+    pub price: f32,
+    /// Always paid this way. With none, it is paid however the cashier picked above the grid.
+    pub payment_method: Option<PaymentMethod>,
+    /// The sales tax rate on a gift shop item, as a percentage. Other items aren't taxed.
+    pub sales_tax: f32,
+}
+
+impl Default for QuickSale {
+    fn default() -> Self {
+        Self {
+            position: 0,
+            label: String::new(),
+            category: TransactionKind::Admission,
+            kind: AdmissionKind::Adult.to_string(),
+            quantity: 1,
+            price: 0.0,
+            payment_method: None,
+            sales_tax: DEFAULT_SALES_TAX,
+        }
+    }
+}
+
+impl QuickSale {
+    /// The kinds `category` can be, or none if it is described freely or not at all.
+    pub fn kinds(category: TransactionKind) -> Vec<String> {
+        match category {
+            TransactionKind::Admission => AdmissionKind::VARIANTS.iter().map(|x| x.to_string()).collect(),
+            TransactionKind::Membership => MembershipKind::VARIANTS.iter().map(|x| x.to_string()).collect(),
+            TransactionKind::Donation | TransactionKind::GiftShopSale => vec![],
+        }
+    }
+
+    /// The item the button adds, paid for with its own payment method or else `picked`.
+    pub fn to_cart_item(&self, picked: Option<PaymentMethod>) -> anyhow::Result<CartItem> {
+        anyhow::ensure!(self.quantity > 0, "{} has no quantity", self.label);
+        let payment_method = self.payment_method.or(picked);
+        let paid = || payment_method.ok_or_else(|| anyhow::anyhow!("pick how {} is being paid for", self.label));
+
+        Ok(match self.category {
+            TransactionKind::Admission => {
+                let kind: AdmissionKind = self.kind.parse().map_err(|_| anyhow::anyhow!("{:?} is not an admission", self.kind))?;
+                let payment_method = if kind.is_free() { None } else { Some(paid()?) };
+                Admission::new(kind, payment_method, self.quantity).into()
+            }
+            TransactionKind::Membership => {
+                let kind: MembershipKind = self.kind.parse().map_err(|_| anyhow::anyhow!("{:?} is not a membership", self.kind))?;
+                Membership::new(kind, paid()?, self.quantity).into()
+            }
+            TransactionKind::Donation => {
+                anyhow::ensure!(self.price > 0.0, "{} has no amount", self.label);
+                Donation { payment_method: paid()?, price: self.price, sale: None }.into()
+            }
+            TransactionKind::GiftShopSale => {
+                anyhow::ensure!(!self.kind.trim().is_empty(), "{} has no item description", self.label);
+                anyhow::ensure!(self.price > 0.0, "{} has no price", self.label);
+                anyhow::ensure!(self.sales_tax >= 0.0, "{} has a negative sales tax", self.label);
+                GiftShopSale::new(self.kind.clone(), self.price, paid()?, self.quantity, self.sales_tax).into()
+            }
+        })
+    }
+
+    /// Whether it could be rung up once a payment method is picked.
+    pub fn check(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(!self.label.trim().is_empty(), "every quick sale button needs a label");
+        self.to_cart_item(Some(PaymentMethod::Cash)).map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::has_payment_method::HasPaymentMethod;
+    use crate::model::has_total_cost::HasTotalCost;
+    use super::*;
+
+    #[test]
+    fn buttons_without_a_payment_method_use_the_one_picked() {
+        let two_adults = QuickSale { label: "2 Adults".to_string(), quantity: 2, ..Default::default() };
+        assert!(two_adults.to_cart_item(None).is_err());
+        let item = two_adults.to_cart_item(Some(PaymentMethod::CreditCard)).unwrap();
+        assert_eq!(item.payment_method(), Some(PaymentMethod::CreditCard));
+        assert_eq!(item.total_cost(), 16.0);
+
+        let cash_only = QuickSale { payment_method: Some(PaymentMethod::Cash), ..two_adults };
+        assert_eq!(cash_only.to_cart_item(Some(PaymentMethod::CreditCard)).unwrap().payment_method(), Some(PaymentMethod::Cash));
+
+        let under_six = QuickSale { label: "Under 6".to_string(), kind: "Child (Under 6)".to_string(), ..Default::default() };
+        assert_eq!(under_six.to_cart_item(None).unwrap().payment_method(), None);
+
+        let postcard = QuickSale { label: "Postcard".to_string(), category: TransactionKind::GiftShopSale, kind: String::new(), price: 1.0, ..Default::default() };
+        assert!(postcard.check().is_err());
+        let tax_free = QuickSale { kind: "Postcard".to_string(), quantity: 2, sales_tax: 0.0, ..postcard };
+        assert_eq!(tax_free.to_cart_item(Some(PaymentMethod::Cash)).unwrap().total_cost(), 2.0);
+    }
+}
@@ -0,0 +1,56 @@
+use pos_derive::DbObject;
+use crate::model::date_time_wrapper::WrapInDateTime;
+use crate::model::has_payment_method::HasPaymentMethod;
+use crate::model::has_total_cost::HasTotalCost;
+use crate::model::line_item::LineItem;
+use crate::model::payment_method::PaymentMethod;
+use crate::model::transaction_record::{TransactionKind, TransactionRecord};
+
+/// Money given back for one line of an earlier sale. It is recorded on the day it was given back, so the day of the
+/// sale is left as it was.
+#[derive(Debug, Clone, PartialEq, Default, DbObject)]
+#[db(table = "refunds")]
+pub struct Refund {
+    /// The number of the sale the line was in.
+    pub sale: i64,
+    pub kind: TransactionKind,
+    pub description: String,
+    pub quantity: u16,
+    pub payment_method: Option<PaymentMethod>,
+    /// The part of `amount` that was sales tax.
+    pub sales_tax: f32,
+    pub amount: f32,
+}
+
+impl Refund {
+    /// Gives back everything paid for `line`, which must be part of sale `sale`.
+    pub fn of(line: &LineItem, sale: i64) -> Self {
+        Self {
+            sale,
+            kind: line.kind,
+            description: line.description.clone(),
+            quantity: line.quantity,
+            payment_method: line.payment_method,
+            sales_tax: line.sales_tax,
+            amount: line.total,
+        }
+    }
+
+    /// The negative transaction that keeps `transaction_records` adding up to what was actually taken.
+    pub fn as_transaction_record(&self) -> TransactionRecord {
+        TransactionRecord::new(self.kind, format!("Refund: {}", self.description), self.quantity, -self.amount)
+            .in_sale(Some(self.sale))
+    }
+}
+
+impl WrapInDateTime for Refund {}
+impl HasTotalCost for Refund {
+    fn total_cost(&self) -> f32 {
+        -self.amount
+    }
+}
+impl HasPaymentMethod for Refund {
+    fn payment_method(&self) -> Option<PaymentMethod> {
+        self.payment_method
+    }
+}
@@ -0,0 +1,63 @@
+use chrono::{DateTime, Local};
+use pos_derive::DbObject;
+use strum::{Display, EnumString};
+use crate::model::date_time_wrapper::{DateTimeWrapper, WrapInDateTime};
+use crate::model::line_item::LineItem;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Display, EnumString)]
+pub enum SaleStatus {
+    #[default]
+    Completed,
+    /// Taken back the same business day. Its rows are gone and it counts for nothing.
+    Voided,
+}
+
+/// One cart rung up at the till. Every row it added carries its number.
+#[derive(Debug, Clone, PartialEq, Default, DbObject)]
+#[db(table = "sales")]
+pub struct Sale {
+    /// Counts up from 1 and never repeats, so receipts can be matched to sales.
+    pub number: i64,
+    pub cashier: String,
+    pub total_cost: f32,
+    #[db(with = "crate::database::codec::text")]
+    pub status: SaleStatus,
+}
+
+impl WrapInDateTime for Sale {}
+
+/// A sale and what was in it. Rows recorded before sales were numbered have no sale and a line each.
+#[derive(Debug, Clone)]
+pub struct SaleLines {
+    pub sale: Option<DateTimeWrapper<Sale>>,
+    /// What was sold, followed by anything refunded, oldest first.
+    pub lines: Vec<LineItem>,
+}
+
+impl SaleLines {
+    pub fn number(&self) -> Option<i64> {
+        self.sale.as_ref().map(|x| x.number)
+    }
+
+    pub fn is_refunded(&self) -> bool {
+        self.lines.iter().any(|x| x.refund)
+    }
+
+    /// "Completed", "Voided" or "Refunded".
+    pub fn status(&self) -> String {
+        match &self.sale {
+            Some(sale) if sale.status == SaleStatus::Completed && self.is_refunded() => "Refunded".to_string(),
+            Some(sale) => sale.status.to_string(),
+            None => SaleStatus::Completed.to_string(),
+        }
+    }
+
+    /// When the sale was rung up, or the line was for rows without one.
+    pub fn date_time(&self) -> Option<DateTime<Local>> {
+        self.sale.as_ref().map(|x| x.date_time()).or(self.lines.first().map(|x| x.date_time))
+    }
+
+    pub fn total(&self) -> f32 {
+        self.lines.iter().filter(|x| !x.refund).fold(0.0, |total, x| total + x.total)
+    }
+}
@@ -1,14 +1,12 @@
-use sqlite::{Row, Value};
-use strum::{Display, EnumString};
-use crate::database::database_object::CanBuildObjectMapper;
-use crate::database::from_sql::FromSql;
+use pos_derive::DbObject;
+use sqlite::Value;
+use strum::{Display, EnumString, VariantArray};
 use crate::database::has_schema::{HasSchema, NOT_NULL};
-use crate::database::object_mapper::ObjectMapper;
 use crate::database::to_sql::ToSql;
 use crate::model::date_time_wrapper::WrapInDateTime;
 use crate::model::has_total_cost::HasTotalCost;
 
-#[derive(Eq, PartialEq, Debug, Clone, Copy, Default, Display, EnumString)]
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Default, Display, EnumString, VariantArray)]
 pub enum TransactionKind {
     #[default]
     Admission,
@@ -27,8 +25,8 @@ impl HasSchema for TransactionKind {
     }
 }
 impl ToSql for TransactionKind {
-    fn to_sql(&self) -> String {
-        format!("'{}'", self.to_string())
+    fn to_sql(&self) -> Value {
+        Value::String(self.to_string())
     }
 }
 impl TryFrom<&Value> for TransactionKind {
@@ -49,13 +47,17 @@ impl TryFrom<&Value> for TransactionKind {
     }
 }
 
+crate::from_column_via_try_from!(TransactionKind);
 
-#[derive(Debug, Clone)]
+#[derive(PartialEq, Debug, Clone, DbObject)]
+#[db(table = "transaction_records")]
 pub struct TransactionRecord {
     pub kind: TransactionKind,
     pub description: String,
     pub quantity: u16,
     pub total_cost: f32,
+    /// The sale this was rung up in. Rows from before sales were numbered have none.
+    pub sale: Option<i64>,
 }
 
 impl TransactionRecord {
@@ -65,37 +67,16 @@ impl TransactionRecord {
             description,
             quantity,
             total_cost: amount,
+            sale: None,
         }
     }
-}
-
-impl CanBuildObjectMapper for TransactionRecord {
-    const TABLE_NAME: &'static str = "transaction_records";
 
-    fn build_object_mapper(&self) -> ObjectMapper {
-        ObjectMapper::new(Self::TABLE_NAME)
-            .add_field("kind", self.kind)
-            .add_field("description", self.description.clone())
-            .add_field("quantity", self.quantity as i32)
-            .add_field("total_cost", self.total_cost)
+    pub fn in_sale(mut self, sale: Option<i64>) -> Self {
+        self.sale = sale;
+        self
     }
 }
 
-impl FromSql for TransactionRecord {
-    fn from_sql(row: Row) -> anyhow::Result<Self>
-    where
-        Self: Sized
-    {
-        let quantity:i64 = row.try_read("quantity")?;
-        let total_cost:f64 = row.try_read("total_cost")?;
-        Ok(Self {
-            kind: row.try_read("kind")?,
-            description: row.try_read::<&str, _>("description")?.to_string(),
-            quantity: quantity as u16,
-            total_cost: total_cost as f32,
-        })
-    }
-}
 impl Default for TransactionRecord {
     fn default() -> Self {
         Self {
@@ -103,6 +84,7 @@ impl Default for TransactionRecord {
             description: "".to_string(),
             quantity: 0,
             total_cost: 0.0,
+            sale: None,
         }
     }
 }
@@ -0,0 +1,40 @@
+use chrono::{DateTime, Local};
+use pos_derive::DbObject;
+use crate::model::cart_item::CartItem;
+use crate::model::date_time_wrapper::WrapInDateTime;
+use crate::model::has_total_cost::HasTotalCost;
+use crate::settings::timezone::Timezone;
+use crate::view::adapters::ff;
+
+/// An item in a cart that hasn't been saved as a sale yet. The cart on screen is kept as it changes, so it can be
+/// restored if the program closes mid-sale, and parked carts wait here until they are recalled.
+#[derive(Debug, Clone, PartialEq, DbObject)]
+#[db(table = "unfinished_cart")]
+pub struct UnfinishedCartItem {
+    #[db(with = "crate::database::codec::json")]
+    pub item: CartItem,
+    /// The label of the parked cart it is in, or none if it is in the cart on screen.
+    pub parked: Option<String>,
+}
+
+impl WrapInDateTime for UnfinishedCartItem {}
+
+/// A cart set aside under a label so the next visitor can be served, e.g. while a family finds one more postcard.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParkedCart {
+    pub label: String,
+    pub parked: DateTime<Local>,
+    pub items: Vec<CartItem>,
+}
+
+impl ParkedCart {
+    /// "Smith family: 3 items, $21.00, parked at 2:14pm", by the museum's clocks.
+    pub fn describe(&self, timezone: Timezone) -> String {
+        let total = self.items.iter().map(|x| x.total_cost()).sum();
+        let items = match self.items.len() {
+            1 => "1 item".to_string(),
+            count => format!("{count} items"),
+        };
+        format!("{}: {items}, {}, parked at {}", self.label, ff("$", total), timezone.clock(self.parked).format("%-I:%M%P"))
+    }
+}
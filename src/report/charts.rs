@@ -0,0 +1,259 @@
+//! Charts for the HTML report, drawn as inline SVG. A report stays one self-contained file that needs no scripts or
+//! network, and prints just as it looks on screen.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use chrono::{DateTime, Local, NaiveDate};
+use itertools::Itertools;
+use serde::Serialize;
+use crate::model::has_total_cost::HasTotalCost;
+use crate::model::ledger::Ledger;
+use crate::report::period::Period;
+use crate::settings::operating_hours::OperatingHours;
+use crate::view::adapters::ff;
+use crate::view::summary_dicts::SummaryDicts;
+
+const WIDTH: f64 = 640.0;
+const FONT: &str = r#"font-family="sans-serif" font-size="11""#;
+/// Distinct in colour and still distinct from each other when printed in greyscale.
+const PALETTE: [&str; 6] = ["#1f4e79", "#f4a259", "#5b8e7d", "#bc4b51", "#8cb8d8", "#3d3d3d"];
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn money(value: f64) -> String {
+    ff("$", value as f32)
+}
+
+fn count(value: f64) -> String {
+    format!("{value:.0}")
+}
+
+/// The smallest 1, 2 or 5 times a power of ten that is at least `max`, so axes end on a round number.
+fn nice_max(max: f64) -> f64 {
+    if max <= 0.0 {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(max.log10().floor());
+    [1.0, 2.0, 5.0, 10.0].into_iter().map(|x| x * magnitude).find(|x| *x >= max).unwrap_or(max)
+}
+
+fn open(height: f64, title: &str) -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {WIDTH} {height}" width="{WIDTH}" height="{height}" role="img" aria-label="{title}"><text x="0" y="16" font-family="sans-serif" font-size="14" font-weight="bold">{title}</text>"#,
+        title = escape(title),
+    )
+}
+
+fn nothing_to_show(svg: &mut String, y: f64) {
+    let _ = write!(svg, r##"<text x="{}" y="{y}" {FONT} text-anchor="middle" fill="#777">Nothing recorded</text>"##, WIDTH / 2.0);
+}
+
+/// Horizontal gridlines with their values down the left, for the column and line charts. Returns the scale's maximum.
+fn axis(svg: &mut String, max: f64, top: f64, bottom: f64, left: f64, label: fn(f64) -> String) -> f64 {
+    let max = nice_max(max);
+    for step in 0..=4 {
+        let value = max * step as f64 / 4.0;
+        let y = bottom - (bottom - top) * step as f64 / 4.0;
+        let _ = write!(
+            svg,
+            r##"<line x1="{left}" y1="{y:.1}" x2="{WIDTH}" y2="{y:.1}" stroke="#ddd"/><text x="{:.1}" y="{:.1}" {FONT} text-anchor="end">{}</text>"##,
+            left - 4.0, y + 4.0, escape(&label(value)),
+        );
+    }
+    max
+}
+
+/// Labels along the bottom, turned on their side when there are too many to fit flat. Only every `every`th is drawn.
+fn x_labels(svg: &mut String, labels: &[(f64, &str)], y: f64, every: usize) {
+    let turned = labels.len() > 8;
+    for (x, label) in labels.iter().step_by(every.max(1)) {
+        if turned {
+            let _ = write!(svg, r#"<text x="{x:.1}" y="{y}" {FONT} text-anchor="end" transform="rotate(-40 {x:.1} {y})">{}</text>"#, escape(label));
+        } else {
+            let _ = write!(svg, r#"<text x="{x:.1}" y="{y}" {FONT} text-anchor="middle">{}</text>"#, escape(label));
+        }
+    }
+}
+
+/// One upright bar per value, left to right, e.g. visitors per hour.
+pub fn column_chart(title: &str, values: &[(String, f64)], label: fn(f64) -> String) -> String {
+    let (height, top, bottom, left) = (260.0, 34.0, 190.0, 50.0);
+    let mut svg = open(height, title);
+    if values.iter().all(|(_, x)| *x == 0.0) {
+        nothing_to_show(&mut svg, height / 2.0);
+        return svg + "</svg>";
+    }
+
+    let max = axis(&mut svg, values.iter().map(|(_, x)| *x).fold(0.0, f64::max), top, bottom, left, label);
+    let slot = (WIDTH - left) / values.len() as f64;
+    for (index, (_, value)) in values.iter().enumerate() {
+        let bar_height = (bottom - top) * value / max;
+        let x = left + slot * index as f64 + slot * 0.15;
+        let _ = write!(
+            svg,
+            r#"<rect x="{x:.1}" y="{:.1}" width="{:.1}" height="{bar_height:.1}" fill="{}"/>"#,
+            bottom - bar_height, slot * 0.7, PALETTE[0],
+        );
+        if *value > 0.0 && slot >= 24.0 {
+            let _ = write!(svg, r#"<text x="{:.1}" y="{:.1}" {FONT} text-anchor="middle">{}</text>"#, x + slot * 0.35, bottom - bar_height - 3.0, escape(&label(*value)));
+        }
+    }
+
+    let labels = values.iter().enumerate().map(|(index, (name, _))| (left + slot * (index as f64 + 0.5), name.as_str())).collect_vec();
+    x_labels(&mut svg, &labels, bottom + 14.0, 1);
+    svg + "</svg>"
+}
+
+/// One bar per category, top to bottom, with its name on the left and its value on the right.
+pub fn bar_chart(title: &str, values: &[(String, f64)], label: fn(f64) -> String) -> String {
+    let (top, row, left, right) = (30.0, 24.0, 150.0, 80.0);
+    let height = top + row * values.len() as f64 + 6.0;
+    let mut svg = open(height, title);
+    let max = nice_max(values.iter().map(|(_, x)| *x).fold(0.0, f64::max));
+
+    for (index, (name, value)) in values.iter().enumerate() {
+        let y = top + row * index as f64;
+        let width = (WIDTH - left - right) * value.max(0.0) / max;
+        let _ = write!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" {FONT} text-anchor="end">{}</text><rect x="{left}" y="{:.1}" width="{width:.1}" height="{:.1}" fill="{}"/><text x="{:.1}" y="{:.1}" {FONT}>{}</text>"#,
+            left - 6.0, y + row * 0.6, escape(name),
+            y + row * 0.15, row * 0.7, PALETTE[index % PALETTE.len()],
+            left + width + 4.0, y + row * 0.6, escape(&label(*value)),
+        );
+    }
+    svg + "</svg>"
+}
+
+/// How a whole divides into parts, as one bar split into segments, with each part's share in the legend.
+pub fn share_chart(title: &str, parts: &[(String, f64)], label: fn(f64) -> String) -> String {
+    let height = 96.0;
+    let mut svg = open(height, title);
+    let total: f64 = parts.iter().map(|(_, x)| x.max(0.0)).sum();
+    if total <= 0.0 {
+        nothing_to_show(&mut svg, height / 2.0);
+        return svg + "</svg>";
+    }
+
+    let (mut x, mut legend_x) = (0.0, 0.0);
+    for (index, (name, value)) in parts.iter().enumerate() {
+        let color = PALETTE[index % PALETTE.len()];
+        let share = value.max(0.0) / total;
+        let _ = write!(svg, r#"<rect x="{x:.1}" y="28" width="{:.1}" height="26" fill="{color}"/>"#, WIDTH * share);
+        x += WIDTH * share;
+
+        let legend = format!("{} {} ({:.0}%)", name, label(*value), share * 100.0);
+        let _ = write!(
+            svg,
+            r#"<rect x="{legend_x:.1}" y="68" width="12" height="12" fill="{color}"/><text x="{:.1}" y="78" {FONT}>{}</text>"#,
+            legend_x + 16.0, escape(&legend),
+        );
+        legend_x += 16.0 + legend.len() as f64 * 6.5 + 16.0;
+    }
+    svg + "</svg>"
+}
+
+/// A line through one value per point, left to right, e.g. visitors per day.
+pub fn line_chart(title: &str, values: &[(String, f64)], label: fn(f64) -> String) -> String {
+    let (height, top, bottom, left) = (240.0, 34.0, 180.0, 50.0);
+    let mut svg = open(height, title);
+    let max = axis(&mut svg, values.iter().map(|(_, x)| *x).fold(0.0, f64::max), top, bottom, left, label);
+
+    let step = (WIDTH - left - 10.0) / (values.len().max(2) - 1) as f64;
+    let points = values.iter().enumerate()
+        .map(|(index, (_, value))| (left + step * index as f64, bottom - (bottom - top) * value / max))
+        .collect_vec();
+    let _ = write!(
+        svg,
+        r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="2"/>"#,
+        points.iter().map(|(x, y)| format!("{x:.1},{y:.1}")).join(" "), PALETTE[0],
+    );
+    if points.len() <= 31 {
+        for (x, y) in &points {
+            let _ = write!(svg, r#"<circle cx="{x:.1}" cy="{y:.1}" r="2.5" fill="{}"/>"#, PALETTE[0]);
+        }
+    }
+
+    let labels = values.iter().zip(&points).map(|((name, _), (x, _))| (*x, name.as_str())).collect_vec();
+    x_labels(&mut svg, &labels, bottom + 14.0, values.len().div_ceil(16));
+    svg + "</svg>"
+}
+
+/// Every chart in the summary report, ready to drop into the page.
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportCharts {
+    pub attendance_by_hour: String,
+    pub revenue_by_category: String,
+    pub tender_mix: String,
+    /// Only for reports covering more than one day.
+    pub daily_attendance: Option<String>,
+    pub daily_revenue: Option<String>,
+}
+
+impl ReportCharts {
+    pub fn new(summary: &SummaryDicts, ledger: &Ledger, operating_hours: &OperatingHours, period: &Period) -> Self {
+        let attendance = summary.attendance_by_hour.iter().map(|(k, v)| (k.clone(), v.value())).collect_vec();
+        let revenue = ["Admissions Revenue", "Membership Sales", "Total Donations", "Gift Shop Sales"].into_iter()
+            .map(|key| (key.to_string(), summary.summary.get(key).map_or(0.0, |x| x.value())))
+            .collect_vec();
+        let tenders = summary.payments.iter()
+            .filter_map(|(key, value)| Some((key.strip_prefix("Total ")?.to_string(), value.value())))
+            .collect_vec();
+
+        let mut daily_attendance = None;
+        let mut daily_revenue = None;
+        if period.from != period.to {
+            let mut days: BTreeMap<NaiveDate, (f64, f64)> = period.dates().map(|x| (x, (0.0, 0.0))).collect();
+            let business_date = |at: DateTime<Local>| operating_hours.business_date(operating_hours.timezone.clock(at));
+            for admission in &ledger.admissions {
+                if let Some(day) = days.get_mut(&business_date(admission.date_time())) {
+                    day.0 += admission.quantity as f64;
+                }
+            }
+            for transaction in &ledger.transactions {
+                if let Some(day) = days.get_mut(&business_date(transaction.date_time())) {
+                    day.1 += transaction.total_cost() as f64;
+                }
+            }
+
+            let label = |date: &NaiveDate| date.format("%b %-d").to_string();
+            daily_attendance = Some(line_chart("Daily Attendance", &days.iter().map(|(k, v)| (label(k), v.0)).collect_vec(), count));
+            daily_revenue = Some(line_chart("Daily Revenue", &days.iter().map(|(k, v)| (label(k), v.1)).collect_vec(), money));
+        }
+
+        Self {
+            attendance_by_hour: column_chart("Attendance by Hour", &attendance, count),
+            revenue_by_category: bar_chart("Revenue by Category", &revenue, money),
+            tender_mix: share_chart("Payment Methods", &tenders, money),
+            daily_attendance,
+            daily_revenue,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn axes_end_on_round_numbers() {
+        assert_eq!(nice_max(0.0), 1.0);
+        assert_eq!(nice_max(7.0), 10.0);
+        assert_eq!(nice_max(130.0), 200.0);
+        assert_eq!(nice_max(4100.0), 5000.0);
+    }
+
+    #[test]
+    fn charts_are_self_contained_svg() {
+        let values = vec![("Cash & Coins".to_string(), 30.0), ("Credit Card".to_string(), 10.0)];
+        let chart = share_chart("Payment Methods", &values, money);
+
+        assert!(chart.starts_with("<svg") && chart.ends_with("</svg>"));
+        assert!(chart.contains("Cash &amp; Coins $30.00 (75%)"));
+        assert!(!chart.contains("<script"));
+        assert!(column_chart("Attendance", &[("10am".to_string(), 0.0)], count).contains("Nothing recorded"));
+        assert_eq!(column_chart("Attendance", &values, count).matches("<rect").count(), 2);
+    }
+}
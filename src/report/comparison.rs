@@ -0,0 +1,259 @@
+//! Two periods side by side, metric by metric, for questions like "how does this July compare with last July?".
+
+use std::io::Write;
+use indexmap::IndexMap;
+use itertools::Itertools;
+use minijinja::Environment;
+use serde::Serialize;
+use serde_json::{json, Map};
+use strum::{Display, VariantArray};
+use crate::database::Database;
+use crate::report::period::Period;
+use crate::report::summary_export;
+use crate::settings::operating_hours::OperatingHours;
+use crate::view::adapters::ff;
+use crate::view::summary_dicts::{Metric, SummaryDicts};
+
+/// What to compare a period with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, VariantArray, Display)]
+pub enum Baseline {
+    #[default]
+    #[strum(serialize = "Same Days Last Year")]
+    YearBefore,
+    #[strum(serialize = "Previous Period")]
+    Preceding,
+    Custom,
+}
+
+impl Baseline {
+    /// The period to compare `current` with. `custom` is only used, and only needed, for a custom baseline.
+    pub fn period(&self, current: &Period, custom: Option<Period>) -> Option<Period> {
+        match self {
+            Baseline::YearBefore => Some(current.year_before()),
+            Baseline::Preceding => Some(current.preceding()),
+            Baseline::Custom => custom,
+        }
+    }
+}
+
+/// One metric from both periods.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComparedMetric {
+    pub name: String,
+    pub current: Metric,
+    pub previous: Metric,
+}
+
+/// Puts a sign in front of a change, leaving it off when nothing changed.
+fn signed(change: f64, magnitude: String) -> String {
+    if change.abs() < 0.005 {
+        magnitude
+    } else if change > 0.0 {
+        format!("+{magnitude}")
+    } else {
+        format!("-{magnitude}")
+    }
+}
+
+impl ComparedMetric {
+    pub fn change(&self) -> f64 {
+        self.current.value() - self.previous.value()
+    }
+
+    /// `None` when the earlier period had none of this, since any change from zero is an infinite percentage.
+    pub fn percent_change(&self) -> Option<f64> {
+        let previous = self.previous.value();
+        (previous != 0.0).then(|| self.change() / previous * 100.0)
+    }
+
+    pub fn change_text(&self) -> String {
+        let change = self.change();
+        match self.current {
+            Metric::Money(_) => signed(change, ff("$", change.abs() as f32)),
+            Metric::Count(_) => signed(change, change.abs().to_string()),
+        }
+    }
+
+    pub fn percent_text(&self) -> String {
+        match self.percent_change() {
+            Some(percent) => signed(percent, format!("{:.1}%", percent.abs())),
+            None => "n/a".to_string(),
+        }
+    }
+}
+
+/// Zero of the same kind as `metric`, for a metric one of the periods doesn't have.
+fn zero_like(metric: Metric) -> Metric {
+    match metric {
+        Metric::Money(_) => Metric::Money(0.0),
+        Metric::Count(_) => Metric::Count(0),
+    }
+}
+
+/// Matches metrics up by name. Ones only the earlier period has, like an hour the museum used to open, go last.
+fn pair(current: Vec<(String, Metric)>, previous: Vec<(String, Metric)>) -> Vec<ComparedMetric> {
+    let mut previous: IndexMap<String, Metric> = previous.into_iter().collect();
+    let mut pairs = current.into_iter()
+        .map(|(name, current)| {
+            let previous = previous.shift_remove(&name).unwrap_or(zero_like(current));
+            ComparedMetric { name, current, previous }
+        })
+        .collect_vec();
+    pairs.extend(previous.into_iter().map(|(name, previous)| ComparedMetric { name, current: zero_like(previous), previous }));
+    pairs
+}
+
+#[derive(Debug, Clone)]
+pub struct Comparison {
+    pub current: Period,
+    pub previous: Period,
+    /// Every section of the summary report, in report order.
+    pub sections: Vec<(&'static str, Vec<ComparedMetric>)>,
+}
+
+impl Comparison {
+    pub fn new(current: &SummaryDicts, previous: &SummaryDicts, current_period: Period, previous_period: Period) -> Self {
+        Self {
+            current: current_period,
+            previous: previous_period,
+            sections: current.sections().into_iter()
+                .zip(previous.sections())
+                .map(|((heading, current), (_, previous))| (heading, pair(current, previous)))
+                .collect(),
+        }
+    }
+
+    pub fn between(database: &Database, operating_hours: &OperatingHours, current: Period, previous: Period) -> anyhow::Result<Self> {
+        Ok(Self::new(
+            &SummaryDicts::new(&database.ledger(&current)?, operating_hours, &current),
+            &SummaryDicts::new(&database.ledger(&previous)?, operating_hours, &previous),
+            current,
+            previous,
+        ))
+    }
+
+    /// Used to name exported files.
+    pub fn file_stem(&self) -> String {
+        format!("{}_vs_{}", self.current.file_stem(), self.previous.file_stem())
+    }
+
+    /// One `section,name,current,previous,change,percent_change` row per metric. Percentages are blank when there is
+    /// nothing to compare with.
+    pub fn write_csv(&self, writer: impl Write) -> anyhow::Result<()> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(["section", "name", "current", "previous", "change", "percent_change"])?;
+        for (heading, metrics) in &self.sections {
+            for metric in metrics {
+                writer.write_record([
+                    heading.to_string(),
+                    metric.name.clone(),
+                    metric.current.value().to_string(),
+                    metric.previous.value().to_string(),
+                    format!("{:.2}", metric.change()),
+                    metric.percent_change().map(|x| format!("{x:.1}")).unwrap_or_default(),
+                ])?;
+            }
+        }
+
+        Ok(writer.flush()?)
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        let period = |period: &Period| json!({
+            "period": period.describe(),
+            "from": period.from.to_string(),
+            "to": period.to.to_string(),
+        });
+        let sections: Map<String, serde_json::Value> = self.sections.iter()
+            .map(|(heading, metrics)| {
+                let metrics: Map<String, serde_json::Value> = metrics.iter()
+                    .map(|x| (x.name.clone(), json!({
+                        "current": summary_export::metric_json(x.current),
+                        "previous": summary_export::metric_json(x.previous),
+                        // Adding zero turns -0.0 into 0, as in the summary.
+                        "change": (x.change() * 100.0).round() / 100.0 + 0.0,
+                        "percent_change": x.percent_change().map(|x| (x * 10.0).round() / 10.0 + 0.0),
+                    })))
+                    .collect();
+                (heading.to_string(), metrics.into())
+            })
+            .collect();
+
+        json!({
+            "current": period(&self.current),
+            "previous": period(&self.previous),
+            "sections": sections,
+        })
+    }
+
+    pub fn render_to_html(&self, chain_hash: &str) -> anyhow::Result<String> {
+        const TEMPLATE_STR: &str = include_str!("../comparison.html");
+
+        let mut templates = Environment::new();
+        templates.add_template("comparison", TEMPLATE_STR)?;
+        #[derive(Serialize)]
+        struct Line {
+            name: String,
+            current: String,
+            previous: String,
+            change: String,
+            percent: String,
+        }
+        #[derive(Serialize)]
+        struct Context {
+            current: String,
+            previous: String,
+            sections: Vec<(&'static str, Vec<Line>)>,
+            chain_hash: String,
+        }
+
+        let context = Context {
+            current: self.current.describe(),
+            previous: self.previous.describe(),
+            sections: self.sections.iter()
+                .map(|(heading, metrics)| (*heading, metrics.iter().map(|x| Line {
+                    name: x.name.clone(),
+                    current: x.current.to_string(),
+                    previous: x.previous.to_string(),
+                    change: x.change_text(),
+                    percent: x.percent_text(),
+                }).collect()))
+                .collect(),
+            chain_hash: chain_hash.to_string(),
+        };
+
+        Ok(templates.get_template("comparison")?.render(&context)?)
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use super::*;
+
+    #[test]
+    fn metrics_are_matched_by_name() {
+        let compared = pair(
+            vec![("Adults".to_string(), Metric::Count(30)), ("10:00am - 11:00am".to_string(), Metric::Count(4))],
+            vec![("Adults".to_string(), Metric::Count(20)), ("9:00am - 10:00am".to_string(), Metric::Count(2))],
+        );
+
+        assert_eq!(compared.iter().map(|x| x.name.as_str()).collect_vec(), ["Adults", "10:00am - 11:00am", "9:00am - 10:00am"]);
+        assert_eq!((compared[0].change_text(), compared[0].percent_text()), ("+10".to_string(), "+50.0%".to_string()));
+        assert_eq!((compared[1].change_text(), compared[1].percent_text()), ("+4".to_string(), "n/a".to_string()));
+        assert_eq!((compared[2].change_text(), compared[2].percent_text()), ("-2".to_string(), "-100.0%".to_string()));
+    }
+
+    #[test]
+    fn every_summary_metric_is_compared() {
+        let hours = OperatingHours::default();
+        let period = Period::day(NaiveDate::from_ymd_opt(2026, 7, 4).unwrap());
+        let summary = SummaryDicts::empty(&hours, &period);
+        let comparison = Comparison::new(&summary, &summary, period, period.year_before());
+
+        assert_eq!(comparison.sections.len(), summary.sections().len());
+        assert_eq!(comparison.sections[0].1[1].change_text(), "$0");
+        assert!(comparison.render_to_html("").unwrap().contains("July 4, 2025"));
+    }
+}
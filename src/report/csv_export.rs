@@ -0,0 +1,100 @@
+use std::path::{Path, PathBuf};
+use itertools::Itertools;
+use sqlite::Value;
+use crate::database::Database;
+use crate::model::line_item::LineItem;
+use crate::report::period::Period;
+use crate::settings::operating_hours::OperatingHours;
+use crate::settings::timezone::Timezone;
+
+fn csv_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::Integer(i) => i.to_string(),
+        Value::Float(f) => f.to_string(),
+        Value::String(s) => s.clone(),
+        Value::Binary(b) => b.iter().map(|x| format!("{x:02x}")).join(""),
+    }
+}
+
+fn write_line_items(line_items: &[LineItem], timezone: Timezone, path: &Path) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_path(path)?;
+    writer.write_record(["date_time", "kind", "description", "quantity", "payment_method", "sales_tax", "total"])?;
+    for item in line_items {
+        writer.write_record([
+            timezone.rfc3339(item.date_time),
+            item.kind.to_string(),
+            item.description.clone(),
+            item.quantity.to_string(),
+            item.payment_method.map(|x| x.to_string()).unwrap_or_default(),
+            format!("{:.2}", item.sales_tax),
+            format!("{:.2}", item.total),
+        ])?;
+    }
+
+    Ok(writer.flush()?)
+}
+
+/// Writes one CSV per sales table, plus every line item in one file, into a folder named after the period inside
+/// `directory`. The tables are written as stored, so they can be imported again, and the line items are timed by the
+/// museum's clocks. Returns that folder.
+pub fn export(database: &Database, period: &Period, operating_hours: &OperatingHours, directory: impl AsRef<Path>) -> anyhow::Result<PathBuf> {
+    let timezone = operating_hours.timezone;
+    let directory = directory.as_ref().join(period.file_stem());
+    std::fs::create_dir_all(&directory)?;
+
+    for table in Database::sales_tables() {
+        let columns = table.column_names().collect_vec();
+        let rows = database.select_columns_between(table.table_name(), &columns, period)?;
+
+        let mut writer = csv::Writer::from_path(directory.join(format!("{}.csv", table.table_name())))?;
+        writer.write_record(&columns)?;
+        for row in rows {
+            writer.write_record(row.iter().map(csv_value))?;
+        }
+        writer.flush()?;
+    }
+
+    write_line_items(&LineItem::from_ledger(&database.ledger(period)?), timezone, &directory.join("line_items.csv"))?;
+    Ok(directory)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::admission::Admission;
+    use crate::model::admission::kind::Kind;
+    use crate::model::gift_shop_sale::GiftShopSale;
+    use crate::model::payment_method::PaymentMethod;
+    use super::*;
+
+    fn read(path: PathBuf) -> Vec<Vec<String>> {
+        let mut reader = csv::ReaderBuilder::new().has_headers(false).from_path(path).unwrap();
+        reader.records().map(|x| x.unwrap().iter().map(String::from).collect()).collect()
+    }
+
+    #[test]
+    fn exports_tables_and_line_items() {
+        let mut database = Database::in_memory();
+        database.record_sale("Sam", &[
+            Admission::new(Kind::ChildUnderSix, None, 1).into(),
+            GiftShopSale::new("Postcard".to_string(), 1.0, PaymentMethod::Cash, 2, 10.0).into(),
+        ]).unwrap();
+        let period = *database.business_day();
+        let directory = std::env::temp_dir().join(format!("pos_csv_export_{}", std::process::id()));
+        // Line items are timed by the museum's clocks, wherever the computer is.
+        let operating_hours = OperatingHours { timezone: Timezone(Some(chrono_tz::Pacific::Kiritimati)), ..Default::default() };
+        let exported = export(&database, &period, &operating_hours, &directory).unwrap();
+
+        let admissions = read(exported.join("admissions.csv"));
+        assert_eq!(admissions[0], ["kind", "payment_method", "quantity", "sale", "date_time", "hour"]);
+        assert_eq!(admissions[1][..4], ["Child (Under 6)", "", "1", "1"]);
+
+        let line_items = read(exported.join("line_items.csv"));
+        assert_eq!(line_items[0], ["date_time", "kind", "description", "quantity", "payment_method", "sales_tax", "total"]);
+        assert_eq!(line_items.len(), 3);
+        assert!(line_items[1..].iter().all(|x| x[0].ends_with("+14:00")), "{line_items:?}");
+        assert!(line_items.iter().any(|x| x[1..] == ["Gift Shop Sales", "Postcard", "2", "Cash", "0.20", "2.20"]));
+        assert!(line_items.iter().any(|x| x[1..] == ["Admission", "Child (Under 6) - Free", "1", "", "0.00", "0.00"]));
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
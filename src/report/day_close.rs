@@ -0,0 +1,233 @@
+//! End-of-day reports. An X-report shows the business day so far and can be printed as often as needed. A Z-report
+//! closes the day: it is numbered, stored in `day_closes`, and nothing recorded in that day can change after it.
+
+use chrono::{DateTime, Local, NaiveDate};
+use indexmap::IndexMap;
+use log::error;
+use minijinja::Environment;
+use serde::Serialize;
+use serde_json::json;
+use strum::Display;
+use crate::database::Database;
+use crate::database::from_sql::FromColumn;
+use crate::database::query::{Aggregate, Query};
+use crate::model::audit_entry::{AuditAction, AuditEntry, UNKNOWN_ACTOR};
+use crate::model::day_close::DayClose;
+use crate::report::summary_export;
+use crate::settings::operating_hours::OperatingHours;
+use crate::settings::timezone::Timezone;
+use crate::view::summary_dicts::{Metric, SummaryDicts};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum ReportKind {
+    /// Mid-day. Printing one changes nothing.
+    X,
+    /// End of day. Printing one closes the day.
+    Z,
+}
+
+/// The audit actions that take back or change a sale, in report order.
+const ADJUSTMENTS: [(&str, AuditAction); 4] = [
+    ("Cart Removals", AuditAction::CartRemoval),
+    ("Voids", AuditAction::Void),
+    ("Refunds", AuditAction::Refund),
+    ("Corrections", AuditAction::Correction),
+];
+
+/// An X- or Z-report for the current business day.
+#[derive(Debug, Clone)]
+pub struct DayReport {
+    pub kind: ReportKind,
+    /// Only Z-reports are numbered.
+    pub number: Option<i64>,
+    pub business_date: NaiveDate,
+    pub generated: DateTime<Local>,
+    /// The museum's clocks, which `generated` is shown by.
+    pub timezone: Timezone,
+    pub cashier: String,
+    pub summary: SummaryDicts,
+    /// How many of each kind of adjustment were made during the day.
+    pub adjustments: IndexMap<&'static str, Metric>,
+    /// The cash the day's sales say is in the drawer.
+    pub expected_cash: f32,
+    /// The cash actually in the drawer, if it was counted.
+    pub counted_cash: Option<f32>,
+    /// Carts still parked when the report was printed, which were never sold.
+    pub parked_carts: Vec<String>,
+    pub chain_hash: String,
+}
+
+impl DayReport {
+    fn new(database: &Database, operating_hours: &OperatingHours, kind: ReportKind, cashier: &str, counted_cash: Option<f32>) -> anyhow::Result<Self> {
+        let day = database.business_day();
+        let counts = database.total(&Query::<AuditEntry>::new().between(day.start(operating_hours), day.end(operating_hours)), Aggregate::Count, Some("action"))?
+            .into_iter()
+            .map(|(action, count)| Ok((AuditAction::from_column(action)?, count as u32)))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let summary = database.daily_summary().clone();
+        let cashier = cashier.trim();
+
+        Ok(Self {
+            kind,
+            number: None,
+            business_date: day.from,
+            generated: Local::now(),
+            timezone: operating_hours.timezone,
+            cashier: if cashier.is_empty() { UNKNOWN_ACTOR.to_string() } else { cashier.to_string() },
+            adjustments: ADJUSTMENTS.iter()
+                .map(|(key, action)| (*key, Metric::Count(counts.iter().filter(|(x, _)| x == action).map(|(_, count)| count).sum())))
+                .collect(),
+            expected_cash: summary.payments["Total Cash"].value() as f32,
+            counted_cash,
+            parked_carts: database.parked_carts()?.iter().map(|x| x.describe(operating_hours.timezone)).collect(),
+            summary,
+            chain_hash: database.chain_hash().unwrap_or_else(|err| {
+                error!("err reading journal chain hash: {}", err);
+                "UNAVAILABLE".to_string()
+            }),
+        })
+    }
+
+    /// A report of the business day so far, which leaves it open.
+    pub fn x_report(database: &mut Database, operating_hours: &OperatingHours, cashier: &str, counted_cash: Option<f32>) -> anyhow::Result<Self> {
+        database.roll_over_day();
+        Self::new(database, operating_hours, ReportKind::X, cashier, counted_cash)
+    }
+
+    /// Closes the current business day with a numbered Z-report, which is stored along with the close. Fails if the day
+    /// was already closed.
+    pub fn close_day(database: &mut Database, operating_hours: &OperatingHours, cashier: &str, counted_cash: f32) -> anyhow::Result<Self> {
+        database.roll_over_day();
+        let mut report = Self::new(database, operating_hours, ReportKind::Z, cashier, Some(counted_cash))?;
+        report.number = Some(database.next_close_number()?);
+        database.close_day(report.day_close())?;
+        Ok(report)
+    }
+
+    /// Positive when the drawer is over, negative when it is short, and `None` if it wasn't counted.
+    pub fn over_short(&self) -> Option<f32> {
+        self.counted_cash.map(|x| x - self.expected_cash)
+    }
+
+    /// "X-Report", or "Z-Report #12".
+    pub fn title(&self) -> String {
+        match self.number {
+            Some(number) => format!("{}-Report #{number}", self.kind),
+            None => format!("{}-Report", self.kind),
+        }
+    }
+
+    /// Used to name the written file. X-reports include the time, since there can be several a day.
+    pub fn file_name(&self) -> String {
+        match self.number {
+            Some(number) => format!("{}_z{number}.html", self.business_date),
+            None => format!("{}_x_{}.html", self.business_date, self.timezone.clock(self.generated).format("%H%M%S")),
+        }
+    }
+
+    /// The day's summary followed by the adjustments and the cash drawer.
+    pub fn sections(&self) -> Vec<(&'static str, Vec<(String, Metric)>)> {
+        let mut drawer = vec![("Expected Cash".to_string(), Metric::Money(self.expected_cash))];
+        if let (Some(counted), Some(over_short)) = (self.counted_cash, self.over_short()) {
+            drawer.push(("Counted Cash".to_string(), Metric::Money(counted)));
+            drawer.push(("Over/Short".to_string(), Metric::Money(over_short)));
+        }
+
+        let mut sections = self.summary.sections();
+        sections.push(("Voids, Refunds and Corrections", self.adjustments.iter().map(|(k, v)| (k.to_string(), *v)).collect()));
+        sections.push(("Cash Drawer", drawer));
+        sections
+    }
+
+    pub fn to_json(&self) -> serde_json::Value {
+        json!({
+            "report": self.kind.to_string(),
+            "number": self.number,
+            "business_date": self.business_date.to_string(),
+            "generated": self.timezone.rfc3339(self.generated),
+            "cashier": self.cashier,
+            "sections": summary_export::sections_json(self.sections()),
+            "parked_carts": self.parked_carts,
+            "chain_hash": self.chain_hash,
+        })
+    }
+
+    fn day_close(&self) -> DayClose {
+        DayClose {
+            number: self.number.unwrap_or_default(),
+            business_date: self.business_date,
+            closed_by: self.cashier.clone(),
+            expected_cash: self.expected_cash,
+            counted_cash: self.counted_cash.unwrap_or_default(),
+            report: self.to_json().to_string(),
+        }
+    }
+
+    pub fn render_to_html(&self) -> anyhow::Result<String> {
+        const TEMPLATE_STR: &str = include_str!("../day_close.html");
+
+        let mut templates = Environment::new();
+        templates.add_template("day_close", TEMPLATE_STR)?;
+        #[derive(Serialize)]
+        struct Context {
+            title: String,
+            note: &'static str,
+            business_date: String,
+            generated: String,
+            cashier: String,
+            sections: Vec<(&'static str, Vec<(String, Metric)>)>,
+            parked_carts: Vec<String>,
+            chain_hash: String,
+        }
+
+        let context = Context {
+            title: self.title(),
+            note: match self.kind {
+                ReportKind::X => "Mid-day report. The business day is still open.",
+                ReportKind::Z => "End of day. The business day is closed and can no longer be changed.",
+            },
+            business_date: self.business_date.format("%B %-d, %Y").to_string(),
+            generated: self.timezone.clock(self.generated).format("%Y-%m-%d %H:%M:%S").to_string(),
+            cashier: self.cashier.clone(),
+            sections: self.sections(),
+            parked_carts: self.parked_carts.clone(),
+            chain_hash: self.chain_hash.clone(),
+        };
+
+        Ok(templates.get_template("day_close")?.render(&context)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::report::period::Period;
+    use super::*;
+
+    #[test]
+    fn drawer_is_balanced_against_cash_sales() {
+        let hours = OperatingHours::default();
+        let date = NaiveDate::from_ymd_opt(2026, 7, 4).unwrap();
+        let report = DayReport {
+            kind: ReportKind::Z,
+            number: Some(12),
+            business_date: date,
+            generated: Local::now(),
+            timezone: hours.timezone,
+            cashier: "Sam".to_string(),
+            summary: SummaryDicts::empty(&hours, &Period::day(date)),
+            adjustments: ADJUSTMENTS.iter().map(|(key, _)| (*key, Metric::Count(0))).collect(),
+            expected_cash: 120.0,
+            counted_cash: Some(115.5),
+            parked_carts: vec!["Smith family: 1 item, $8.00, parked at 2:14pm".to_string()],
+            chain_hash: String::new(),
+        };
+
+        assert_eq!(report.title(), "Z-Report #12");
+        assert_eq!(report.file_name(), "2026-07-04_z12.html");
+        assert_eq!(report.over_short(), Some(-4.5));
+        assert_eq!(report.to_json()["sections"]["Cash Drawer"]["Over/Short"], json!(-4.5));
+        assert_eq!(report.day_close().over_short(), -4.5);
+        assert!(report.render_to_html().unwrap().contains("<p> Over/Short: $-4.50 </p>"));
+        assert!(report.render_to_html().unwrap().contains("<p> Smith family: 1 item, $8.00, parked at 2:14pm </p>"));
+    }
+}
@@ -0,0 +1,165 @@
+//! The sales behind one number in a report, for questions like "which admissions made up the 2pm hour?".
+
+use std::io::Write;
+use itertools::Itertools;
+use minijinja::Environment;
+use serde::Serialize;
+use crate::model::ledger::Ledger;
+use crate::model::line_item::LineItem;
+use crate::report::period::Period;
+use crate::settings::timezone::Timezone;
+use crate::view::adapters::ff;
+use crate::view::summary_dicts::{Metric, SummaryDicts};
+
+#[derive(Debug, Clone)]
+pub struct DrillDown {
+    pub period: Period,
+    pub section: &'static str,
+    pub name: String,
+    /// The number that was drilled into.
+    pub metric: Metric,
+    pub items: Vec<LineItem>,
+    /// The museum's clocks, which the items' times are shown by.
+    pub timezone: Timezone,
+}
+
+/// "10:00am - 11:00am" becomes "10_00am_11_00am", which is safe in any file name.
+fn slug(text: &str) -> String {
+    text.split(|x: char| !x.is_ascii_alphanumeric())
+        .filter(|x| !x.is_empty())
+        .map(|x| x.to_ascii_lowercase())
+        .join("_")
+}
+
+impl DrillDown {
+    pub fn new(summary: &SummaryDicts, ledger: &Ledger, period: Period, section: &'static str, name: String, metric: Metric) -> Self {
+        Self {
+            items: LineItem::from_ledger(&summary.rows_behind(ledger, section, &name)),
+            period,
+            section,
+            name,
+            metric,
+            timezone: summary.operating_hours().timezone,
+        }
+    }
+
+    /// "Attendance by Hour: 2:00pm - 3:00pm".
+    pub fn title(&self) -> String {
+        format!("{}: {}", self.section, self.name)
+    }
+
+    /// Used to name exported files.
+    pub fn file_stem(&self) -> String {
+        format!("{}_{}_{}", self.period.file_stem(), slug(self.section), slug(&self.name))
+    }
+
+    pub fn write_csv(&self, writer: impl Write) -> anyhow::Result<()> {
+        let mut writer = csv::Writer::from_writer(writer);
+        writer.write_record(["date_time", "kind", "description", "quantity", "payment_method", "sales_tax", "total"])?;
+        for item in &self.items {
+            writer.write_record([
+                self.timezone.rfc3339(item.date_time),
+                item.kind.to_string(),
+                item.description.clone(),
+                item.quantity.to_string(),
+                item.payment_method.map(|x| x.to_string()).unwrap_or_default(),
+                format!("{:.2}", item.sales_tax),
+                format!("{:.2}", item.total),
+            ])?;
+        }
+
+        Ok(writer.flush()?)
+    }
+
+    pub fn render_to_html(&self, chain_hash: &str) -> anyhow::Result<String> {
+        const TEMPLATE_STR: &str = include_str!("../drill_down.html");
+
+        let mut templates = Environment::new();
+        templates.add_template("drill_down", TEMPLATE_STR)?;
+        #[derive(Serialize)]
+        struct Line {
+            time: String,
+            kind: String,
+            description: String,
+            quantity: u16,
+            payment_method: String,
+            sales_tax: String,
+            total: String,
+        }
+        #[derive(Serialize)]
+        struct Context {
+            title: String,
+            period: String,
+            metric: Metric,
+            lines: Vec<Line>,
+            chain_hash: String,
+        }
+
+        let context = Context {
+            title: self.title(),
+            period: self.period.describe(),
+            metric: self.metric,
+            lines: self.items.iter()
+                .map(|x| Line {
+                    time: self.timezone.clock(x.date_time).format("%Y-%m-%d %H:%M").to_string(),
+                    kind: x.kind.to_string(),
+                    description: x.description.clone(),
+                    quantity: x.quantity,
+                    payment_method: x.payment_method.map(|x| x.to_string()).unwrap_or_default(),
+                    sales_tax: ff("$", x.sales_tax),
+                    total: ff("$", x.total),
+                })
+                .collect(),
+            chain_hash: chain_hash.to_string(),
+        };
+
+        Ok(templates.get_template("drill_down")?.render(&context)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+    use crate::model::admission::Admission;
+    use crate::model::admission::kind::Kind;
+    use crate::model::date_time_wrapper::WrapInDateTime;
+    use crate::model::donation::Donation;
+    use crate::model::payment_method::PaymentMethod;
+    use crate::settings::operating_hours::OperatingHours;
+    use super::*;
+
+    #[test]
+    fn tiles_drill_down_to_the_rows_they_add_up() {
+        let hours = OperatingHours::default();
+        let period = Period::day(NaiveDate::from_ymd_opt(2026, 7, 4).unwrap());
+        let ledger = Ledger {
+            admissions: vec![
+                Admission::new(Kind::Adult, Some(PaymentMethod::Cash), 2).wrapped_in_date_time(),
+                Admission::new(Kind::Senior, Some(PaymentMethod::CreditCard), 1).wrapped_in_date_time(),
+                Admission::new(Kind::ChildUnderSix, None, 1).wrapped_in_date_time(),
+            ],
+            donations: vec![Donation { payment_method: PaymentMethod::Cash, price: 20.0, sale: None }.wrapped_in_date_time()],
+            ..Default::default()
+        };
+        let summary = SummaryDicts::new(&ledger, &hours, &period);
+        let drill = |section, name: &str| DrillDown::new(&summary, &ledger, period, section, name.to_string(), Metric::Count(0));
+
+        assert_eq!(drill("Admission Breakdown", "Seniors").items.len(), 1);
+        assert_eq!(drill("Payments Breakdown", "Total Cash").items.len(), 2);
+        assert_eq!(drill("Payments Breakdown", "Cash - Donations").items.len(), 1);
+        assert_eq!(drill("Payments Breakdown", "Free - Admissions").items.len(), 1);
+        assert_eq!(drill("Summary", "Total Attendance").items.len(), 3);
+        assert_eq!(drill("Summary", "Total Revenue").items.len(), 4);
+        assert_eq!(drill("Attendance by Hour", "10:00am - 11:00am").file_stem(), "2026-07-04_attendance_by_hour_10_00am_11_00am");
+
+        // A count is the visitors in the rows behind it, so drilling in always shows what the tile says.
+        for (section, name) in [("Payments Breakdown", "Free - Admissions"), ("Admission Breakdown", "Adults"), ("Summary", "Total Attendance")] {
+            let tile = summary.sections().into_iter()
+                .find(|(heading, _)| *heading == section)
+                .and_then(|(_, metrics)| metrics.into_iter().find(|(key, _)| key == name))
+                .map(|(_, metric)| metric);
+            let visitors: u16 = drill(section, name).items.iter().map(|x| x.quantity).sum();
+            assert_eq!(tile, Some(Metric::Count(visitors as u32)), "{section}: {name}");
+        }
+    }
+}
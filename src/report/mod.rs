@@ -0,0 +1,9 @@
+pub mod period;
+pub mod csv_export;
+pub mod xlsx_export;
+pub mod summary_export;
+pub mod day_close;
+pub mod comparison;
+pub mod charts;
+pub mod drill_down;
+pub mod receipt;
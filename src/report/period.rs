@@ -0,0 +1,166 @@
+use chrono::{DateTime, Datelike, Days, Local, Months, NaiveDate};
+use strum::{Display, VariantArray};
+use crate::settings::operating_hours::OperatingHours;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, VariantArray, Display)]
+pub enum PeriodKind {
+    #[default]
+    Day,
+    #[strum(serialize = "Week to Date")]
+    Week,
+    #[strum(serialize = "Month to Date")]
+    Month,
+    #[strum(serialize = "Year to Date")]
+    Year,
+    Custom,
+}
+
+/// An inclusive range of business days to report on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    pub kind: PeriodKind,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+impl Period {
+    pub fn day(date: NaiveDate) -> Self {
+        Self { kind: PeriodKind::Day, from: date, to: date }
+    }
+
+    pub fn custom(from: NaiveDate, to: NaiveDate) -> Self {
+        Self { kind: PeriodKind::Custom, from: from.min(to), to: from.max(to) }
+    }
+
+    /// The preset `kind` running up to and including `today`. Weeks start on Monday.
+    pub fn to_date(kind: PeriodKind, today: NaiveDate) -> Self {
+        let from = match kind {
+            PeriodKind::Day | PeriodKind::Custom => today,
+            PeriodKind::Week => today - Days::new(today.weekday().num_days_from_monday() as u64),
+            PeriodKind::Month => today.with_day(1).unwrap_or(today),
+            PeriodKind::Year => today.with_ordinal(1).unwrap_or(today),
+        };
+
+        Self { kind, from, to: today }
+    }
+
+    /// The same days a year earlier, e.g. last July for this July. February 29th becomes the 28th.
+    pub fn year_before(&self) -> Self {
+        let back = |date: NaiveDate| date.checked_sub_months(Months::new(12)).unwrap_or(date);
+        Self { kind: self.kind, from: back(self.from), to: back(self.to) }
+    }
+
+    /// As many days again, ending the day before this period starts.
+    pub fn preceding(&self) -> Self {
+        let days = Days::new(self.dates().count() as u64);
+        Self { kind: PeriodKind::Custom, from: self.from - days, to: self.to - days }
+    }
+
+    pub fn dates(&self) -> impl Iterator<Item=NaiveDate> + use<> {
+        self.from.iter_days().take_while({
+            let to = self.to;
+            move |x| *x <= to
+        })
+    }
+
+    /// The first moment inside the period, when the museum's clocks reach the start of its first business day.
+    pub fn start(&self, hours: &OperatingHours) -> DateTime<Local> {
+        hours.timezone.moment(self.from.and_time(hours.day_start()))
+    }
+
+    /// The first moment after the period.
+    pub fn end(&self, hours: &OperatingHours) -> DateTime<Local> {
+        hours.timezone.moment((self.to + Days::new(1)).and_time(hours.day_start()))
+    }
+
+    pub fn contains(&self, at: DateTime<Local>, hours: &OperatingHours) -> bool {
+        self.start(hours) <= at && at < self.end(hours)
+    }
+
+    /// A heading such as "Daily" or "Month to Date".
+    pub fn title(&self) -> String {
+        match self.kind {
+            PeriodKind::Day => "Daily".to_string(),
+            kind => kind.to_string(),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        if self.from == self.to {
+            self.from.format("%B %-d, %Y").to_string()
+        } else {
+            format!("{} - {}", self.from.format("%B %-d, %Y"), self.to.format("%B %-d, %Y"))
+        }
+    }
+
+    /// Used to name exported files.
+    pub fn file_stem(&self) -> String {
+        if self.from == self.to {
+            self.from.format("%Y-%m-%d").to_string()
+        } else {
+            format!("{}_to_{}", self.from.format("%Y-%m-%d"), self.to.format("%Y-%m-%d"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn presets_run_to_today() {
+        // 2026-10-15 is a Thursday.
+        let today = date("2026-10-15");
+        assert_eq!(Period::to_date(PeriodKind::Week, today).from, date("2026-10-12"));
+        assert_eq!(Period::to_date(PeriodKind::Month, today).from, date("2026-10-01"));
+        assert_eq!(Period::to_date(PeriodKind::Year, today).from, date("2026-01-01"));
+        assert_eq!(Period::to_date(PeriodKind::Year, today).dates().count(), 288);
+    }
+
+    #[test]
+    fn boundaries_follow_the_day_start() {
+        let period = Period::day(date("2026-07-04"));
+        let hours = OperatingHours { day_start_hour: 4, ..Default::default() };
+        let local = |x: chrono::NaiveDateTime| hours.timezone.moment(x);
+        assert_eq!(period.start(&hours).naive_local(), date("2026-07-04").and_hms_opt(4, 0, 0).unwrap());
+        assert_eq!(period.end(&hours).naive_local(), date("2026-07-05").and_hms_opt(4, 0, 0).unwrap());
+        assert!(period.contains(local(date("2026-07-05").and_hms_opt(1, 30, 0).unwrap()), &hours));
+        assert!(!period.contains(local(date("2026-07-04").and_hms_opt(1, 30, 0).unwrap()), &hours));
+    }
+
+    #[test]
+    fn boundaries_follow_the_museum_clocks() {
+        use chrono::{TimeZone, Utc};
+        use crate::settings::timezone::Timezone;
+
+        // Midnight in Denver is 6am UTC in summer, whatever timezone the computer is in.
+        let hours = OperatingHours { timezone: Timezone(Some(chrono_tz::America::Denver)), ..Default::default() };
+        let period = Period::day(date("2026-07-04"));
+        assert_eq!(period.start(&hours), Utc.with_ymd_and_hms(2026, 7, 4, 6, 0, 0).unwrap());
+        assert_eq!(period.end(&hours), Utc.with_ymd_and_hms(2026, 7, 5, 6, 0, 0).unwrap());
+        let late_evening = Utc.with_ymd_and_hms(2026, 7, 5, 5, 30, 0).unwrap().with_timezone(&Local);
+        assert!(period.contains(late_evening, &hours));
+        assert_eq!(hours.business_date(hours.timezone.clock(late_evening)), date("2026-07-04"));
+    }
+
+    #[test]
+    fn periods_to_compare_against() {
+        let ytd = Period::to_date(PeriodKind::Year, date("2028-02-29"));
+        assert_eq!(ytd.year_before(), Period { kind: PeriodKind::Year, from: date("2027-01-01"), to: date("2027-02-28") });
+
+        let week = Period::custom(date("2026-07-06"), date("2026-07-12"));
+        assert_eq!(week.preceding(), Period::custom(date("2026-06-29"), date("2026-07-05")));
+    }
+
+    #[test]
+    fn custom_ranges_are_ordered_and_inclusive() {
+        let period = Period::custom(date("2026-07-31"), date("2026-07-01"));
+        assert_eq!(period.from, date("2026-07-01"));
+        assert_eq!(period.dates().count(), 31);
+        assert_eq!(period.file_stem(), "2026-07-01_to_2026-07-31");
+    }
+}
@@ -0,0 +1,61 @@
+//! Receipts for sales that were already saved, printed again from the history screen.
+
+use chrono::Local;
+use minijinja::Environment;
+use serde::Serialize;
+use crate::model::sale::SaleLines;
+use crate::settings::timezone::Timezone;
+use crate::view::adapters::ff;
+
+/// Used to name the written file.
+pub fn file_name(number: i64) -> String {
+    format!("sale_{number}_receipt.html")
+}
+
+/// A copy of the receipt for a numbered sale, marked as a reprint along with anything refunded since. Times are shown
+/// by the museum's clocks.
+pub fn render_to_html(sale: &SaleLines, timezone: Timezone) -> anyhow::Result<String> {
+    const TEMPLATE_STR: &str = include_str!("../receipt.html");
+
+    let Some(header) = &sale.sale else {
+        anyhow::bail!("only numbered sales have receipts");
+    };
+    let mut templates = Environment::new();
+    templates.add_template("receipt", TEMPLATE_STR)?;
+    #[derive(Serialize)]
+    struct Line {
+        description: String,
+        quantity: u16,
+        payment_method: String,
+        total: String,
+    }
+    #[derive(Serialize)]
+    struct Context {
+        number: i64,
+        sold: String,
+        cashier: String,
+        status: String,
+        lines: Vec<Line>,
+        total: String,
+        reprinted: String,
+    }
+
+    let context = Context {
+        number: header.number,
+        sold: timezone.clock(header.date_time()).format("%Y-%m-%d %H:%M").to_string(),
+        cashier: header.cashier.clone(),
+        status: sale.status(),
+        lines: sale.lines.iter()
+            .map(|x| Line {
+                description: x.description.clone(),
+                quantity: x.quantity,
+                payment_method: x.payment_method.map(|x| x.to_string()).unwrap_or("Free".to_string()),
+                total: ff("$", x.total),
+            })
+            .collect(),
+        total: ff("$", sale.total()),
+        reprinted: timezone.clock(Local::now()).format("%Y-%m-%d %H:%M").to_string(),
+    };
+
+    Ok(templates.get_template("receipt")?.render(&context)?)
+}
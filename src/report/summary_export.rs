@@ -0,0 +1,47 @@
+use std::io::Write;
+use serde_json::{json, Map};
+use crate::report::period::Period;
+use crate::view::summary_dicts::{Metric, SummaryDicts};
+
+pub fn metric_json(metric: Metric) -> serde_json::Value {
+    match metric {
+        // Adding zero turns the -0.0 that sums of nothing come out as back into 0.
+        Metric::Money(m) => json!((m as f64 * 100.0).round() / 100.0 + 0.0),
+        Metric::Count(c) => json!(c),
+    }
+}
+
+/// The summary as `section,name,value` rows. Values are plain numbers so a spreadsheet can add them up.
+pub fn write_csv(summary: &SummaryDicts, writer: impl Write) -> anyhow::Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    writer.write_record(["section", "name", "value"])?;
+    for (heading, values) in summary.sections() {
+        for (name, metric) in values {
+            writer.write_record([heading.to_string(), name, metric_json(metric).to_string()])?;
+        }
+    }
+
+    Ok(writer.flush()?)
+}
+
+/// Report sections as one JSON object, with a key per section in report order.
+pub fn sections_json(sections: Vec<(&'static str, Vec<(String, Metric)>)>) -> serde_json::Value {
+    let sections: Map<String, serde_json::Value> = sections.into_iter()
+        .map(|(heading, values)| {
+            let values: Map<String, serde_json::Value> = values.into_iter().map(|(name, metric)| (name, metric_json(metric))).collect();
+            (heading.to_string(), values.into())
+        })
+        .collect();
+    sections.into()
+}
+
+/// The summary as one JSON object, with a key per section in report order.
+pub fn to_json(summary: &SummaryDicts, period: &Period, chain_hash: &str) -> serde_json::Value {
+    json!({
+        "period": period.describe(),
+        "from": period.from.to_string(),
+        "to": period.to.to_string(),
+        "sections": sections_json(summary.sections()),
+        "chain_hash": chain_hash,
+    })
+}
@@ -0,0 +1,166 @@
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Local};
+use itertools::Itertools;
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+use sqlite::Value;
+use crate::database::Database;
+use crate::model::line_item::LineItem;
+use crate::report::period::Period;
+use crate::settings::operating_hours::OperatingHours;
+use crate::settings::timezone::Timezone;
+use crate::view::summary_dicts::{Metric, SummaryDicts};
+
+/// Raw columns holding amounts of money, which get the currency format.
+const MONEY_COLUMNS: [&str; 2] = ["price", "total_cost"];
+const DATE_TIME_COLUMN: &str = "date_time";
+
+struct Formats {
+    title: Format,
+    header: Format,
+    money: Format,
+    count: Format,
+    date_time: Format,
+}
+
+impl Default for Formats {
+    fn default() -> Self {
+        Self {
+            title: Format::new().set_bold().set_font_size(16),
+            header: Format::new().set_bold(),
+            money: Format::new().set_num_format("$#,##0.00"),
+            count: Format::new().set_num_format("0"),
+            date_time: Format::new().set_num_format("yyyy-mm-dd hh:mm"),
+        }
+    }
+}
+
+/// Money is kept as f32, so round away the noise before it reaches the formula bar.
+fn cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
+
+/// "gift_shop_sales" becomes "Gift Shop Sales".
+fn sheet_name(table_name: &str) -> String {
+    table_name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|x| x.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        })
+        .join(" ")
+}
+
+fn write_headers(sheet: &mut Worksheet, headers: &[&str], formats: &Formats) -> Result<(), XlsxError> {
+    for (column, header) in headers.iter().enumerate() {
+        sheet.write_string_with_format(0, column as u16, *header, &formats.header)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    Ok(())
+}
+
+fn write_summary(sheet: &mut Worksheet, summary: &SummaryDicts, period: &Period, formats: &Formats) -> Result<(), XlsxError> {
+    sheet.set_name("Summary")?;
+    sheet.write_string_with_format(0, 0, format!("{} Report", period.title()), &formats.title)?;
+    sheet.write_string(1, 0, period.describe())?;
+    sheet.set_column_width(0, 32)?;
+    sheet.set_column_width(1, 16)?;
+
+    let mut row = 3;
+    for (heading, values) in summary.sections() {
+        sheet.write_string_with_format(row, 0, format!("{} {}", period.title(), heading), &formats.header)?;
+        row += 1;
+        for (name, metric) in values {
+            sheet.write_string(row, 0, name)?;
+            match metric {
+                Metric::Money(_) => sheet.write_number_with_format(row, 1, cents(metric.value()), &formats.money)?,
+                Metric::Count(_) => sheet.write_number_with_format(row, 1, metric.value(), &formats.count)?,
+            };
+            row += 1;
+        }
+        row += 1;
+    }
+
+    Ok(())
+}
+
+fn write_table(sheet: &mut Worksheet, table_name: &str, columns: &[&str], rows: Vec<Vec<Value>>, timezone: Timezone, formats: &Formats) -> anyhow::Result<()> {
+    sheet.set_name(sheet_name(table_name))?;
+    write_headers(sheet, columns, formats)?;
+
+    for (row, values) in rows.into_iter().enumerate() {
+        let row = row as u32 + 1;
+        for (column, (name, value)) in columns.iter().zip(values).enumerate() {
+            let column = column as u16;
+            match value {
+                Value::String(s) if *name == DATE_TIME_COLUMN => {
+                    let date_time = DateTime::parse_from_rfc3339(&s)?.with_timezone(&Local);
+                    sheet.write_datetime_with_format(row, column, timezone.clock(date_time), &formats.date_time)?;
+                }
+                Value::String(s) => { sheet.write_string(row, column, s)?; }
+                Value::Integer(i) => { sheet.write_number(row, column, i as f64)?; }
+                Value::Float(f) if MONEY_COLUMNS.contains(name) => { sheet.write_number_with_format(row, column, f, &formats.money)?; }
+                Value::Float(f) => { sheet.write_number(row, column, f)?; }
+                Value::Binary(_) | Value::Null => {}
+            }
+        }
+    }
+
+    sheet.set_column_width(columns.iter().position(|x| *x == DATE_TIME_COLUMN).unwrap_or(0) as u16, 18)?;
+    Ok(())
+}
+
+fn write_line_items(sheet: &mut Worksheet, line_items: &[LineItem], timezone: Timezone, formats: &Formats) -> Result<(), XlsxError> {
+    sheet.set_name("Line Items")?;
+    write_headers(sheet, &["Date", "Kind", "Description", "Quantity", "Payment Method", "Sales Tax", "Total"], formats)?;
+
+    for (row, item) in line_items.iter().enumerate() {
+        let row = row as u32 + 1;
+        sheet.write_datetime_with_format(row, 0, timezone.clock(item.date_time), &formats.date_time)?;
+        sheet.write_string(row, 1, item.kind.to_string())?;
+        sheet.write_string(row, 2, &item.description)?;
+        sheet.write_number(row, 3, item.quantity)?;
+        sheet.write_string(row, 4, item.payment_method.map(|x| x.to_string()).unwrap_or_default())?;
+        sheet.write_number_with_format(row, 5, cents(item.sales_tax as f64), &formats.money)?;
+        sheet.write_number_with_format(row, 6, cents(item.total as f64), &formats.money)?;
+    }
+
+    sheet.set_column_width(0, 18)?;
+    sheet.set_column_width(2, 32)?;
+    Ok(())
+}
+
+/// Writes a workbook for `period` into `directory`: a summary sheet laid out like the HTML report, every line item, then
+/// one raw sheet per sales table. Returns the workbook's path.
+pub fn export(database: &Database, period: &Period, operating_hours: &OperatingHours, directory: impl AsRef<Path>) -> anyhow::Result<PathBuf> {
+    let formats = Formats::default();
+    let ledger = database.ledger(period)?;
+    let mut workbook = Workbook::new();
+
+    write_summary(workbook.add_worksheet(), &SummaryDicts::new(&ledger, operating_hours, period), period, &formats)?;
+    write_line_items(workbook.add_worksheet(), &LineItem::from_ledger(&ledger), operating_hours.timezone, &formats)?;
+    for table in Database::sales_tables() {
+        let columns = table.column_names().collect_vec();
+        let rows = database.select_columns_between(table.table_name(), &columns, period)?;
+        write_table(workbook.add_worksheet(), table.table_name(), &columns, rows, operating_hours.timezone, &formats)?;
+    }
+
+    std::fs::create_dir_all(directory.as_ref())?;
+    let path = directory.as_ref().join(format!("{}_report.xlsx", period.file_stem()));
+    workbook.save(&path)?;
+    Ok(path)
+}
+
+/// Writes a workbook holding only the summary sheet to `path`.
+pub fn export_summary(summary: &SummaryDicts, period: &Period, path: impl AsRef<Path>) -> anyhow::Result<()> {
+    let mut workbook = Workbook::new();
+    write_summary(workbook.add_worksheet(), summary, period, &Formats::default())?;
+    workbook.save(path)?;
+    Ok(())
+}
+
+/// Writes a workbook holding only `line_items`, timed by `timezone`'s clocks, to `path`.
+pub fn export_line_items(line_items: &[LineItem], timezone: Timezone, path: impl AsRef<Path>) -> anyhow::Result<()> {
+    let mut workbook = Workbook::new();
+    write_line_items(workbook.add_worksheet(), line_items, timezone, &Formats::default())?;
+    workbook.save(path)?;
+    Ok(())
+}
@@ -5,6 +5,7 @@ use crate::view::donation::Donation;
 use crate::view::gift_shop_sale::GiftShopSale;
 use crate::view::membership::Membership;
 use crate::RULE_HEIGHT;
+#[derive(Default)]
 pub struct SaleScreen {
     admission: Admission,
     donation: Donation,
@@ -38,26 +39,26 @@ impl SaleScreen {
             Message::AddGiftShopSale => self.gift_shop_sale = Default::default(),
         }
     }
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         iced::widget::column![
             row![
                 iced::widget::column![
-                    self.admission.view().map(|x| Message::Admission(x)),
-                    button("Add Admission").on_press_maybe(self.admission.is_valid().then(|| Message::AddAdmission)),
+                    self.admission.view().map(Message::Admission),
+                    button("Add Admission").on_press_maybe(self.admission.is_valid().then_some(Message::AddAdmission)),
                 ].spacing(RULE_HEIGHT).padding(RULE_HEIGHT),
                 iced::widget::column![
-                    self.donation.view().map(|x| Message::Donation(x)),
-                    button("Add Donation").on_press_maybe(self.donation.is_valid().then(|| Message::AddDonation)),
+                    self.donation.view().map(Message::Donation),
+                    button("Add Donation").on_press_maybe(self.donation.is_valid().then_some(Message::AddDonation)),
                 ].spacing(RULE_HEIGHT).padding(RULE_HEIGHT),
             ].spacing(RULE_HEIGHT).padding(RULE_HEIGHT),
             row![
                 iced::widget::column![
-                    self.membership.view().map(|x| Message::Membership(x)),
-                    button("Add Membership").on_press_maybe(self.membership.is_valid().then(|| Message::AddMembership)),
+                    self.membership.view().map(Message::Membership),
+                    button("Add Membership").on_press_maybe(self.membership.is_valid().then_some(Message::AddMembership)),
                 ].spacing(RULE_HEIGHT).padding(RULE_HEIGHT),
                 iced::widget::column![
-                    self.gift_shop_sale.view().map(|x| Message::GiftShopSale(x)),
-                    button("Add Sale").on_press_maybe(self.gift_shop_sale.is_valid().then(|| Message::AddGiftShopSale)),
+                    self.gift_shop_sale.view().map(Message::GiftShopSale),
+                    button("Add Sale").on_press_maybe(self.gift_shop_sale.is_valid().then_some(Message::AddGiftShopSale)),
                 ].spacing(RULE_HEIGHT).padding(RULE_HEIGHT),
             ].spacing(RULE_HEIGHT).padding(RULE_HEIGHT),
         ].spacing(RULE_HEIGHT).into()
@@ -77,14 +78,3 @@ impl SaleScreen {
     }
 }
 
-impl Default for SaleScreen {
-    fn default() -> Self {
-
-        Self {
-            admission: Default::default(),
-            donation: Default::default(),
-            membership: Default::default(),
-            gift_shop_sale: Default::default(),
-        }
-    }
-}
\ No newline at end of file
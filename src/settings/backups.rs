@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+
+/// When backups are taken and how many are kept. The newest backup of each of the last `keep_daily` days, `keep_weekly`
+/// weeks and `keep_monthly` months is kept, and so is the newest of all; older ones are deleted after each new backup.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    /// How often to back up while the program is open. 0 turns the timer off.
+    pub interval_minutes: u32,
+    pub keep_daily: usize,
+    pub keep_weekly: usize,
+    pub keep_monthly: usize,
+    /// Somewhere else to keep a copy of every backup, e.g. a USB drive.
+    pub second_destination: Option<PathBuf>,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            interval_minutes: 60,
+            keep_daily: 7,
+            keep_weekly: 4,
+            keep_monthly: 12,
+            second_destination: None,
+        }
+    }
+}
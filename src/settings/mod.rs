@@ -0,0 +1,98 @@
+pub mod operating_hours;
+pub mod paths;
+pub mod backups;
+pub mod permissions;
+pub mod timezone;
+
+use std::path::PathBuf;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use crate::settings::backups::BackupSettings;
+use crate::settings::operating_hours::OperatingHours;
+use crate::settings::paths::{DataDir, Paths};
+use crate::settings::permissions::Permissions;
+
+/// Everything the museum can tune without a new build. Stored as TOML so it can be edited by hand.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub operating_hours: OperatingHours,
+    pub paths: Paths,
+    pub backups: BackupSettings,
+    pub permissions: Permissions,
+    /// Where this file was loaded from, which every relative path in it is relative to.
+    #[serde(skip)]
+    pub data_dir: DataDir,
+}
+
+impl Settings {
+    const FILENAME: &'static str = "settings.toml";
+
+    /// Reads the settings file in `data_dir`, writing out the defaults if there isn't one yet. A file that can't be read
+    /// or parsed is an error rather than a fall back to the defaults, which could point the till at another database.
+    pub fn load(data_dir: DataDir) -> anyhow::Result<Self> {
+        let defaults = Self { data_dir, ..Default::default() };
+        let filepath = defaults.filepath();
+        let text = match std::fs::read_to_string(&filepath) {
+            Ok(text) => text,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                match defaults.save() {
+                    Ok(_) => info!("Wrote default settings to {}", filepath.display()),
+                    Err(err) => error!("err writing default settings: {}", err),
+                }
+                return Ok(defaults);
+            }
+            Err(err) => anyhow::bail!("unable to read {}: {err}", filepath.display()),
+        };
+
+        match toml::from_str::<Self>(&text) {
+            Ok(settings) => Ok(Self { data_dir: defaults.data_dir, ..settings }),
+            Err(err) => anyhow::bail!("{} has a mistake in it: {err}", filepath.display()),
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        std::fs::create_dir_all(&self.data_dir.path)?;
+        std::fs::write(self.filepath(), toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn filepath(&self) -> PathBuf {
+        self.data_dir.join(Self::FILENAME)
+    }
+
+    pub fn database_path(&self) -> PathBuf {
+        self.data_dir.join(&self.paths.database)
+    }
+
+    pub fn report_directory(&self) -> PathBuf {
+        self.data_dir.join(&self.paths.reports)
+    }
+
+    pub fn backup_directory(&self) -> PathBuf {
+        self.data_dir.join(&self.paths.backups)
+    }
+
+    pub fn export_directory(&self) -> PathBuf {
+        self.data_dir.join(&self.paths.exports)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_typo_is_an_error_not_the_defaults() {
+        let directory = std::env::temp_dir().join(format!("pos_settings_typo_{}", std::process::id()));
+        let data_dir = || DataDir::resolve(Some(directory.clone()));
+        let written = Settings::load(data_dir()).unwrap();
+        assert_eq!(written.paths, Paths::default());
+
+        let typo = "[paths]\ndatabase = \"museum.db\"\n[backups\n";
+        std::fs::write(written.filepath(), typo).unwrap();
+        assert!(Settings::load(data_dir()).is_err());
+        assert_eq!(std::fs::read_to_string(written.filepath()).unwrap(), typo);
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,288 @@
+use std::fmt::Display;
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use serde::{Deserialize, Serialize};
+use crate::settings::timezone::Timezone;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayHours {
+    pub opens: NaiveTime,
+    pub closes: NaiveTime,
+}
+
+impl DayHours {
+    pub fn new(opens: NaiveTime, closes: NaiveTime) -> Self {
+        Self { opens, closes }
+    }
+}
+
+/// Opening hours for each day of the week. A missing day means the museum is closed.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WeeklyHours {
+    pub monday: Option<DayHours>,
+    pub tuesday: Option<DayHours>,
+    pub wednesday: Option<DayHours>,
+    pub thursday: Option<DayHours>,
+    pub friday: Option<DayHours>,
+    pub saturday: Option<DayHours>,
+    pub sunday: Option<DayHours>,
+}
+
+impl WeeklyHours {
+    pub fn every_day(hours: DayHours) -> Self {
+        Self {
+            monday: Some(hours),
+            tuesday: Some(hours),
+            wednesday: Some(hours),
+            thursday: Some(hours),
+            friday: Some(hours),
+            saturday: Some(hours),
+            sunday: Some(hours),
+        }
+    }
+
+    pub fn on(&self, weekday: Weekday) -> Option<DayHours> {
+        match weekday {
+            Weekday::Mon => self.monday,
+            Weekday::Tue => self.tuesday,
+            Weekday::Wed => self.wednesday,
+            Weekday::Thu => self.thursday,
+            Weekday::Fri => self.friday,
+            Weekday::Sat => self.saturday,
+            Weekday::Sun => self.sunday,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct MonthDay {
+    pub month: u32,
+    pub day: u32,
+}
+
+impl MonthDay {
+    fn of(date: NaiveDate) -> Self {
+        Self { month: date.month(), day: date.day() }
+    }
+}
+
+/// A stretch of the year with its own hours. Seasons may wrap around the new year.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Season {
+    pub name: String,
+    pub starts: MonthDay,
+    pub ends: MonthDay,
+    pub hours: WeeklyHours,
+}
+
+impl Season {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        let date = MonthDay::of(date);
+        if self.starts <= self.ends {
+            self.starts <= date && date <= self.ends
+        } else {
+            date >= self.starts || date <= self.ends
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OperatingHours {
+    pub bucket_minutes: u32,
+    /// Hours used on any date no season covers.
+    pub regular: WeeklyHours,
+    /// Checked in order; the first season containing a date decides its hours.
+    pub seasons: Vec<Season>,
+    /// The hour (0-23) at which one business day ends and the next begins. Anything earlier counts towards the day
+    /// before, so an evening event that runs past midnight stays on one day's report.
+    pub day_start_hour: u32,
+    /// Which clocks the hours above are on.
+    pub timezone: Timezone,
+}
+
+impl Default for OperatingHours {
+    fn default() -> Self {
+        Self {
+            bucket_minutes: 60,
+            regular: WeeklyHours::every_day(DayHours::new(
+                NaiveTime::from_hms_opt(10, 0, 0).unwrap(),
+                NaiveTime::from_hms_opt(16, 0, 0).unwrap(),
+            )),
+            seasons: vec![],
+            day_start_hour: 0,
+            timezone: Timezone::default(),
+        }
+    }
+}
+
+/// Where a moment falls relative to the opening hours of its day. Ordered the way a report should list them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum HourBucket {
+    BeforeOpening,
+    Open { starts: NaiveTime, ends: NaiveTime },
+    AfterClosing,
+    ClosedDay,
+}
+
+impl Display for HourBucket {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HourBucket::BeforeOpening => write!(f, "Before Opening"),
+            HourBucket::Open { starts, ends } => write!(f, "{} - {}", starts.format("%-I:%M%P"), ends.format("%-I:%M%P")),
+            HourBucket::AfterClosing => write!(f, "After Closing"),
+            HourBucket::ClosedDay => write!(f, "Closed Day"),
+        }
+    }
+}
+
+impl OperatingHours {
+    fn bucket_length(&self) -> Duration {
+        Duration::minutes(self.bucket_minutes.max(1) as i64)
+    }
+
+    /// When every business day begins.
+    pub fn day_start(&self) -> NaiveTime {
+        NaiveTime::from_hms_opt(self.day_start_hour.min(23), 0, 0).unwrap_or_default()
+    }
+
+    /// The business day `at`, as read off the museum's clocks, belongs to.
+    pub fn business_date(&self, at: NaiveDateTime) -> NaiveDate {
+        (at - Duration::hours(self.day_start_hour.min(23) as i64)).date()
+    }
+
+    /// The business day it is right now.
+    pub fn today(&self) -> NaiveDate {
+        self.business_date(self.timezone.clock(Local::now()))
+    }
+
+    pub fn hours_on(&self, date: NaiveDate) -> Option<DayHours> {
+        self.seasons.iter()
+            .find(|x| x.contains(date))
+            .map(|x| &x.hours)
+            .unwrap_or(&self.regular)
+            .on(date.weekday())
+    }
+
+    /// Every open bucket on `date`, in order. Empty on closed days.
+    pub fn buckets_on(&self, date: NaiveDate) -> Vec<HourBucket> {
+        let Some(hours) = self.hours_on(date) else {
+            return vec![];
+        };
+
+        let mut buckets = vec![];
+        let mut starts = hours.opens;
+        while starts < hours.closes {
+            let ends = (starts + self.bucket_length()).min(hours.closes);
+            // Adding wraps past midnight, so stop rather than loop forever.
+            let ends = if ends <= starts { hours.closes } else { ends };
+            buckets.push(HourBucket::Open { starts, ends });
+            starts = ends;
+        }
+        buckets
+    }
+
+    /// Where `at`, as read off the museum's clocks, falls in its business day. Past midnight but before
+    /// `day_start_hour` is after closing on the day before.
+    pub fn bucket(&self, at: NaiveDateTime) -> HourBucket {
+        let date = self.business_date(at);
+        let Some(hours) = self.hours_on(date) else {
+            return HourBucket::ClosedDay;
+        };
+
+        let time = at.time();
+        if at.date() > date || time >= hours.closes {
+            return HourBucket::AfterClosing;
+        }
+        if time < hours.opens {
+            return HourBucket::BeforeOpening;
+        }
+
+        self.buckets_on(date)
+            .into_iter()
+            .find(|x| matches!(x, HourBucket::Open { starts, ends } if *starts <= time && time < *ends))
+            .unwrap_or(HourBucket::AfterClosing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::new(date.parse().unwrap(), time.parse().unwrap())
+    }
+
+    fn time(time: &str) -> NaiveTime {
+        time.parse().unwrap()
+    }
+
+    fn summer() -> OperatingHours {
+        OperatingHours {
+            bucket_minutes: 30,
+            seasons: vec![Season {
+                name: "Summer".to_string(),
+                starts: MonthDay { month: 5, day: 25 },
+                ends: MonthDay { month: 9, day: 7 },
+                hours: WeeklyHours {
+                    saturday: Some(DayHours::new(time("09:00:00"), time("17:30:00"))),
+                    ..Default::default()
+                },
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn nine_am_and_five_pm_are_not_misfiled() {
+        let hours = OperatingHours::default();
+        assert_eq!(hours.bucket(at("2025-10-01", "10:15:00")), HourBucket::Open { starts: time("10:00:00"), ends: time("11:00:00") });
+        assert_eq!(hours.bucket(at("2025-10-01", "09:05:00")), HourBucket::BeforeOpening);
+        assert_eq!(hours.bucket(at("2025-10-01", "17:00:00")), HourBucket::AfterClosing);
+    }
+
+    #[test]
+    fn seasons_override_regular_hours() {
+        let hours = summer();
+        // 2025-07-05 is a Saturday inside the season, 2025-07-07 a Monday on which the season is closed.
+        assert_eq!(hours.bucket(at("2025-07-05", "09:10:00")), HourBucket::Open { starts: time("09:00:00"), ends: time("09:30:00") });
+        assert_eq!(hours.bucket(at("2025-07-07", "11:00:00")), HourBucket::ClosedDay);
+        assert_eq!(hours.buckets_on("2025-07-05".parse().unwrap()).len(), 17);
+        assert_eq!(hours.buckets_on("2025-10-04".parse().unwrap()).len(), 12);
+    }
+
+    #[test]
+    fn business_days_can_start_after_midnight() {
+        let hours = OperatingHours { day_start_hour: 4, ..Default::default() };
+        assert_eq!(hours.business_date(at("2025-10-02", "03:59:00")), "2025-10-01".parse().unwrap());
+        assert_eq!(hours.business_date(at("2025-10-02", "04:00:00")), "2025-10-02".parse().unwrap());
+        assert_eq!(OperatingHours::default().business_date(at("2025-10-02", "00:00:00")), "2025-10-02".parse().unwrap());
+    }
+
+    #[test]
+    fn sales_after_midnight_fall_in_the_business_day_before() {
+        let hours = OperatingHours { day_start_hour: 4, ..Default::default() };
+        // 2025-10-01 is an open Wednesday.
+        assert_eq!(hours.bucket(at("2025-10-02", "01:30:00")), HourBucket::AfterClosing);
+        assert_eq!(hours.bucket(at("2025-10-02", "04:30:00")), HourBucket::BeforeOpening);
+        assert_eq!(hours.bucket(at("2025-10-01", "23:30:00")), HourBucket::AfterClosing);
+
+        // In summer the museum is only open on Saturdays, so early Saturday morning is still a closed Friday.
+        let summer = OperatingHours { day_start_hour: 4, ..summer() };
+        assert_eq!(summer.bucket(at("2025-07-05", "02:00:00")), HourBucket::ClosedDay);
+        assert_eq!(summer.bucket(at("2025-07-06", "02:00:00")), HourBucket::AfterClosing);
+    }
+
+    #[test]
+    fn seasons_wrap_around_the_new_year() {
+        let winter = Season {
+            name: "Winter".to_string(),
+            starts: MonthDay { month: 11, day: 1 },
+            ends: MonthDay { month: 2, day: 28 },
+            hours: WeeklyHours::default(),
+        };
+        assert!(winter.contains("2025-12-31".parse().unwrap()));
+        assert!(winter.contains("2026-01-15".parse().unwrap()));
+        assert!(!winter.contains("2026-03-01".parse().unwrap()));
+    }
+}
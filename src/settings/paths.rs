@@ -0,0 +1,88 @@
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+
+/// What older versions called the database, in whatever directory they happened to be started from.
+const LEGACY_DATABASE: &str = "pos.db";
+
+/// Where each kind of file goes. Relative paths are relative to the data directory; absolute ones are used as they are,
+/// e.g. to keep exports on a shared drive.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Paths {
+    pub database: PathBuf,
+    pub reports: PathBuf,
+    pub backups: PathBuf,
+    pub exports: PathBuf,
+}
+
+impl Default for Paths {
+    fn default() -> Self {
+        Self {
+            database: PathBuf::from("pos.db"),
+            reports: PathBuf::from("reports"),
+            backups: PathBuf::from("backups"),
+            exports: PathBuf::from("exports"),
+        }
+    }
+}
+
+/// Why the data directory is where it is, so the About screen can explain it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DataDirSource {
+    /// Passed with `--data-dir`.
+    Flag,
+    /// The working directory, because an older version left `pos.db` there.
+    Legacy,
+    /// The per-user data directory for this operating system.
+    #[default]
+    PerUser,
+}
+
+impl Display for DataDirSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataDirSource::Flag => write!(f, "chosen with --data-dir"),
+            DataDirSource::Legacy => write!(f, "the working directory, where an older version kept pos.db"),
+            DataDirSource::PerUser => write!(f, "the default for this user"),
+        }
+    }
+}
+
+/// The directory holding `settings.toml` and, unless the settings say otherwise, everything else.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DataDir {
+    pub path: PathBuf,
+    pub source: DataDirSource,
+}
+
+impl DataDir {
+    /// `--data-dir` wins. Without it, a `pos.db` in the working directory means an install from before data directories
+    /// existed, which keeps working where it is. Otherwise the data lives in the per-user directory, e.g.
+    /// `%APPDATA%\Silverplume\Point Of Sale\data` on Windows.
+    pub fn resolve(flag: Option<PathBuf>) -> Self {
+        if let Some(path) = flag {
+            return Self { path, source: DataDirSource::Flag };
+        }
+
+        let per_user = ProjectDirs::from("", "Silverplume", "Point Of Sale").map(|x| x.data_dir().to_path_buf());
+        let per_user_is_empty = per_user.as_ref().is_none_or(|x| !x.join(LEGACY_DATABASE).exists());
+        if Path::new(LEGACY_DATABASE).exists() && per_user_is_empty {
+            return Self {
+                path: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+                source: DataDirSource::Legacy,
+            };
+        }
+
+        match per_user {
+            Some(path) => Self { path, source: DataDirSource::PerUser },
+            None => Self { path: PathBuf::from("."), source: DataDirSource::Legacy },
+        }
+    }
+
+    /// `path` taken relative to the data directory, unless it is absolute.
+    pub fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.path.join(path)
+    }
+}
@@ -0,0 +1,38 @@
+use serde::{Deserialize, Serialize};
+
+/// Who may take money back or change a sale once it is saved. The cashier's name at the till is typed in by whoever is
+/// there, so it only says who did what in the audit log and never lets anyone past the PIN.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Permissions {
+    /// Asked for before a void, refund or correction. Until one is set, none of them can be done.
+    pub supervisor_pin: String,
+}
+
+impl Permissions {
+    /// Whether a supervisor PIN was set, without which no sale can be changed.
+    pub fn has_pin(&self) -> bool {
+        !self.supervisor_pin.is_empty()
+    }
+
+    /// Whether someone who entered `pin` may void, refund or correct a saved sale.
+    pub fn may_change_sales(&self, pin: &str) -> bool {
+        self.has_pin() && pin.trim() == self.supervisor_pin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changing_sales_needs_the_pin_and_is_refused_until_there_is_one() {
+        assert!(!Permissions::default().has_pin());
+        assert!(!Permissions::default().may_change_sales(""));
+
+        let locked = Permissions { supervisor_pin: "2468".to_string() };
+        assert!(!locked.may_change_sales(""));
+        assert!(!locked.may_change_sales("1234"));
+        assert!(locked.may_change_sales(" 2468 "));
+    }
+}
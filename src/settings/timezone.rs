@@ -0,0 +1,86 @@
+use chrono::{DateTime, Duration, Local, LocalResult, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// The timezone the museum's clocks are in, e.g. "America/Denver". Times are stored as moments, and shown and sorted
+/// into business days by what the museum's clocks read then. Left unset, that is the computer's own timezone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Timezone(pub Option<Tz>);
+
+impl Timezone {
+    /// What the museum's clocks read at `at`.
+    pub fn clock(&self, at: DateTime<Local>) -> NaiveDateTime {
+        match self.0 {
+            Some(tz) => at.with_timezone(&tz).naive_local(),
+            None => at.naive_local(),
+        }
+    }
+
+    /// `at` as RFC 3339, e.g. "2026-07-04T12:00:00-06:00", by the museum's clocks and with their offset.
+    pub fn rfc3339(&self, at: DateTime<Local>) -> String {
+        match self.0 {
+            Some(tz) => at.with_timezone(&tz).to_rfc3339(),
+            None => at.to_rfc3339(),
+        }
+    }
+
+    /// The moment the museum's clocks read `clock`. When the clocks go back and read it twice, the first one. When
+    /// they go forward past it, the moment it would have been had they not.
+    pub fn moment(&self, clock: NaiveDateTime) -> DateTime<Local> {
+        match self.0 {
+            Some(tz) => Self::resolve(&tz, clock),
+            None => Self::resolve(&Local, clock),
+        }
+    }
+
+    fn resolve<Z: TimeZone>(zone: &Z, clock: NaiveDateTime) -> DateTime<Local> {
+        match zone.from_local_datetime(&clock) {
+            LocalResult::Single(x) | LocalResult::Ambiguous(x, _) => x.with_timezone(&Local),
+            LocalResult::None => match zone.from_local_datetime(&(clock - Duration::hours(1))) {
+                LocalResult::Single(x) | LocalResult::Ambiguous(x, _) => x.with_timezone(&Local) + Duration::hours(1),
+                LocalResult::None => clock.and_utc().with_timezone(&Local),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use super::*;
+
+    fn clock(s: &str) -> NaiveDateTime {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn times_read_off_the_museum_clocks() {
+        let denver = Timezone(Some(chrono_tz::America::Denver));
+        let noon_in_denver = Utc.with_ymd_and_hms(2026, 7, 4, 18, 0, 0).unwrap().with_timezone(&Local);
+        assert_eq!(denver.clock(noon_in_denver), clock("2026-07-04T12:00:00"));
+        assert_eq!(denver.moment(clock("2026-07-04T12:00:00")), noon_in_denver);
+        assert_eq!(denver.rfc3339(noon_in_denver), "2026-07-04T12:00:00-06:00");
+
+        // Clocks in Denver skip from 2am to 3am on 2026-03-08 and read 1am twice on 2026-11-01.
+        let skipped = denver.moment(clock("2026-03-08T02:00:00"));
+        assert_eq!(skipped.with_timezone(&Utc), Utc.with_ymd_and_hms(2026, 3, 8, 9, 0, 0).unwrap());
+        assert_eq!(denver.clock(skipped), clock("2026-03-08T03:00:00"));
+        let repeated = denver.moment(clock("2026-11-01T01:30:00"));
+        assert_eq!(repeated.with_timezone(&Utc), Utc.with_ymd_and_hms(2026, 11, 1, 7, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn written_as_a_name() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Wrapper {
+            timezone: Timezone,
+        }
+
+        let denver = Wrapper { timezone: Timezone(Some(chrono_tz::America::Denver)) };
+        assert_eq!(toml::to_string(&denver).unwrap().trim(), "timezone = \"America/Denver\"");
+        assert_eq!(toml::from_str::<Wrapper>("timezone = \"America/Denver\"").unwrap(), denver);
+        assert!(toml::from_str::<Wrapper>("timezone = \"Silverplume\"").is_err());
+        assert_eq!(toml::to_string(&Wrapper { timezone: Timezone::default() }).unwrap().trim(), "");
+    }
+}
@@ -0,0 +1,31 @@
+use std::path::Path;
+use iced::{Element, Length};
+use iced::widget::{horizontal_rule, row, text, Column};
+use crate::{HEADER_SIZE, RULE_HEIGHT, TEXT_SIZE};
+use crate::settings::Settings;
+
+fn path_row<'a, Message: 'a>(label: &'a str, path: &Path) -> Element<'a, Message> {
+    row![
+        text(label).size(TEXT_SIZE).width(Length::FillPortion(1)),
+        text(path.display().to_string()).size(TEXT_SIZE).width(Length::FillPortion(4)),
+    ].spacing(RULE_HEIGHT).into()
+}
+
+/// Which version is running and where it keeps everything, so nobody has to go hunting for `pos.db`.
+pub fn view<'a, Message: 'a>(settings: &Settings) -> Element<'a, Message> {
+    Column::new()
+        .push(text("About").size(HEADER_SIZE))
+        .push(horizontal_rule(RULE_HEIGHT))
+        .push(text(format!("Museum Point Of Sale {}", env!("CARGO_PKG_VERSION"))).size(TEXT_SIZE))
+        .push(text(format!("The data directory is {}.", settings.data_dir.source)).size(TEXT_SIZE))
+        .push(path_row("Data directory", &settings.data_dir.path))
+        .push(path_row("Settings", &settings.filepath()))
+        .push(path_row("Database", &settings.database_path()))
+        .push(path_row("Reports", &settings.report_directory()))
+        .push(path_row("Backups", &settings.backup_directory()))
+        .push(path_row("Exports", &settings.export_directory()))
+        .push(text("Change these under [paths] in the settings file, or start the program with --data-dir.").size(TEXT_SIZE))
+        .spacing(RULE_HEIGHT)
+        .padding(RULE_HEIGHT)
+        .into()
+}
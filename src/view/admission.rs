@@ -16,6 +16,7 @@ pub struct Admission {
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
 pub enum Message {
     KindSet(Kind),
     PaymentMethodSet(PaymentMethod),
@@ -38,7 +39,7 @@ impl Admission {
         }
     }
 
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         let mut column = iced::widget::column![
             iced::widget::text("Admissions").size(HEADER_SIZE),
             iced::widget::horizontal_rule(RULE_HEIGHT),
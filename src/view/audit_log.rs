@@ -0,0 +1,148 @@
+use std::fmt::Display;
+use iced::{Element, Length};
+use iced::widget::{button, horizontal_rule, pick_list, row, scrollable, text, text_input, Row};
+use strum::VariantArray;
+use crate::{HEADER_SIZE, RULE_HEIGHT, TEXT_SIZE};
+use crate::model::audit_entry::{AuditAction, AuditEntry};
+use crate::model::date_time_wrapper::DateTimeWrapper;
+use crate::settings::timezone::Timezone;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ActionFilter {
+    #[default]
+    All,
+    Only(AuditAction),
+}
+
+impl ActionFilter {
+    fn options() -> Vec<ActionFilter> {
+        std::iter::once(ActionFilter::All)
+            .chain(AuditAction::VARIANTS.iter().copied().map(ActionFilter::Only))
+            .collect()
+    }
+
+    fn matches(&self, action: AuditAction) -> bool {
+        match self {
+            ActionFilter::All => true,
+            ActionFilter::Only(only) => *only == action,
+        }
+    }
+}
+
+impl Display for ActionFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActionFilter::All => write!(f, "All Actions"),
+            ActionFilter::Only(action) => write!(f, "{action}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AuditLog {
+    action: ActionFilter,
+    actor: String,
+    search: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Action(ActionFilter),
+    Actor(String),
+    Search(String),
+    /// Reads the next page of older entries.
+    ShowOlder,
+}
+
+fn contains_ignoring_case(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+impl AuditLog {
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Action(a) => self.action = a,
+            Message::Actor(a) => self.actor = a,
+            Message::Search(s) => self.search = s,
+            Message::ShowOlder => {}
+        }
+    }
+
+    fn matches(&self, entry: &AuditEntry) -> bool {
+        let search_ok = self.search.is_empty() || [
+            Some(&entry.subject),
+            Some(&entry.reason),
+            entry.before.as_ref(),
+            entry.after.as_ref(),
+        ].into_iter().flatten().any(|x| contains_ignoring_case(x, &self.search));
+
+        self.action.matches(entry.action) && contains_ignoring_case(&entry.actor, &self.actor) && search_ok
+    }
+
+    fn cells<'a>(values: [String; 7]) -> Row<'a, Message> {
+        const PORTIONS: [u16; 7] = [2, 2, 2, 3, 3, 3, 3];
+        values.into_iter()
+            .zip(PORTIONS)
+            .fold(Row::new().spacing(RULE_HEIGHT), |row, (value, portion)| {
+                row.push(text(value).size(TEXT_SIZE).width(Length::FillPortion(portion)))
+            })
+    }
+
+    /// Shows `entries` that pass the filters. `older` is whether there are older entries than those read so far.
+    pub fn view<'a>(&'a self, entries: &'a [DateTimeWrapper<AuditEntry>], older: bool, timezone: Timezone) -> Element<'a, Message> {
+        let header = Self::cells(["Time", "Actor", "Action", "Subject", "Before", "After", "Reason"].map(String::from));
+        let rows = entries.iter()
+            .filter(|x| self.matches(x.element()))
+            .fold(iced::widget::column![].spacing(RULE_HEIGHT), |column, x| {
+                let entry = x.element();
+                column.push(Self::cells([
+                    timezone.clock(x.date_time()).format("%Y-%m-%d %H:%M").to_string(),
+                    entry.actor.clone(),
+                    entry.action.to_string(),
+                    entry.subject.clone(),
+                    entry.before.clone().unwrap_or_default(),
+                    entry.after.clone().unwrap_or_default(),
+                    entry.reason.clone(),
+                ]))
+            });
+
+        iced::widget::column![
+            text("Audit Log").size(HEADER_SIZE),
+            horizontal_rule(RULE_HEIGHT),
+            row![
+                pick_list(ActionFilter::options(), Some(self.action), Message::Action),
+                text_input("Filter by actor", &self.actor).on_input(Message::Actor),
+                text_input("Search subject, values and reasons", &self.search).on_input(Message::Search),
+            ].spacing(RULE_HEIGHT),
+            header,
+            horizontal_rule(RULE_HEIGHT),
+            scrollable(rows).height(Length::Fill),
+            button("Show Older").on_press_maybe(older.then_some(Message::ShowOlder)),
+        ].spacing(RULE_HEIGHT).padding(RULE_HEIGHT).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filters_combine_and_ignore_case() {
+        let removal = AuditEntry::new("Alice", AuditAction::CartRemoval, "Cart", "wrong size").with_before("Gift Shop Sale: Mug");
+        let export = AuditEntry::new("Bob", AuditAction::Export, "reports/2026-07-04_report.html", "");
+        let mut log = AuditLog::default();
+        assert!(log.matches(&removal) && log.matches(&export));
+
+        log.update(Message::Action(ActionFilter::Only(AuditAction::CartRemoval)));
+        assert!(log.matches(&removal) && !log.matches(&export));
+
+        log.update(Message::Search("MUG".to_string()));
+        assert!(log.matches(&removal));
+        log.update(Message::Actor("bob".to_string()));
+        assert!(!log.matches(&removal));
+
+        log = AuditLog::default();
+        log.update(Message::Search("2026-07-04".to_string()));
+        assert!(!log.matches(&removal) && log.matches(&export));
+    }
+}
@@ -0,0 +1,78 @@
+use std::path::PathBuf;
+use iced::{Element, Length};
+use iced::widget::{button, horizontal_rule, row, text};
+use iced_aw::selection_list;
+use crate::{HEADER_SIZE, RULE_HEIGHT, TEXT_SIZE};
+use crate::database::backup::BackupFile;
+
+/// Lists the backups and walks through restoring one, which needs a second, explicit confirmation.
+#[derive(Debug, Clone, Default)]
+pub struct Backups {
+    backups: Vec<BackupFile>,
+    labels: Vec<String>,
+    selected: Option<usize>,
+    confirming: bool,
+    status: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Select(usize),
+    BackUpNow,
+    Restore,
+    ConfirmRestore(PathBuf),
+    CancelRestore,
+}
+
+impl Backups {
+    pub fn set_backups(&mut self, backups: Vec<BackupFile>) {
+        self.labels = backups.iter()
+            .map(|x| format!("{}    {}", x.taken.format("%Y-%m-%d %H:%M:%S"), x.path.display()))
+            .collect();
+        self.backups = backups;
+        self.selected = None;
+        self.confirming = false;
+    }
+
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = status.into();
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Select(index) => {
+                self.selected = Some(index);
+                self.confirming = false;
+            }
+            Message::Restore => self.confirming = true,
+            Message::CancelRestore | Message::ConfirmRestore(_) => self.confirming = false,
+            Message::BackUpNow => {}
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let selected = self.selected.and_then(|x| self.backups.get(x));
+        let actions = match (selected, self.confirming) {
+            (Some(backup), true) => row![
+                text(format!(
+                    "Replace everything in the database with the backup from {}? What is there now is backed up first.",
+                    backup.taken.format("%Y-%m-%d %H:%M")
+                )).size(TEXT_SIZE),
+                button("Yes, Restore It").on_press(Message::ConfirmRestore(backup.path.clone())),
+                button("Cancel").on_press(Message::CancelRestore),
+            ],
+            _ => row![
+                button("Back Up Now").on_press(Message::BackUpNow),
+                button("Restore Selected Backup").on_press_maybe(selected.map(|_| Message::Restore)),
+            ],
+        };
+
+        iced::widget::column![
+            text("Backups").size(HEADER_SIZE),
+            horizontal_rule(RULE_HEIGHT),
+            actions.spacing(RULE_HEIGHT),
+            text(&self.status).size(TEXT_SIZE),
+            selection_list(&self.labels, |x, _| Message::Select(x)).height(Length::Fill),
+        ].spacing(RULE_HEIGHT).padding(RULE_HEIGHT).into()
+    }
+}
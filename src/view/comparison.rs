@@ -0,0 +1,121 @@
+use chrono::NaiveDate;
+use iced::{Element, Length};
+use iced::widget::{button, horizontal_rule, pick_list, row, scrollable, text, text_input, Row};
+use strum::VariantArray;
+use crate::{HEADER_SIZE, RULE_HEIGHT, TEXT_SIZE};
+use crate::report::comparison::{Baseline, Comparison};
+use crate::report::period::Period;
+use crate::view::period_picker::{self, PeriodPicker};
+
+/// Picks a period and what to compare it with, and shows the two side by side.
+#[derive(Debug, Clone)]
+pub struct Comparisons {
+    period: PeriodPicker,
+    baseline: Baseline,
+    from: String,
+    to: String,
+    comparison: Option<Comparison>,
+    status: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Period(period_picker::Message),
+    Baseline(Baseline),
+    From(String),
+    To(String),
+    Compare(Period, Period),
+    Export,
+}
+
+impl Comparisons {
+    pub fn new(today: NaiveDate) -> Self {
+        Self {
+            period: PeriodPicker::new(today),
+            baseline: Default::default(),
+            from: String::new(),
+            to: String::new(),
+            comparison: None,
+            status: String::new(),
+        }
+    }
+
+    pub fn comparison(&self) -> Option<&Comparison> {
+        self.comparison.as_ref()
+    }
+
+    pub fn set_comparison(&mut self, comparison: Comparison) {
+        self.comparison = Some(comparison);
+        self.status.clear();
+    }
+
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = status.into();
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Period(p) => self.period.update(p),
+            Message::Baseline(b) => self.baseline = b,
+            Message::From(f) => self.from = f,
+            Message::To(t) => self.to = t,
+            Message::Compare(..) | Message::Export => {}
+        }
+    }
+
+    /// The chosen period and the one to compare it with, or None while either isn't filled in properly.
+    fn periods(&self, today: NaiveDate) -> Option<(Period, Period)> {
+        let current = self.period.period(today)?;
+        let custom = period_picker::parse(&self.from).zip(period_picker::parse(&self.to)).map(|(from, to)| Period::custom(from, to));
+        Some((current, self.baseline.period(&current, custom)?))
+    }
+
+    fn cells<'a>(values: [String; 5]) -> Row<'a, Message> {
+        const PORTIONS: [u16; 5] = [3, 2, 2, 2, 2];
+        values.into_iter()
+            .zip(PORTIONS)
+            .fold(Row::new().spacing(RULE_HEIGHT), |row, (value, portion)| {
+                row.push(text(value).size(TEXT_SIZE).width(Length::FillPortion(portion)))
+            })
+    }
+
+    pub fn view(&self, today: NaiveDate) -> Element<'_, Message> {
+        let mut against = row![
+            text("Compare with: ").size(TEXT_SIZE),
+            pick_list(Baseline::VARIANTS, Some(self.baseline), Message::Baseline),
+        ].spacing(RULE_HEIGHT);
+        if self.baseline == Baseline::Custom {
+            against = against
+                .push(text_input("From (YYYY-MM-DD)", &self.from).on_input(Message::From).width(160))
+                .push(text_input("To (YYYY-MM-DD)", &self.to).on_input(Message::To).width(160));
+        }
+
+        let mut table = iced::widget::column![].spacing(RULE_HEIGHT);
+        if let Some(comparison) = &self.comparison {
+            table = table.push(Self::cells([
+                String::new(), comparison.current.describe(), comparison.previous.describe(), "Change".to_string(), "Change (%)".to_string(),
+            ]));
+            for (heading, metrics) in &comparison.sections {
+                table = table.push(text(*heading).size(TEXT_SIZE + 4)).push(horizontal_rule(RULE_HEIGHT));
+                for metric in metrics {
+                    table = table.push(Self::cells([
+                        metric.name.clone(), metric.current.to_string(), metric.previous.to_string(), metric.change_text(), metric.percent_text(),
+                    ]));
+                }
+            }
+        }
+
+        iced::widget::column![
+            text("Compare Periods").size(HEADER_SIZE),
+            horizontal_rule(RULE_HEIGHT),
+            row![
+                self.period.view().map(Message::Period),
+                against,
+                button("Compare").on_press_maybe(self.periods(today).map(|(current, previous)| Message::Compare(current, previous))),
+                button("Export Comparison").on_press_maybe(self.comparison.as_ref().map(|_| Message::Export)),
+            ].spacing(RULE_HEIGHT),
+            text(&self.status).size(TEXT_SIZE),
+            scrollable(table).height(Length::Fill),
+        ].spacing(RULE_HEIGHT).padding(RULE_HEIGHT).into()
+    }
+}
@@ -27,7 +27,7 @@ impl Donation {
         }
     }
 
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         iced::widget::column![
             text("Donations").size(HEADER_SIZE),
             horizontal_rule(RULE_HEIGHT),
@@ -57,6 +57,7 @@ impl ToModel for Donation {
         Ok(Self::ModelType {
             payment_method: self.payment_method.ok_or(anyhow!("Donation is missing it's payment method!"))?,
             price: self.price.value(),
+            sale: None,
         })
     }
 }
\ No newline at end of file
@@ -5,6 +5,7 @@ use iced_aw::number_input;
 use strum::VariantArray;
 use crate::decimal_input::DecimalInput;
 use crate::{HEADER_SIZE, RULE_HEIGHT, TEXT_SIZE};
+use crate::model::gift_shop_sale::DEFAULT_SALES_TAX;
 use crate::model::payment_method::PaymentMethod;
 use crate::to_model::ToModel;
 
@@ -17,7 +18,6 @@ pub struct GiftShopSale {
     sales_tax: DecimalInput
 }
 
-const DEFAULT_SALES_TAX: f32 = 8.55 ;
 impl Default for GiftShopSale {
     fn default() -> Self {
         Self {
@@ -31,6 +31,7 @@ impl Default for GiftShopSale {
 }
 
 #[derive(Debug, Clone)]
+#[allow(clippy::enum_variant_names)]
 pub enum Message {
     DescriptionChanged(String),
     PriceChanged(crate::decimal_input::Message),
@@ -54,15 +55,15 @@ impl GiftShopSale {
         self.price.value() * (self.quantity as f32) * tax_factor
     }
     
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         iced::widget::column![
             iced::widget::text("Gift Shop Sales").size(HEADER_SIZE),
             iced::widget::horizontal_rule(RULE_HEIGHT),
             text_input("Item Description", self.item_description.as_str()).on_input(Message::DescriptionChanged),
-            self.price.view().map(|x| Message::PriceChanged(x)),
+            self.price.view().map(Message::PriceChanged),
             pick_list(PaymentMethod::VARIANTS, self.payment_method.as_ref(), Message::PaymentMethodChanged).placeholder("Select Payment Method"),
             row![text("Quantity: ").size(TEXT_SIZE), number_input(&self.quantity, 1..=u16::MAX, Message::QuantityChanged)].spacing(RULE_HEIGHT),
-            self.sales_tax.view().map(|x| Message::SalesTaxChanged(x)),
+            self.sales_tax.view().map(Message::SalesTaxChanged),
             text(format!("Total due: ${:.2}", self.compute_total_cost())).size(TEXT_SIZE),
         ].spacing(RULE_HEIGHT).into()
     }
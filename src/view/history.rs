@@ -0,0 +1,444 @@
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use chrono::NaiveDate;
+use iced::{Element, Length};
+use iced::widget::{button, horizontal_rule, pick_list, row, scrollable, text, text_input, Row};
+use iced_aw::selection_list;
+use itertools::Itertools;
+use strum::VariantArray;
+use crate::{HEADER_SIZE, RULE_HEIGHT, TEXT_SIZE};
+use crate::model::admission;
+use crate::model::cart_item::CartItem;
+use crate::model::membership;
+use crate::model::payment_method::PaymentMethod;
+use crate::model::sale::{SaleLines, SaleStatus};
+use crate::model::transaction_record::TransactionKind;
+use crate::report::period::Period;
+use crate::settings::timezone::Timezone;
+use crate::view::adapters::ff;
+use crate::view::period_picker::{self, PeriodPicker};
+
+/// Either anything at all, or only the one choice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Filter<T> {
+    #[default]
+    All,
+    Only(T),
+}
+
+impl<T: Copy + PartialEq> Filter<T> {
+    fn options(variants: &[T]) -> Vec<Filter<T>> {
+        std::iter::once(Filter::All).chain(variants.iter().copied().map(Filter::Only)).collect()
+    }
+
+    fn matches(&self, value: Option<T>) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Only(only) => value == Some(*only),
+        }
+    }
+}
+
+impl<T: Display> Display for Filter<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Filter::All => write!(f, "All"),
+            Filter::Only(x) => write!(f, "{x}"),
+        }
+    }
+}
+
+/// One item of the selected sale being corrected, as it was saved and as it will be.
+#[derive(Debug, Clone)]
+struct Correction {
+    before: CartItem,
+    after: CartItem,
+    /// As typed, so it can be cleared while typing.
+    quantity: String,
+}
+
+/// Finds past sales and shows what was in them, with the reprint, void and refund buttons for the one picked.
+#[derive(Debug, Clone)]
+pub struct History {
+    period: PeriodPicker,
+    /// The period `sales` came from, so they can be read again after a change.
+    loaded: Option<Period>,
+    sales: Vec<SaleLines>,
+    category: Filter<TransactionKind>,
+    payment_method: Filter<PaymentMethod>,
+    cashier: String,
+    minimum: String,
+    maximum: String,
+    search: String,
+    /// The gift shop item descriptions `search` finds.
+    found: BTreeSet<String>,
+    /// What was taken and given back in the period loaded.
+    takings: (f64, f64),
+    /// Indexes into `sales` of the ones that pass the filters, and what the list shows for each.
+    shown: Vec<usize>,
+    labels: Vec<String>,
+    selected: Option<SaleLines>,
+    /// What the selected sale is made of, for correcting one of them.
+    items: Vec<CartItem>,
+    correction: Option<Correction>,
+    reason: String,
+    pin: String,
+    status: String,
+    /// The museum's clocks, which times are shown by.
+    timezone: Timezone,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Period(period_picker::Message),
+    Load(Period),
+    Category(Filter<TransactionKind>),
+    PaymentMethod(Filter<PaymentMethod>),
+    Cashier(String),
+    Minimum(String),
+    Maximum(String),
+    Search(String),
+    Select(usize),
+    Reason(String),
+    Pin(String),
+    Reprint(i64),
+    Void(i64),
+    Refund(i64),
+    Correct(usize),
+    CorrectAdmissionKind(admission::kind::Kind),
+    CorrectMembershipKind(membership::kind::Kind),
+    CorrectPaymentMethod(PaymentMethod),
+    CorrectQuantity(String),
+    CorrectDescription(String),
+    CancelCorrection,
+    SaveCorrection(i64),
+}
+
+fn contains_ignoring_case(haystack: &str, needle: &str) -> bool {
+    haystack.to_lowercase().contains(&needle.to_lowercase())
+}
+
+/// A blank amount doesn't limit anything, and neither does one that isn't a number yet.
+fn amount(text: &str) -> Option<f32> {
+    text.trim().trim_start_matches('$').parse().ok()
+}
+
+impl History {
+    pub fn new(today: NaiveDate, timezone: Timezone) -> Self {
+        Self {
+            period: PeriodPicker::new(today),
+            loaded: None,
+            sales: vec![],
+            category: Default::default(),
+            payment_method: Default::default(),
+            cashier: String::new(),
+            minimum: String::new(),
+            maximum: String::new(),
+            search: String::new(),
+            found: BTreeSet::new(),
+            takings: (0.0, 0.0),
+            shown: vec![],
+            labels: vec![],
+            selected: None,
+            items: vec![],
+            correction: None,
+            reason: String::new(),
+            pin: String::new(),
+            status: String::new(),
+            timezone,
+        }
+    }
+
+    /// The period the sales shown were read for, so they can be read again after one changes.
+    pub fn loaded(&self) -> Option<Period> {
+        self.loaded
+    }
+
+    pub fn selected(&self) -> Option<&SaleLines> {
+        self.selected.as_ref()
+    }
+
+    /// The item being corrected as it was saved and as it will be, once it is ready to save.
+    pub fn correction(&self) -> Option<(&CartItem, &CartItem)> {
+        let correction = self.correction.as_ref()?;
+        let quantity = correction.quantity.trim().parse::<u16>().is_ok_and(|x| x > 0);
+        (quantity && correction.before != correction.after).then_some((&correction.before, &correction.after))
+    }
+
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    pub fn pin(&self) -> &str {
+        &self.pin
+    }
+
+    /// What is being searched for, once there is something to search for.
+    pub fn search(&self) -> Option<&str> {
+        Some(self.search.trim()).filter(|x| !x.is_empty())
+    }
+
+    pub fn set_sales(&mut self, period: Period, sales: Vec<SaleLines>, takings: (f64, f64)) {
+        self.loaded = Some(period);
+        self.sales = sales;
+        self.takings = takings;
+        self.filter();
+    }
+
+    /// The gift shop item descriptions found by the search.
+    pub fn set_found(&mut self, found: BTreeSet<String>) {
+        self.found = found;
+        self.filter();
+    }
+
+    /// Shows `sale` in place of the one selected, e.g. after it was voided.
+    pub fn set_selected(&mut self, sale: SaleLines) {
+        self.selected = Some(sale);
+        self.reason.clear();
+        self.pin.clear();
+    }
+
+    /// What the selected sale is made of, read after it was selected or changed.
+    pub fn set_items(&mut self, items: Vec<CartItem>) {
+        self.items = items;
+        self.correction = None;
+    }
+
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = status.into();
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Period(p) => self.period.update(p),
+            Message::Category(c) => self.category = c,
+            Message::PaymentMethod(p) => self.payment_method = p,
+            Message::Cashier(c) => self.cashier = c,
+            Message::Minimum(m) => self.minimum = m,
+            Message::Maximum(m) => self.maximum = m,
+            Message::Search(s) => self.search = s,
+            Message::Select(index) => {
+                self.selected = self.shown.get(index).map(|x| self.sales[*x].clone());
+                self.items.clear();
+                self.correction = None;
+                self.reason.clear();
+                self.pin.clear();
+                self.status.clear();
+            }
+            Message::Reason(r) => self.reason = r,
+            Message::Pin(p) => self.pin = p,
+            Message::Correct(index) => {
+                self.correction = self.items.get(index).map(|x| Correction {
+                    before: x.clone(),
+                    after: x.clone(),
+                    quantity: x.as_transaction_record().quantity.to_string(),
+                });
+                self.status.clear();
+            }
+            Message::CorrectAdmissionKind(kind) => {
+                if let Some(CartItem::Admission(x)) = self.corrected() {
+                    x.kind = kind;
+                    // Free admissions aren't paid for, so they have no payment method.
+                    if kind.is_free() {
+                        x.payment_method = None;
+                    }
+                }
+            }
+            Message::CorrectMembershipKind(kind) => {
+                if let Some(CartItem::Membership(x)) = self.corrected() {
+                    x.kind = kind;
+                }
+            }
+            Message::CorrectPaymentMethod(payment_method) => match self.corrected() {
+                Some(CartItem::Admission(x)) => x.payment_method = Some(payment_method),
+                Some(CartItem::Membership(x)) => x.payment_method = payment_method,
+                Some(CartItem::Donation(x)) => x.payment_method = payment_method,
+                Some(CartItem::GiftShopSale(x)) => x.payment_method = payment_method,
+                None => {}
+            },
+            Message::CorrectQuantity(q) => {
+                if let Ok(quantity) = q.trim().parse() {
+                    match self.corrected() {
+                        Some(CartItem::Admission(x)) => x.quantity = quantity,
+                        Some(CartItem::Membership(x)) => x.quantity = quantity,
+                        Some(CartItem::GiftShopSale(x)) => x.quantity = quantity,
+                        Some(CartItem::Donation(_)) | None => {}
+                    }
+                }
+                if let Some(correction) = &mut self.correction {
+                    correction.quantity = q;
+                }
+            }
+            Message::CorrectDescription(d) => {
+                if let Some(CartItem::GiftShopSale(x)) = self.corrected() {
+                    x.item_description = d;
+                }
+            }
+            Message::CancelCorrection => self.correction = None,
+            Message::Load(_) | Message::Reprint(_) | Message::Void(_) | Message::Refund(_) | Message::SaveCorrection(_) => {}
+        }
+        self.filter();
+    }
+
+    fn corrected(&mut self) -> Option<&mut CartItem> {
+        self.correction.as_mut().map(|x| &mut x.after)
+    }
+
+    fn matches(&self, sale: &SaleLines) -> bool {
+        let cashier = sale.sale.as_ref().map(|x| x.cashier.as_str()).unwrap_or_default();
+        let total = sale.total();
+        // Voided sales have no lines left, so only the filters that let everything through show them.
+        (self.category == Filter::All || sale.lines.iter().any(|x| self.category.matches(Some(x.kind))))
+            && (self.payment_method == Filter::All || sale.lines.iter().any(|x| self.payment_method.matches(x.payment_method)))
+            && contains_ignoring_case(cashier, self.cashier.trim())
+            && amount(&self.minimum).is_none_or(|x| total >= x)
+            && amount(&self.maximum).is_none_or(|x| total <= x)
+            && (self.search().is_none() || sale.lines.iter().any(|x| {
+                x.kind == TransactionKind::GiftShopSale && self.found.contains(&x.description)
+            }))
+    }
+
+    fn filter(&mut self) {
+        self.shown = (0..self.sales.len()).filter(|x| self.matches(&self.sales[*x])).collect();
+        self.labels = self.shown.iter()
+            .map(|x| {
+                let sale = &self.sales[*x];
+                format!(
+                    "{:<8}{:<20}{:<16}{:<12}{:<10}{}",
+                    sale.number().map(|x| format!("#{x}")).unwrap_or("-".to_string()),
+                    sale.date_time().map(|x| self.timezone.clock(x).format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default(),
+                    sale.sale.as_ref().map(|x| x.cashier.clone()).unwrap_or_default(),
+                    ff("$", sale.total()),
+                    sale.status(),
+                    sale.lines.iter().filter(|x| !x.refund).map(|x| x.description.as_str()).join(", "),
+                )
+            })
+            .collect();
+    }
+
+    fn cells<'a>(values: [String; 6]) -> Row<'a, Message> {
+        const PORTIONS: [u16; 6] = [3, 2, 4, 1, 2, 2];
+        values.into_iter()
+            .zip(PORTIONS)
+            .fold(Row::new().spacing(RULE_HEIGHT), |row, (value, portion)| {
+                row.push(text(value).size(TEXT_SIZE).width(Length::FillPortion(portion)))
+            })
+    }
+
+    fn detail(&self, sale: &SaleLines) -> Element<'_, Message> {
+        let title = match &sale.sale {
+            Some(header) => format!("Sale #{} by {}: {}", header.number, header.cashier, sale.status()),
+            None => "Recorded before sales were numbered".to_string(),
+        };
+        let lines = sale.lines.iter().fold(iced::widget::column![].spacing(RULE_HEIGHT), |column, x| {
+            column.push(Self::cells([
+                self.timezone.clock(x.date_time).format("%Y-%m-%d %H:%M").to_string(),
+                x.kind.to_string(),
+                x.description.clone(),
+                x.quantity.to_string(),
+                x.payment_method.map(|x| x.to_string()).unwrap_or("Free".to_string()),
+                ff("$", x.total),
+            ]))
+        });
+
+        let mut detail = iced::widget::column![
+            text(title).size(HEADER_SIZE),
+            horizontal_rule(RULE_HEIGHT),
+            Self::cells(["Time", "Kind", "Description", "Quantity", "Payment Method", "Total"].map(String::from)),
+            lines,
+            text(format!("Total: {}", ff("$", sale.total()))).size(TEXT_SIZE),
+        ].spacing(RULE_HEIGHT);
+
+        if let Some(header) = &sale.sale {
+            let completed = header.status == SaleStatus::Completed && !sale.is_refunded();
+            let has_reason = !self.reason.trim().is_empty();
+            detail = detail.push(row![
+                button("Reprint Receipt").on_press(Message::Reprint(header.number)),
+                text_input("Reason for voiding, refunding or correcting", &self.reason).on_input(Message::Reason),
+                text_input("Supervisor PIN", &self.pin).on_input(Message::Pin).secure(true).width(160),
+                button("Void Sale").on_press_maybe((completed && has_reason).then_some(Message::Void(header.number))),
+                button("Refund Sale").on_press_maybe((completed && has_reason).then_some(Message::Refund(header.number))),
+            ].spacing(RULE_HEIGHT));
+
+            if completed {
+                detail = detail.push(self.items.iter().enumerate().fold(iced::widget::column![].spacing(RULE_HEIGHT), |column, (index, item)| {
+                    column.push(row![
+                        text(item.describe()).size(TEXT_SIZE).width(Length::Fill),
+                        button("Correct").on_press(Message::Correct(index)),
+                    ].spacing(RULE_HEIGHT))
+                }));
+            }
+            if let Some(correction) = self.correction.as_ref().filter(|_| completed) {
+                let save = (has_reason && self.correction().is_some()).then_some(Message::SaveCorrection(header.number));
+                detail = detail.push(row![
+                    text(format!("Correcting {}", correction.before.describe())).size(TEXT_SIZE),
+                    Self::corrector(correction),
+                    button("Save Correction").on_press_maybe(save),
+                    button("Cancel").on_press(Message::CancelCorrection),
+                ].spacing(RULE_HEIGHT));
+            }
+        }
+
+        detail.into()
+    }
+
+    /// The fields that can be corrected for the kind of item being corrected.
+    fn corrector(correction: &Correction) -> Row<'_, Message> {
+        let quantity = || text_input("Quantity", &correction.quantity).on_input(Message::CorrectQuantity).width(80);
+        let payment_method = |x| pick_list(PaymentMethod::VARIANTS, x, Message::CorrectPaymentMethod);
+        let fields = match &correction.after {
+            CartItem::Admission(x) => row![
+                pick_list(admission::kind::Kind::VARIANTS, Some(x.kind), Message::CorrectAdmissionKind),
+                quantity(),
+            ].push_maybe((!x.kind.is_free()).then(|| payment_method(x.payment_method))),
+            CartItem::Membership(x) => row![
+                pick_list(membership::kind::Kind::VARIANTS, Some(x.kind), Message::CorrectMembershipKind),
+                quantity(),
+                payment_method(Some(x.payment_method)),
+            ],
+            CartItem::Donation(x) => row![payment_method(Some(x.payment_method))],
+            CartItem::GiftShopSale(x) => row![
+                text_input("Description", &x.item_description).on_input(Message::CorrectDescription),
+                quantity(),
+                payment_method(Some(x.payment_method)),
+            ],
+        };
+
+        fields.spacing(RULE_HEIGHT)
+    }
+
+    pub fn view(&self, today: NaiveDate) -> Element<'_, Message> {
+        let filters = row![
+            text("Category: ").size(TEXT_SIZE),
+            pick_list(Filter::options(&[
+                TransactionKind::Admission, TransactionKind::Membership, TransactionKind::Donation, TransactionKind::GiftShopSale,
+            ]), Some(self.category), Message::Category),
+            text("Paid by: ").size(TEXT_SIZE),
+            pick_list(Filter::options(PaymentMethod::VARIANTS), Some(self.payment_method), Message::PaymentMethod),
+            text_input("Cashier", &self.cashier).on_input(Message::Cashier),
+            text_input("Minimum $", &self.minimum).on_input(Message::Minimum).width(100),
+            text_input("Maximum $", &self.maximum).on_input(Message::Maximum).width(100),
+            text_input("Search gift shop items", &self.search).on_input(Message::Search),
+        ].spacing(RULE_HEIGHT);
+
+        let mut body = iced::widget::column![
+            text("History").size(HEADER_SIZE),
+            horizontal_rule(RULE_HEIGHT),
+            row![
+                self.period.view().map(Message::Period),
+                button("Show Sales").on_press_maybe(self.period.period(today).map(Message::Load)),
+            ].spacing(RULE_HEIGHT),
+            filters,
+            text(format!(
+                "{} of {} sales. {} taken and {} given back.",
+                self.shown.len(), self.sales.len(), ff("$", self.takings.0 as f32), ff("$", self.takings.1 as f32),
+            )).size(TEXT_SIZE),
+            selection_list(&self.labels, |x, _| Message::Select(x)).height(Length::FillPortion(1)),
+        ].spacing(RULE_HEIGHT).padding(RULE_HEIGHT);
+
+        if let Some(sale) = &self.selected {
+            body = body.push(scrollable(self.detail(sale)).height(Length::FillPortion(1)));
+        }
+        body.push(text(&self.status).size(TEXT_SIZE)).into()
+    }
+}
@@ -32,7 +32,7 @@ impl Membership {
         }
     }
 
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         iced::widget::column![
             iced::widget::text("Memberships").size(HEADER_SIZE),
             iced::widget::horizontal_rule(RULE_HEIGHT),
@@ -3,4 +3,14 @@ pub mod admission;
 pub mod membership;
 pub mod gift_shop_sale;
 pub mod adapters;
-pub mod summary_dicts;
\ No newline at end of file
+pub mod summary_dicts;
+pub mod audit_log;
+pub mod period_picker;
+pub mod startup_error;
+pub mod about;
+pub mod backups;
+pub mod comparison;
+pub mod reports;
+
+pub mod history;
+pub mod quick_sales;
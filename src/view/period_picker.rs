@@ -0,0 +1,69 @@
+use chrono::NaiveDate;
+use iced::Element;
+use iced::widget::{pick_list, row, text, text_input};
+use strum::VariantArray;
+use crate::{RULE_HEIGHT, TEXT_SIZE};
+use crate::report::period::{Period, PeriodKind};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeriodPicker {
+    kind: PeriodKind,
+    from: String,
+    to: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Kind(PeriodKind),
+    From(String),
+    To(String),
+}
+
+pub(crate) fn parse(date: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(date.trim(), DATE_FORMAT).ok()
+}
+
+impl PeriodPicker {
+    /// Starts on today's report, with a custom range of just `today` ready to edit.
+    pub fn new(today: NaiveDate) -> Self {
+        let today = today.format(DATE_FORMAT).to_string();
+        Self {
+            kind: Default::default(),
+            from: today.clone(),
+            to: today,
+        }
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Kind(k) => self.kind = k,
+            Message::From(f) => self.from = f,
+            Message::To(t) => self.to = t,
+        }
+    }
+
+    /// The chosen period, or None while a custom range isn't filled in properly.
+    pub fn period(&self, today: NaiveDate) -> Option<Period> {
+        match self.kind {
+            PeriodKind::Custom => Some(Period::custom(parse(&self.from)?, parse(&self.to)?)),
+            kind => Some(Period::to_date(kind, today)),
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let mut row = row![
+            text("Period: ").size(TEXT_SIZE),
+            pick_list(PeriodKind::VARIANTS, Some(self.kind), Message::Kind),
+        ].spacing(RULE_HEIGHT);
+
+        if self.kind == PeriodKind::Custom {
+            row = row
+                .push(text_input("From (YYYY-MM-DD)", &self.from).on_input(Message::From).width(160))
+                .push(text_input("To (YYYY-MM-DD)", &self.to).on_input(Message::To).width(160));
+        }
+
+        row.into()
+    }
+}
@@ -0,0 +1,227 @@
+use std::fmt::Display;
+use iced::{Element, Length};
+use iced::widget::{button, horizontal_rule, pick_list, row, scrollable, text, text_input, Row};
+use strum::VariantArray;
+use crate::{HEADER_SIZE, RULE_HEIGHT, TEXT_SIZE};
+use crate::model::payment_method::PaymentMethod;
+use crate::model::quick_sale::QuickSale;
+use crate::model::transaction_record::TransactionKind;
+
+/// How a button is paid for: always the same way, or however the cashier picked at the till.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultPayment {
+    Picked,
+    Always(PaymentMethod),
+}
+
+impl DefaultPayment {
+    fn all() -> Vec<DefaultPayment> {
+        std::iter::once(DefaultPayment::Picked).chain(PaymentMethod::VARIANTS.iter().map(|x| DefaultPayment::Always(*x))).collect()
+    }
+
+    fn method(&self) -> Option<PaymentMethod> {
+        match self {
+            DefaultPayment::Picked => None,
+            DefaultPayment::Always(x) => Some(*x),
+        }
+    }
+}
+
+impl Display for DefaultPayment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DefaultPayment::Picked => write!(f, "Picked at the till"),
+            DefaultPayment::Always(x) => write!(f, "Always {x}"),
+        }
+    }
+}
+
+/// A button as it is being edited, with the numbers kept as typed until they are saved.
+#[derive(Debug, Clone)]
+struct Editing {
+    button: QuickSale,
+    quantity: String,
+    price: String,
+    sales_tax: String,
+}
+
+impl Editing {
+    fn new(button: QuickSale) -> Self {
+        Self {
+            quantity: button.quantity.to_string(),
+            price: format!("{:.2}", button.price),
+            sales_tax: button.sales_tax.to_string(),
+            button,
+        }
+    }
+
+    fn button(&self, position: usize) -> anyhow::Result<QuickSale> {
+        let label = &self.button.label;
+        let quantity = self.quantity.trim().parse().map_err(|_| anyhow::anyhow!("{label}: {:?} is not a quantity", self.quantity))?;
+        let price = match self.button.category {
+            TransactionKind::Donation | TransactionKind::GiftShopSale => {
+                self.price.trim().parse().map_err(|_| anyhow::anyhow!("{label}: {:?} is not a price", self.price))?
+            }
+            TransactionKind::Admission | TransactionKind::Membership => 0.0,
+        };
+        let sales_tax = match self.button.category {
+            TransactionKind::GiftShopSale => {
+                self.sales_tax.trim().parse().map_err(|_| anyhow::anyhow!("{label}: {:?} is not a sales tax rate", self.sales_tax))?
+            }
+            TransactionKind::Admission | TransactionKind::Membership | TransactionKind::Donation => self.button.sales_tax,
+        };
+        let button = QuickSale { position: position as u16, quantity, price, sales_tax, ..self.button.clone() };
+        button.check()?;
+        Ok(button)
+    }
+}
+
+/// Edits the grid of quick sale buttons on the sales screen: what each adds, how it is paid for and where it goes.
+#[derive(Debug, Clone, Default)]
+pub struct QuickSales {
+    saved: Vec<QuickSale>,
+    editing: Vec<Editing>,
+    status: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Label(usize, String),
+    Category(usize, TransactionKind),
+    Kind(usize, String),
+    Quantity(usize, String),
+    Price(usize, String),
+    SalesTax(usize, String),
+    Payment(usize, DefaultPayment),
+    MoveUp(usize),
+    MoveDown(usize),
+    Remove(usize),
+    Add,
+    Revert,
+    Save,
+}
+
+impl QuickSales {
+    /// Shows `saved` as the buttons to edit, throwing away any unsaved edits.
+    pub fn set_buttons(&mut self, saved: Vec<QuickSale>) {
+        self.editing = saved.iter().cloned().map(Editing::new).collect();
+        self.saved = saved;
+    }
+
+    pub fn saved(&self) -> &[QuickSale] {
+        &self.saved
+    }
+
+    /// The buttons as edited, or what is wrong with the first one that can't be saved.
+    pub fn buttons(&self) -> anyhow::Result<Vec<QuickSale>> {
+        self.editing.iter().enumerate().map(|(position, x)| x.button(position)).collect()
+    }
+
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = status.into();
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Label(index, label) => self.editing[index].button.label = label,
+            Message::Category(index, category) => {
+                let button = &mut self.editing[index].button;
+                button.category = category;
+                button.kind = QuickSale::kinds(category).into_iter().next().unwrap_or_default();
+            }
+            Message::Kind(index, kind) => self.editing[index].button.kind = kind,
+            Message::Quantity(index, quantity) => self.editing[index].quantity = quantity,
+            Message::Price(index, price) => self.editing[index].price = price,
+            Message::SalesTax(index, sales_tax) => self.editing[index].sales_tax = sales_tax,
+            Message::Payment(index, payment) => self.editing[index].button.payment_method = payment.method(),
+            Message::MoveUp(index) => self.editing.swap(index, index - 1),
+            Message::MoveDown(index) => self.editing.swap(index, index + 1),
+            Message::Remove(index) => {
+                self.editing.remove(index);
+            }
+            Message::Add => self.editing.push(Editing::new(QuickSale { label: "New Button".to_string(), ..Default::default() })),
+            Message::Revert => {
+                self.set_buttons(self.saved.clone());
+                self.status.clear();
+            }
+            Message::Save => {}
+        }
+    }
+
+    fn editor(&self, index: usize, editing: &Editing) -> Row<'_, Message> {
+        let quick_sale = &editing.button;
+        let kinds = QuickSale::kinds(quick_sale.category);
+        let kind: Element<'_, Message> = match quick_sale.category {
+            TransactionKind::Donation => text("").width(Length::FillPortion(3)).into(),
+            TransactionKind::GiftShopSale => text_input("Item description", &quick_sale.kind)
+                .on_input(move |x| Message::Kind(index, x))
+                .width(Length::FillPortion(3))
+                .into(),
+            TransactionKind::Admission | TransactionKind::Membership => pick_list(kinds, Some(quick_sale.kind.clone()), move |x| Message::Kind(index, x))
+                .width(Length::FillPortion(3))
+                .into(),
+        };
+        let price: Element<'_, Message> = match quick_sale.category {
+            TransactionKind::Donation | TransactionKind::GiftShopSale => text_input("Price", &editing.price)
+                .on_input(move |x| Message::Price(index, x))
+                .width(Length::FillPortion(1))
+                .into(),
+            TransactionKind::Admission | TransactionKind::Membership => text("").width(Length::FillPortion(1)).into(),
+        };
+        let sales_tax: Element<'_, Message> = match quick_sale.category {
+            TransactionKind::GiftShopSale => text_input("Sales Tax (%)", &editing.sales_tax)
+                .on_input(move |x| Message::SalesTax(index, x))
+                .width(Length::FillPortion(1))
+                .into(),
+            TransactionKind::Admission | TransactionKind::Membership | TransactionKind::Donation => text("").width(Length::FillPortion(1)).into(),
+        };
+        let payment = match quick_sale.payment_method {
+            Some(x) => DefaultPayment::Always(x),
+            None => DefaultPayment::Picked,
+        };
+
+        row![
+            text(if index < 9 { format!("{}.", index + 1) } else { String::new() }).size(TEXT_SIZE).width(Length::FillPortion(1)),
+            text_input("Label", &quick_sale.label).on_input(move |x| Message::Label(index, x)).width(Length::FillPortion(3)),
+            pick_list(TransactionKind::VARIANTS, Some(quick_sale.category), move |x| Message::Category(index, x)).width(Length::FillPortion(2)),
+            kind,
+            text_input("Quantity", &editing.quantity).on_input(move |x| Message::Quantity(index, x)).width(Length::FillPortion(1)),
+            price,
+            sales_tax,
+            pick_list(DefaultPayment::all(), Some(payment), move |x| Message::Payment(index, x)).width(Length::FillPortion(2)),
+            button("Up").on_press_maybe((index > 0).then_some(Message::MoveUp(index))),
+            button("Down").on_press_maybe((index + 1 < self.editing.len()).then_some(Message::MoveDown(index))),
+            button("Remove").on_press(Message::Remove(index)),
+        ].spacing(RULE_HEIGHT)
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let buttons = self.buttons();
+        let changed = buttons.as_ref().is_ok_and(|x| *x != self.saved);
+        let problem = buttons.err().map(|x| format!("{x:#}")).unwrap_or_default();
+        let headings = ["Key", "Label", "Category", "Kind or Item", "Quantity", "Price", "Sales Tax (%)", "Payment"]
+            .into_iter()
+            .zip([1, 3, 2, 3, 1, 1, 1, 2])
+            .fold(Row::new().spacing(RULE_HEIGHT), |row, (heading, portion)| {
+                row.push(text(heading).size(TEXT_SIZE).width(Length::FillPortion(portion)))
+            });
+        let rows = self.editing.iter().enumerate().fold(iced::widget::column![].spacing(RULE_HEIGHT), |column, (index, x)| {
+            column.push(self.editor(index, x))
+        });
+
+        iced::widget::column![
+            text("Quick Sales").size(HEADER_SIZE),
+            horizontal_rule(RULE_HEIGHT),
+            text("The buttons above the sales forms, in order. The first nine can also be rung up with the keys 1 to 9.").size(TEXT_SIZE),
+            row![
+                button("Add Button").on_press(Message::Add),
+                button("Save").on_press_maybe(changed.then_some(Message::Save)),
+                button("Undo Changes").on_press(Message::Revert),
+            ].spacing(RULE_HEIGHT),
+            text(if problem.is_empty() { self.status.clone() } else { problem }).size(TEXT_SIZE),
+            headings,
+            horizontal_rule(RULE_HEIGHT),
+            scrollable(rows).height(Length::Fill),
+        ].spacing(RULE_HEIGHT).padding(RULE_HEIGHT).into()
+    }
+}
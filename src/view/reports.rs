@@ -0,0 +1,188 @@
+use chrono::NaiveDate;
+use iced::alignment::Horizontal;
+use iced::{Element, Length};
+use iced::widget::{button, horizontal_rule, row, scrollable, text, Row};
+use strum::{Display, VariantArray};
+use crate::{HEADER_SIZE, RULE_HEIGHT, TEXT_SIZE};
+use crate::model::ledger::Ledger;
+use crate::report::drill_down::DrillDown;
+use crate::report::period::Period;
+use crate::view::adapters::ff;
+use crate::view::period_picker::{self, PeriodPicker};
+use crate::view::summary_dicts::{Metric, SummaryDicts};
+
+/// What the reports screen can save what it is showing as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, VariantArray, Display)]
+pub enum Format {
+    #[strum(serialize = "HTML")]
+    Html,
+    #[strum(serialize = "CSV")]
+    Csv,
+    #[strum(serialize = "Spreadsheet")]
+    Xlsx,
+}
+
+impl Format {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Html => "html",
+            Format::Csv => "csv",
+            Format::Xlsx => "xlsx",
+        }
+    }
+}
+
+/// A period's report, and the rows it was added up from so any number in it can be drilled into.
+#[derive(Debug, Clone)]
+pub struct ShownReport {
+    pub period: Period,
+    pub summary: SummaryDicts,
+    ledger: Ledger,
+}
+
+/// Shows the summary report for any period, and the sales behind any number in it.
+#[derive(Debug, Clone)]
+pub struct Reports {
+    period: PeriodPicker,
+    report: Option<ShownReport>,
+    drill_down: Option<DrillDown>,
+    status: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Period(period_picker::Message),
+    Show(Period),
+    DrillDown(&'static str, String, Metric),
+    BackToSummary,
+    Export(Format),
+}
+
+impl Reports {
+    pub fn new(today: NaiveDate) -> Self {
+        Self {
+            period: PeriodPicker::new(today),
+            report: None,
+            drill_down: None,
+            status: String::new(),
+        }
+    }
+
+    pub fn report(&self) -> Option<&ShownReport> {
+        self.report.as_ref()
+    }
+
+    /// The numbers being drilled into, if the summary isn't what is on screen.
+    pub fn drill_down(&self) -> Option<&DrillDown> {
+        self.drill_down.as_ref()
+    }
+
+    pub fn set_report(&mut self, period: Period, summary: SummaryDicts, ledger: Ledger) {
+        self.report = Some(ShownReport { period, summary, ledger });
+        self.drill_down = None;
+        self.status.clear();
+    }
+
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = status.into();
+    }
+
+    pub fn update(&mut self, message: Message) {
+        match message {
+            Message::Period(p) => self.period.update(p),
+            Message::DrillDown(section, name, metric) => {
+                let Some(report) = &self.report else { return; };
+                self.drill_down = Some(DrillDown::new(&report.summary, &report.ledger, report.period, section, name, metric));
+                self.status.clear();
+            }
+            Message::BackToSummary => {
+                self.drill_down = None;
+                self.status.clear();
+            }
+            Message::Show(_) | Message::Export(_) => {}
+        }
+    }
+
+    fn tile(section: &'static str, name: String, metric: Metric) -> Element<'static, Message> {
+        button(iced::widget::column![
+            text(name.clone()).align_x(Horizontal::Center),
+            text(metric.to_string()).size(TEXT_SIZE + 4).align_x(Horizontal::Center),
+        ].spacing(RULE_HEIGHT).align_x(Horizontal::Center).padding(RULE_HEIGHT))
+            .style(button::secondary)
+            .on_press(Message::DrillDown(section, name, metric))
+            .into()
+    }
+
+    fn summary(report: &ShownReport) -> Element<'_, Message> {
+        let mut sections = iced::widget::column![].spacing(RULE_HEIGHT);
+        for (heading, metrics) in report.summary.sections() {
+            let mut grid = iced::widget::column![].spacing(RULE_HEIGHT);
+            for chunk in metrics.chunks(5) {
+                grid = grid.push(chunk.iter().fold(Row::new().spacing(RULE_HEIGHT), |row, (name, metric)| {
+                    row.push(Self::tile(heading, name.clone(), *metric))
+                }));
+            }
+            sections = sections.push(text(heading).size(HEADER_SIZE)).push(horizontal_rule(RULE_HEIGHT)).push(grid);
+        }
+
+        sections.into()
+    }
+
+    fn cells<'a>(values: [String; 7]) -> Row<'a, Message> {
+        const PORTIONS: [u16; 7] = [3, 2, 4, 1, 2, 2, 2];
+        values.into_iter()
+            .zip(PORTIONS)
+            .fold(Row::new().spacing(RULE_HEIGHT), |row, (value, portion)| {
+                row.push(text(value).size(TEXT_SIZE).width(Length::FillPortion(portion)))
+            })
+    }
+
+    fn line_items(drill_down: &DrillDown) -> Element<'_, Message> {
+        let rows = drill_down.items.iter().fold(iced::widget::column![].spacing(RULE_HEIGHT), |column, x| {
+            column.push(Self::cells([
+                drill_down.timezone.clock(x.date_time).format("%Y-%m-%d %H:%M").to_string(),
+                x.kind.to_string(),
+                x.description.clone(),
+                x.quantity.to_string(),
+                x.payment_method.map(|x| x.to_string()).unwrap_or_default(),
+                ff("$", x.sales_tax),
+                ff("$", x.total),
+            ]))
+        });
+
+        iced::widget::column![
+            row![
+                button("Back to Summary").on_press(Message::BackToSummary),
+                text(format!("{}: {}", drill_down.title(), drill_down.metric)).size(HEADER_SIZE),
+            ].spacing(RULE_HEIGHT),
+            horizontal_rule(RULE_HEIGHT),
+            Self::cells(["Time", "Kind", "Description", "Quantity", "Payment Method", "Sales Tax", "Total"].map(String::from)),
+            horizontal_rule(RULE_HEIGHT),
+            rows,
+        ].spacing(RULE_HEIGHT).into()
+    }
+
+    pub fn view(&self, today: NaiveDate) -> Element<'_, Message> {
+        let exports = Format::VARIANTS.iter().fold(Row::new().spacing(RULE_HEIGHT), |row, format| {
+            row.push(button(text(format!("Export {format}"))).on_press_maybe(self.report.as_ref().map(|_| Message::Export(*format))))
+        });
+        let body = match (&self.report, &self.drill_down) {
+            (_, Some(drill_down)) => Self::line_items(drill_down),
+            (Some(report), None) => Self::summary(report),
+            (None, None) => text("Pick a period and press \"Show Report\".").size(TEXT_SIZE).into(),
+        };
+
+        iced::widget::column![
+            text("Reports").size(HEADER_SIZE),
+            horizontal_rule(RULE_HEIGHT),
+            row![
+                self.period.view().map(Message::Period),
+                button("Show Report").on_press_maybe(self.period.period(today).map(Message::Show)),
+                exports,
+            ].spacing(RULE_HEIGHT),
+            text(self.report.as_ref().map(|x| x.period.describe()).unwrap_or_default()).size(TEXT_SIZE),
+            text(&self.status).size(TEXT_SIZE),
+            scrollable(body).height(Length::Fill),
+        ].spacing(RULE_HEIGHT).padding(RULE_HEIGHT).into()
+    }
+}
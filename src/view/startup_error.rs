@@ -0,0 +1,41 @@
+use iced::{Element, Length, Task};
+use iced::widget::{button, column, container, text};
+use crate::{HEADER_SIZE, RULE_HEIGHT, TEXT_SIZE};
+
+/// Shown instead of the till when the settings or the database can't be opened, e.g. because of a typo in the settings
+/// or a newer version of the program has already migrated the database. Nothing can be sold until whatever it says is
+/// sorted out.
+pub struct StartupError {
+    title: &'static str,
+    message: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Close,
+}
+
+impl StartupError {
+    pub fn run(title: &'static str, error: anyhow::Error) -> iced::Result {
+        let message = format!("{error:#}");
+        iced::application("Museum Point Of Sale", Self::update, Self::view)
+            .run_with(move || (Self { title, message }, Task::none()))
+    }
+
+    fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Close => iced::exit(),
+        }
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        container(column![
+            text(self.title).size(HEADER_SIZE),
+            text(&self.message).size(TEXT_SIZE),
+            text("Nothing has been changed. Close the program and ask whoever looks after it for help.").size(TEXT_SIZE),
+            button("Close").on_press(Message::Close),
+        ].spacing(2 * RULE_HEIGHT).max_width(720))
+            .center(Length::Fill)
+            .into()
+    }
+}
@@ -1,85 +1,359 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
 use indexmap::IndexMap;
-use serde::Serialize;
-use crate::database::Database;
+use serde::{Serialize, Serializer};
+use crate::model::admission::Admission;
+use crate::model::date_time_wrapper::DateTimeWrapper;
+use crate::model::donation::Donation;
+use crate::model::gift_shop_sale::GiftShopSale;
 use crate::model::has_payment_method::HasPaymentMethod;
+use crate::model::membership::Membership;
 use crate::model::payment_method::PaymentMethod;
+use crate::model::refund::Refund;
 use crate::model::has_total_cost::HasTotalCost;
+use crate::model::ledger::Ledger;
+use crate::model::transaction_record::{TransactionKind, TransactionRecord};
+use crate::report::period::Period;
+use crate::settings::operating_hours::{HourBucket, OperatingHours};
 
-type Dictionary = IndexMap<&'static str, String>;
-#[derive(Debug, Clone, Serialize)]
+/// A single reported number, which knows whether it is an amount of money or a count of something.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metric {
+    Money(f32),
+    Count(u32),
+}
+
+impl Metric {
+    pub fn value(&self) -> f64 {
+        match self {
+            Metric::Money(m) => *m as f64,
+            Metric::Count(c) => *c as f64,
+        }
+    }
+}
+
+impl Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Metric::Money(m) => write!(f, "{}", crate::view::adapters::ff("$", *m)),
+            Metric::Count(c) => write!(f, "{c}"),
+        }
+    }
+}
+
+/// Templates only ever show metrics, so they serialize as their display text.
+impl Serialize for Metric {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+type Dictionary = IndexMap<&'static str, Metric>;
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct SummaryDicts {
     pub summary: Dictionary,
     pub payments: Dictionary,
     pub admissions: Dictionary,
     pub memberships: Dictionary,
+    pub attendance_by_hour: IndexMap<String, Metric>,
+    #[serde(skip)]
+    operating_hours: OperatingHours,
+    /// Visitors per bucket, kept in bucket order so `attendance_by_hour` can be rebuilt as buckets turn up.
+    #[serde(skip)]
+    buckets: BTreeMap<HourBucket, u32>,
 }
 
-fn total_money_by_payment_method(database: &Database, payment_method: PaymentMethod) -> f32 {
-    let mut total = 0.0;
-    database.daily_admissions().iter().filter(|x| x.matches_payment_method(payment_method)).for_each(|x| {
-        total += x.total_cost()
-    });
+type At = crate::model::admission::kind::Kind;
+type Mk = crate::model::membership::kind::Kind;
+
+const ADMISSION_KINDS: [(&str, At); 5] = [
+    ("Adults", At::Adult),
+    ("Seniors", At::Senior),
+    ("Children (6-12)", At::ChildUnderThirteen),
+    ("Children (Under 6)", At::ChildUnderSix),
+    ("PFSP Members", At::PfspMember),
+];
 
-    database.daily_memberships().iter().filter(|x| x.matches_payment_method(payment_method)).for_each(|x| {
-        total += x.total_cost()
-    });
+/// The summary line each kind of sale adds to, and the name its payments are listed under.
+const CATEGORIES: [(TransactionKind, &str, &str); 4] = [
+    (TransactionKind::Admission, "Admissions Revenue", "Admissions"),
+    (TransactionKind::Membership, "Membership Sales", "Memberships"),
+    (TransactionKind::Donation, "Total Donations", "Donations"),
+    (TransactionKind::GiftShopSale, "Gift Shop Sales", "Shop Sales"),
+];
 
-    database.daily_gift_shop_sales().iter().filter(|x| x.matches_payment_method(payment_method)).for_each(|x| {
-        total += x.total_cost()
-    });
+const MEMBERSHIP_KINDS: [(&str, Mk); 5] = [
+    ("Family", Mk::Family),
+    ("Individual", Mk::Individual),
+    ("Senior Family", Mk::SeniorFamily),
+    ("Senior Individual", Mk::SeniorIndividual),
+    ("Lifetime Member", Mk::LifetimeMember),
+];
 
-    database.daily_donations().iter().filter(|x| x.matches_payment_method(payment_method)).for_each(|x| {
-        total += x.total_cost()
-    });
+fn add_money(dictionary: &mut Dictionary, key: &str, amount: f32) {
+    if let Some(Metric::Money(total)) = dictionary.get_mut(key) {
+        *total += amount;
+    }
+}
 
-    total
+fn add_count(dictionary: &mut Dictionary, key: &str, count: u32) {
+    if let Some(Metric::Count(total)) = dictionary.get_mut(key) {
+        *total += count;
+    }
 }
 
 impl SummaryDicts {
-    pub fn new(database: &Database) -> Self {
-        use crate::view::adapters::*;
+    pub fn new(ledger: &Ledger, operating_hours: &OperatingHours, period: &Period) -> Self {
+        let mut dicts = Self::empty(operating_hours, period);
+        ledger.admissions.iter().for_each(|x| dicts.add_admission(x));
+        ledger.memberships.iter().for_each(|x| dicts.add_membership(x));
+        ledger.donations.iter().for_each(|x| dicts.add_donation(x));
+        ledger.gift_shop_sales.iter().for_each(|x| dicts.add_gift_shop_sale(x));
+        ledger.refunds.iter().for_each(|x| dicts.add_refund(x));
+        ledger.transactions.iter().for_each(|x| dicts.add_transaction(x));
+        dicts
+    }
 
-        type At = crate::model::admission::kind::Kind;
-        type Mk = crate::model::membership::kind::Kind;
-        type Pm = PaymentMethod;
+    /// Every metric at zero, with an empty bucket for each open hour in `period` so quiet hours still show up.
+    pub fn empty(operating_hours: &OperatingHours, period: &Period) -> Self {
+        let money = |key| (key, Metric::Money(0.0));
+        let count = |key| (key, Metric::Count(0));
 
-        Self {
+        let mut dicts = Self {
             summary: IndexMap::from([
-                ("Total Attendance", database.daily_admissions().iter().map(|x| x.quantity as u32).sum::<u32>().to_string()),
-                ("Admissions Revenue", ff("$", database.daily_admissions().total_cost())),
-                ("Total Donations", ff("$", database.daily_donations().total_cost())),
-                ("Membership Sales", ff("$",database.daily_memberships().total_cost())),
-                ("Gift Shop Sales", ff("$",database.daily_gift_shop_sales().total_cost())),
-                ("Sales Tax Collected", ff("$", database.daily_gift_shop_sales().iter().map(|x| x.compute_tax()).sum::<f32>())),
-                ("Total Daily Revenue", ff("$", database.daily_transactions().total_cost())),
+                count("Total Attendance"),
+                money("Admissions Revenue"),
+                money("Total Donations"),
+                money("Membership Sales"),
+                money("Gift Shop Sales"),
+                money("Sales Tax Collected"),
+                money("Refunds"),
+                money("Total Revenue"),
             ]),
             payments: IndexMap::from([
-                ("Cash - Admissions", ff("$", filter_by_payment_and_sum(database.daily_admissions(), Pm::Cash))),
-                ("Credit Card - Admissions", ff("$", filter_by_payment_and_sum(database.daily_admissions(), Pm::CreditCard))),
-                ("Free - Admissions", database.daily_admissions().len().to_string()),
-                ("Cash - Donations", ff("$", filter_by_payment_and_sum(database.daily_donations(), Pm::Cash))),
-                ("Credit Card - Donations", ff("$", filter_by_payment_and_sum(database.daily_donations(), Pm::CreditCard))),
-                ("Cash - Memberships", ff("$", filter_by_payment_and_sum(database.daily_memberships(), Pm::Cash))),
-                ("Credit Card - Memberships", ff("$", filter_by_payment_and_sum(database.daily_memberships(), Pm::CreditCard))),
-                ("Cash - Shop Sales", ff("$", filter_by_payment_and_sum(database.daily_gift_shop_sales(), Pm::Cash))),
-                ("Credit Card - Shop Sales", ff("$", filter_by_payment_and_sum(database.daily_gift_shop_sales(), Pm::CreditCard))),
-                ("Total Cash", ff("$", total_money_by_payment_method(database, Pm::Cash))),
-                ("Total Credit Card", ff("$", total_money_by_payment_method(database, Pm::CreditCard))),
-            ]),
-            admissions: IndexMap::from([
-                ("Adults", sum_over_admission_kind(database.daily_admissions(), At::Adult).to_string()),
-                ("Seniors", sum_over_admission_kind(database.daily_admissions(), At::Senior).to_string()),
-                ("Children (6-12)", sum_over_admission_kind(database.daily_admissions(), At::ChildUnderThirteen).to_string()),
-                ("Children (Under 6)", sum_over_admission_kind(database.daily_admissions(), At::ChildUnderSix).to_string()),
-                ("PFSP Members", sum_over_admission_kind(database.daily_admissions(), At::PfspMember).to_string()),
-            ]),
-            memberships: IndexMap::from([
-                ("Family", sum_over_membership_sale(database.daily_memberships(), Mk::Family).to_string()),
-                ("Individual", sum_over_membership_sale(database.daily_memberships(), Mk::Individual).to_string()),
-                ("Senior Family", sum_over_membership_sale(database.daily_memberships(), Mk::SeniorFamily).to_string()),
-                ("Senior Individual", sum_over_membership_sale(database.daily_memberships(), Mk::SeniorIndividual).to_string()),
-                ("Lifetime Member", sum_over_membership_sale(database.daily_memberships(), Mk::LifetimeMember).to_string()),
+                money("Cash - Admissions"),
+                money("Credit Card - Admissions"),
+                count("Free - Admissions"),
+                money("Cash - Donations"),
+                money("Credit Card - Donations"),
+                money("Cash - Memberships"),
+                money("Credit Card - Memberships"),
+                money("Cash - Shop Sales"),
+                money("Credit Card - Shop Sales"),
+                money("Total Cash"),
+                money("Total Credit Card"),
             ]),
+            admissions: ADMISSION_KINDS.iter().map(|(key, _)| count(*key)).collect(),
+            memberships: MEMBERSHIP_KINDS.iter().map(|(key, _)| count(*key)).collect(),
+            attendance_by_hour: IndexMap::new(),
+            operating_hours: operating_hours.clone(),
+            buckets: period.dates().flat_map(|x| operating_hours.buckets_on(x)).map(|x| (x, 0)).collect(),
+        };
+        dicts.rebuild_attendance_by_hour();
+        dicts
+    }
+
+    fn rebuild_attendance_by_hour(&mut self) {
+        self.attendance_by_hour = self.buckets.iter()
+            .map(|(bucket, visitors)| (bucket.to_string(), Metric::Count(*visitors)))
+            .collect();
+    }
+
+    /// Adds `amount` to `category`'s line for the payment method and to that method's total.
+    fn add_payment(&mut self, category: &str, payment_method: Option<PaymentMethod>, amount: f32) {
+        let Some(payment_method) = payment_method else {
+            return;
+        };
+        add_money(&mut self.payments, &format!("{payment_method} - {category}"), amount);
+        add_money(&mut self.payments, &format!("Total {payment_method}"), amount);
+    }
+
+    pub fn add_admission(&mut self, admission: &DateTimeWrapper<Admission>) {
+        let cost = admission.total_cost();
+        add_count(&mut self.summary, "Total Attendance", admission.quantity as u32);
+        add_money(&mut self.summary, "Admissions Revenue", cost);
+        self.add_payment("Admissions", admission.payment_method(), cost);
+        if admission.payment_method().is_none() {
+            add_count(&mut self.payments, "Free - Admissions", admission.quantity as u32);
+        }
+        if let Some((key, _)) = ADMISSION_KINDS.iter().find(|(_, kind)| *kind == admission.kind) {
+            add_count(&mut self.admissions, key, admission.quantity as u32);
+        }
+
+        let bucket = self.operating_hours.bucket(self.operating_hours.timezone.clock(admission.date_time()));
+        *self.buckets.entry(bucket).or_default() += admission.quantity as u32;
+        self.rebuild_attendance_by_hour();
+    }
+
+    pub fn add_membership(&mut self, membership: &DateTimeWrapper<Membership>) {
+        let cost = membership.total_cost();
+        add_money(&mut self.summary, "Membership Sales", cost);
+        self.add_payment("Memberships", membership.payment_method(), cost);
+        if let Some((key, _)) = MEMBERSHIP_KINDS.iter().find(|(_, kind)| membership.matches_type(*kind)) {
+            add_count(&mut self.memberships, key, membership.quantity as u32);
         }
     }
-}
\ No newline at end of file
+
+    pub fn add_donation(&mut self, donation: &DateTimeWrapper<Donation>) {
+        let cost = donation.total_cost();
+        add_money(&mut self.summary, "Total Donations", cost);
+        self.add_payment("Donations", donation.payment_method(), cost);
+    }
+
+    pub fn add_gift_shop_sale(&mut self, sale: &DateTimeWrapper<GiftShopSale>) {
+        let cost = sale.total_cost();
+        add_money(&mut self.summary, "Gift Shop Sales", cost);
+        add_money(&mut self.summary, "Sales Tax Collected", sale.compute_tax());
+        self.add_payment("Shop Sales", sale.payment_method(), cost);
+    }
+
+    /// Takes a refund back off the revenue, tax and payments its sale added to.
+    pub fn add_refund(&mut self, refund: &DateTimeWrapper<Refund>) {
+        add_money(&mut self.summary, "Refunds", refund.amount);
+        add_money(&mut self.summary, "Sales Tax Collected", -refund.sales_tax);
+        if let Some((_, revenue, category)) = CATEGORIES.iter().find(|(kind, _, _)| *kind == refund.kind) {
+            add_money(&mut self.summary, revenue, -refund.amount);
+            self.add_payment(category, refund.payment_method, -refund.amount);
+        }
+    }
+
+    pub fn add_transaction(&mut self, transaction: &DateTimeWrapper<TransactionRecord>) {
+        add_money(&mut self.summary, "Total Revenue", transaction.total_cost());
+    }
+
+    pub fn operating_hours(&self) -> &OperatingHours {
+        &self.operating_hours
+    }
+
+    /// Every section with the heading reports give it, in report order.
+    pub fn sections(&self) -> Vec<(&'static str, Vec<(String, Metric)>)> {
+        let section = |dictionary: &Dictionary| dictionary.iter().map(|(k, v)| (k.to_string(), *v)).collect();
+        vec![
+            ("Summary", section(&self.summary)),
+            ("Payments Breakdown", section(&self.payments)),
+            ("Admission Breakdown", section(&self.admissions)),
+            ("Membership Sales Breakdown", section(&self.memberships)),
+            ("Attendance by Hour", self.attendance_by_hour.iter().map(|(k, v)| (k.clone(), *v)).collect()),
+        ]
+    }
+
+    /// The rows in `ledger` that the metric called `name` in `section` was added up from. Total revenue, and anything
+    /// not known here, is made of every row.
+    pub fn rows_behind(&self, ledger: &Ledger, section: &str, name: &str) -> Ledger {
+        let admissions = |keep: &dyn Fn(&DateTimeWrapper<Admission>) -> bool| Ledger {
+            admissions: ledger.admissions.iter().filter(|x| keep(x)).cloned().collect(),
+            ..Default::default()
+        };
+
+        let refunds = |kind| ledger.refunds.iter().filter(|x| x.kind == kind).cloned().collect();
+
+        match (section, name) {
+            ("Summary", "Total Attendance") => admissions(&|_| true),
+            ("Summary", "Admissions Revenue") => Ledger { refunds: refunds(TransactionKind::Admission), ..admissions(&|_| true) },
+            ("Summary", "Total Donations") => Ledger {
+                donations: ledger.donations.clone(),
+                refunds: refunds(TransactionKind::Donation),
+                ..Default::default()
+            },
+            ("Summary", "Membership Sales") => Ledger {
+                memberships: ledger.memberships.clone(),
+                refunds: refunds(TransactionKind::Membership),
+                ..Default::default()
+            },
+            ("Summary", "Gift Shop Sales" | "Sales Tax Collected") => Ledger {
+                gift_shop_sales: ledger.gift_shop_sales.clone(),
+                refunds: refunds(TransactionKind::GiftShopSale),
+                ..Default::default()
+            },
+            ("Summary", "Refunds") => Ledger { refunds: ledger.refunds.clone(), ..Default::default() },
+            ("Payments Breakdown", "Free - Admissions") => admissions(&|x| x.payment_method().is_none()),
+            ("Payments Breakdown", name) => Self::paid_with(ledger, name),
+            ("Admission Breakdown", name) => match ADMISSION_KINDS.iter().find(|(key, _)| *key == name) {
+                Some((_, kind)) => admissions(&|x| x.kind == *kind),
+                None => Ledger::default(),
+            },
+            ("Membership Sales Breakdown", name) => match MEMBERSHIP_KINDS.iter().find(|(key, _)| *key == name) {
+                Some((_, kind)) => Ledger {
+                    memberships: ledger.memberships.iter().filter(|x| x.matches_type(*kind)).cloned().collect(),
+                    ..Default::default()
+                },
+                None => Ledger::default(),
+            },
+            ("Attendance by Hour", name) => admissions(&|x| self.operating_hours.bucket(self.operating_hours.timezone.clock(x.date_time())).to_string() == name),
+            _ => ledger.clone(),
+        }
+    }
+
+    /// Rows behind a payments line, either "Total Cash" or one category's, like "Cash - Shop Sales".
+    fn paid_with(ledger: &Ledger, name: &str) -> Ledger {
+        let (method, category) = match name.strip_prefix("Total ") {
+            Some(method) => (method, None),
+            None => match name.split_once(" - ") {
+                Some((method, category)) => (method, Some(category)),
+                None => return Ledger::default(),
+            },
+        };
+        let Ok(method) = method.parse::<PaymentMethod>() else {
+            return Ledger::default();
+        };
+
+        fn paid<T: HasPaymentMethod + Clone>(rows: &[DateTimeWrapper<T>], method: PaymentMethod, included: bool) -> Vec<DateTimeWrapper<T>> {
+            if !included {
+                return vec![];
+            }
+            rows.iter().filter(|x| x.payment_method() == Some(method)).cloned().collect()
+        }
+        let included = |x: &str| category.is_none_or(|category| category == x);
+        let refunded = |kind| CATEGORIES.iter().any(|(x, _, name)| *x == kind && included(name));
+
+        Ledger {
+            admissions: paid(&ledger.admissions, method, included("Admissions")),
+            memberships: paid(&ledger.memberships, method, included("Memberships")),
+            donations: paid(&ledger.donations, method, included("Donations")),
+            gift_shop_sales: paid(&ledger.gift_shop_sales, method, included("Shop Sales")),
+            transactions: vec![],
+            refunds: paid(&ledger.refunds, method, true).into_iter().filter(|x| refunded(x.kind)).collect(),
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use crate::model::admission::kind::Kind;
+    use crate::model::date_time_wrapper::WrapInDateTime;
+    use crate::model::line_item::LineItem;
+    use crate::model::membership;
+    use super::*;
+
+    #[test]
+    fn adding_rows_one_at_a_time() {
+        let mut dicts = SummaryDicts::empty(&OperatingHours::default(), &Period::day(OperatingHours::default().today()));
+        dicts.add_admission(&Admission::new(Kind::Adult, Some(PaymentMethod::Cash), 2).wrapped_in_date_time());
+        dicts.add_admission(&Admission::new(Kind::ChildUnderSix, None, 3).wrapped_in_date_time());
+        dicts.add_membership(&Membership::new(membership::kind::Kind::Family, PaymentMethod::CreditCard, 1).wrapped_in_date_time());
+        dicts.add_gift_shop_sale(&GiftShopSale::new("Mug".to_string(), 10.0, PaymentMethod::Cash, 1, 5.0).wrapped_in_date_time());
+
+        assert_eq!(dicts.summary["Total Attendance"], Metric::Count(5));
+        assert_eq!(dicts.summary["Sales Tax Collected"], Metric::Money(0.5));
+        assert_eq!(dicts.payments["Free - Admissions"], Metric::Count(3));
+        assert_eq!(dicts.payments["Total Cash"], Metric::Money(26.5));
+        assert_eq!(dicts.payments["Total Credit Card"], Metric::Money(40.0));
+        assert_eq!(dicts.admissions["Children (Under 6)"], Metric::Count(3));
+        assert_eq!(dicts.memberships["Family"], Metric::Count(1));
+        let visitors: u32 = dicts.attendance_by_hour.values().map(|x| x.value() as u32).sum();
+        assert_eq!(visitors, 5);
+    }
+
+    #[test]
+    fn refunds_come_off_what_they_were_paid_for() {
+        let mut dicts = SummaryDicts::empty(&OperatingHours::default(), &Period::day(OperatingHours::default().today()));
+        let mug = GiftShopSale::new("Mug".to_string(), 10.0, PaymentMethod::Cash, 1, 5.0).wrapped_in_date_time();
+        dicts.add_gift_shop_sale(&mug);
+        let line = LineItem::from_ledger(&Ledger { gift_shop_sales: vec![mug], ..Default::default() }).remove(0);
+        dicts.add_refund(&Refund::of(&line, 1).wrapped_in_date_time());
+
+        assert_eq!(dicts.summary["Refunds"], Metric::Money(10.5));
+        assert_eq!(dicts.summary["Gift Shop Sales"], Metric::Money(0.0));
+        assert_eq!(dicts.summary["Sales Tax Collected"], Metric::Money(0.0));
+        assert_eq!(dicts.payments["Total Cash"], Metric::Money(0.0));
+    }
+}